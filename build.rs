@@ -0,0 +1,42 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Dialects with a directory of migrations in "migrations".
+const DIALECTS: &[&str] = &["postgres", "mysql", "sqlite"];
+
+/// Writes "<dialect>_migrations.rs" to `OUT_DIR` for every dialect, listing the names of its
+/// migrations in order for `embed_dialect_migrations!` (see `database::sql::migrations`).
+fn main() -> io::Result<()> {
+    println!("cargo:rerun-if-changed=migrations");
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR must be set");
+    for dialect in DIALECTS {
+        let dir = Path::new("migrations").join(dialect);
+        println!("cargo:rerun-if-changed={}", dir.display());
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                names.push(
+                    entry
+                        .file_name()
+                        .into_string()
+                        .expect("non-UTF-8 migration name"),
+                );
+            }
+        }
+        names.sort();
+
+        let mut code = format!("embed_dialect_migrations!({:?}, [\n", dialect);
+        for name in names {
+            code.push_str(&format!("    {:?},\n", name));
+        }
+        code.push_str("])\n");
+        fs::write(
+            Path::new(&out_dir).join(format!("{}_migrations.rs", dialect)),
+            code,
+        )?;
+    }
+    Ok(())
+}
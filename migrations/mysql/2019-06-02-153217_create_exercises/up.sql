@@ -0,0 +1,8 @@
+CREATE TABLE exercises (
+    id VARCHAR(36) PRIMARY KEY,
+    title VARCHAR(255) NOT NULL,
+    body TEXT NOT NULL,
+    topic VARCHAR(255),
+    created_on DATETIME(6) NOT NULL,
+    modified_on DATETIME(6) NOT NULL
+)
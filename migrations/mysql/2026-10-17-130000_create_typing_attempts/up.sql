@@ -0,0 +1,14 @@
+CREATE TABLE typing_attempts (
+    id VARCHAR(36) PRIMARY KEY,
+    exercise_id VARCHAR(36) NOT NULL,
+    started_on DATETIME(6) NOT NULL,
+    finished_on DATETIME(6) NOT NULL,
+    typed_text TEXT NOT NULL,
+    keystrokes INTEGER NOT NULL,
+    errors INTEGER NOT NULL,
+    wpm DOUBLE NOT NULL,
+    accuracy DOUBLE NOT NULL,
+    FOREIGN KEY (exercise_id) REFERENCES exercises (id) ON DELETE CASCADE
+);
+
+CREATE INDEX typing_attempts_exercise_id_idx ON typing_attempts (exercise_id, started_on);
@@ -0,0 +1,11 @@
+ALTER TABLE typing_attempts RENAME COLUMN wpm TO gross_wpm;
+ALTER TABLE typing_attempts ADD COLUMN net_wpm DOUBLE NOT NULL DEFAULT 0;
+ALTER TABLE typing_attempts ADD COLUMN corrected_errors INTEGER NOT NULL DEFAULT 0;
+-- Comma-separated character positions, e.g. "2,13".
+-- NOTE: MySQL does not support literal defaults for TEXT columns.
+ALTER TABLE typing_attempts ADD COLUMN error_positions TEXT;
+UPDATE typing_attempts SET error_positions = '';
+ALTER TABLE typing_attempts MODIFY error_positions TEXT NOT NULL;
+
+-- NOTE: Earlier attempts were not scored from a keystroke log, so their net WPM is unknown.
+UPDATE typing_attempts SET net_wpm = gross_wpm;
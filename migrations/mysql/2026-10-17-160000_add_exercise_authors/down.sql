@@ -0,0 +1,2 @@
+ALTER TABLE exercises DROP FOREIGN KEY exercises_author_id_fk;
+ALTER TABLE exercises DROP COLUMN author_id;
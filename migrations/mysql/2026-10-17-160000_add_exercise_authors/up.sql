@@ -0,0 +1,5 @@
+-- NOTE: Exercises created before users existed have no author.
+ALTER TABLE exercises ADD COLUMN author_id VARCHAR(36);
+ALTER TABLE exercises
+    ADD CONSTRAINT exercises_author_id_fk FOREIGN KEY (author_id) REFERENCES users (id)
+    ON DELETE SET NULL;
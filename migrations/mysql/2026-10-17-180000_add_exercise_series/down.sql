@@ -0,0 +1,3 @@
+DROP INDEX exercises_series_part_idx ON exercises;
+ALTER TABLE exercises DROP COLUMN part_index;
+ALTER TABLE exercises DROP COLUMN series_id;
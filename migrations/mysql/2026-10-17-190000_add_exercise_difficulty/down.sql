@@ -0,0 +1,2 @@
+DROP INDEX exercises_difficulty_idx ON exercises;
+ALTER TABLE exercises DROP COLUMN difficulty;
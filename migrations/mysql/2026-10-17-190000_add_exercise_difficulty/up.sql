@@ -0,0 +1,4 @@
+-- NOTE: Computed by the server from the body (see `difficulty::difficulty`). Exercises created
+-- before this migration are rated when the server starts.
+ALTER TABLE exercises ADD COLUMN difficulty INTEGER NULL;
+CREATE INDEX exercises_difficulty_idx ON exercises (difficulty);
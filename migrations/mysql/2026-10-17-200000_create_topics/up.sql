@@ -0,0 +1,24 @@
+-- Topics form a tree of categories, each exercise belonging to any number of topics.
+CREATE TABLE topics (
+    id VARCHAR(36) PRIMARY KEY,
+    parent_id VARCHAR(36) NULL,
+    slug VARCHAR(255) NOT NULL UNIQUE,
+    name VARCHAR(255) NOT NULL,
+    description TEXT NULL,
+    created_on DATETIME(6) NOT NULL,
+    FOREIGN KEY (parent_id) REFERENCES topics (id)
+);
+
+CREATE TABLE exercise_topics (
+    exercise_id VARCHAR(36) NOT NULL,
+    topic_id VARCHAR(36) NOT NULL,
+    PRIMARY KEY (exercise_id, topic_id),
+    FOREIGN KEY (exercise_id) REFERENCES exercises (id) ON DELETE CASCADE,
+    FOREIGN KEY (topic_id) REFERENCES topics (id) ON DELETE CASCADE
+);
+
+CREATE INDEX exercise_topics_topic_id_idx ON exercise_topics (topic_id);
+
+-- NOTE: The free-text topics of existing exercises are converted into top-level topics by the
+-- migration runner (see `migrations::run_migration`), since slugs are derived from topics by
+-- `models::slugify`, which SQL cannot express portably.
@@ -0,0 +1,16 @@
+-- Free-form tags of exercises (e.g. "numbers-heavy" or "quotes"), created when first used.
+CREATE TABLE tags (
+    id VARCHAR(36) PRIMARY KEY,
+    name VARCHAR(255) NOT NULL UNIQUE,
+    created_on DATETIME(6) NOT NULL
+);
+
+CREATE TABLE exercise_tags (
+    exercise_id VARCHAR(36) NOT NULL,
+    tag_id VARCHAR(36) NOT NULL,
+    PRIMARY KEY (exercise_id, tag_id),
+    FOREIGN KEY (exercise_id) REFERENCES exercises (id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags (id) ON DELETE CASCADE
+);
+
+CREATE INDEX exercise_tags_tag_id_idx ON exercise_tags (tag_id);
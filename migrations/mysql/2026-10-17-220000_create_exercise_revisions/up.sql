@@ -0,0 +1,23 @@
+-- Snapshots of the content of every version of an exercise, written when it is created or
+-- updated.
+CREATE TABLE exercise_revisions (
+    id VARCHAR(36) PRIMARY KEY,
+    exercise_id VARCHAR(36) NOT NULL,
+    version INTEGER NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    body TEXT NOT NULL,
+    topic VARCHAR(255),
+    author_id VARCHAR(36),
+    created_on DATETIME(6) NOT NULL,
+    UNIQUE (exercise_id, version),
+    FOREIGN KEY (exercise_id) REFERENCES exercises (id) ON DELETE CASCADE,
+    FOREIGN KEY (author_id) REFERENCES users (id) ON DELETE SET NULL
+);
+
+-- NOTE: Earlier versions of existing exercises are lost, so only their current version is kept.
+-- Who made it is only known for exercises that were never updated.
+INSERT INTO exercise_revisions
+    (id, exercise_id, version, title, body, topic, author_id, created_on)
+SELECT UUID(), id, version, title, body, topic,
+    CASE WHEN version = 1 THEN author_id END, modified_on
+FROM exercises;
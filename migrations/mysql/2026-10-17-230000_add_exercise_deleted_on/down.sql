@@ -0,0 +1,2 @@
+DROP INDEX exercises_deleted_on_idx ON exercises;
+ALTER TABLE exercises DROP COLUMN deleted_on;
@@ -0,0 +1 @@
+DROP INDEX exercises_search_idx
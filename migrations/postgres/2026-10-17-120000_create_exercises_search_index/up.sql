@@ -0,0 +1,7 @@
+-- Full-text search over exercise titles (weighted higher) and bodies.
+--
+-- NOTE: Queries must use the exact same expression to make use of the index.
+CREATE INDEX exercises_search_idx ON exercises USING GIN ((
+    setweight(to_tsvector('english', title), 'A') ||
+    setweight(to_tsvector('english', body), 'B')
+))
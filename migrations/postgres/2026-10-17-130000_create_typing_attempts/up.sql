@@ -0,0 +1,13 @@
+CREATE TABLE typing_attempts (
+    id VARCHAR PRIMARY KEY,
+    exercise_id VARCHAR NOT NULL REFERENCES exercises (id) ON DELETE CASCADE,
+    started_on TIMESTAMP NOT NULL,
+    finished_on TIMESTAMP NOT NULL,
+    typed_text TEXT NOT NULL,
+    keystrokes INTEGER NOT NULL,
+    errors INTEGER NOT NULL,
+    wpm DOUBLE PRECISION NOT NULL,
+    accuracy DOUBLE PRECISION NOT NULL
+);
+
+CREATE INDEX typing_attempts_exercise_id_idx ON typing_attempts (exercise_id, started_on);
@@ -0,0 +1,4 @@
+ALTER TABLE typing_attempts DROP COLUMN error_positions;
+ALTER TABLE typing_attempts DROP COLUMN corrected_errors;
+ALTER TABLE typing_attempts DROP COLUMN net_wpm;
+ALTER TABLE typing_attempts RENAME COLUMN gross_wpm TO wpm;
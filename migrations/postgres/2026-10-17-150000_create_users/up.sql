@@ -0,0 +1,7 @@
+CREATE TABLE users (
+    id VARCHAR PRIMARY KEY,
+    username VARCHAR NOT NULL UNIQUE,
+    password_hash VARCHAR NOT NULL,
+    role VARCHAR NOT NULL DEFAULT 'user',
+    created_on TIMESTAMP NOT NULL
+);
@@ -0,0 +1,2 @@
+-- Incremented by every update, for optimistic concurrency control.
+ALTER TABLE exercises ADD COLUMN version INTEGER NOT NULL DEFAULT 1
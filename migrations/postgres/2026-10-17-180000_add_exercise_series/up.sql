@@ -0,0 +1,4 @@
+-- Exercises split from one long text are parts of a series, numbered from 0.
+ALTER TABLE exercises ADD COLUMN series_id VARCHAR(36) NULL;
+ALTER TABLE exercises ADD COLUMN part_index INTEGER NULL;
+CREATE UNIQUE INDEX exercises_series_part_idx ON exercises (series_id, part_index);
@@ -0,0 +1,2 @@
+DROP INDEX exercises_difficulty_idx;
+ALTER TABLE exercises DROP COLUMN difficulty;
@@ -0,0 +1,2 @@
+DROP TABLE exercise_topics;
+DROP TABLE topics;
@@ -0,0 +1,2 @@
+DROP TABLE exercise_tags;
+DROP TABLE tags;
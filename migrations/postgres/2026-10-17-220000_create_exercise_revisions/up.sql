@@ -0,0 +1,21 @@
+-- Snapshots of the content of every version of an exercise, written when it is created or
+-- updated.
+CREATE TABLE exercise_revisions (
+    id VARCHAR PRIMARY KEY,
+    exercise_id VARCHAR NOT NULL REFERENCES exercises (id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    title VARCHAR NOT NULL,
+    body TEXT NOT NULL,
+    topic VARCHAR,
+    author_id VARCHAR REFERENCES users (id) ON DELETE SET NULL,
+    created_on TIMESTAMP NOT NULL,
+    UNIQUE (exercise_id, version)
+);
+
+-- NOTE: Earlier versions of existing exercises are lost, so only their current version is kept.
+-- Who made it is only known for exercises that were never updated.
+INSERT INTO exercise_revisions
+    (id, exercise_id, version, title, body, topic, author_id, created_on)
+SELECT CAST(CAST(md5(id || '/' || version) AS UUID) AS VARCHAR), id, version, title, body, topic,
+    CASE WHEN version = 1 THEN author_id END, modified_on
+FROM exercises;
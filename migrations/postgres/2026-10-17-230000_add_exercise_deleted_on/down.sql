@@ -0,0 +1,2 @@
+DROP INDEX exercises_deleted_on_idx;
+ALTER TABLE exercises DROP COLUMN deleted_on;
@@ -0,0 +1,4 @@
+DROP TRIGGER exercises_fts_delete;
+DROP TRIGGER exercises_fts_update;
+DROP TRIGGER exercises_fts_insert;
+DROP TABLE exercises_fts;
@@ -0,0 +1,25 @@
+-- Full-text search over exercise titles and bodies.
+--
+-- The FTS5 table keeps its own copy of each title and body, synchronized with `exercises` by the
+-- triggers below.
+CREATE VIRTUAL TABLE exercises_fts USING fts5(
+    id UNINDEXED,
+    title,
+    body,
+    tokenize = 'porter unicode61'
+);
+
+INSERT INTO exercises_fts (id, title, body) SELECT id, title, body FROM exercises;
+
+CREATE TRIGGER exercises_fts_insert AFTER INSERT ON exercises BEGIN
+    INSERT INTO exercises_fts (id, title, body) VALUES (new.id, new.title, new.body);
+END;
+
+CREATE TRIGGER exercises_fts_update AFTER UPDATE OF id, title, body ON exercises BEGIN
+    DELETE FROM exercises_fts WHERE id = old.id;
+    INSERT INTO exercises_fts (id, title, body) VALUES (new.id, new.title, new.body);
+END;
+
+CREATE TRIGGER exercises_fts_delete AFTER DELETE ON exercises BEGIN
+    DELETE FROM exercises_fts WHERE id = old.id;
+END;
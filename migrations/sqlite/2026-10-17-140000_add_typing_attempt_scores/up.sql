@@ -0,0 +1,8 @@
+ALTER TABLE typing_attempts RENAME COLUMN wpm TO gross_wpm;
+ALTER TABLE typing_attempts ADD COLUMN net_wpm DOUBLE PRECISION NOT NULL DEFAULT 0;
+ALTER TABLE typing_attempts ADD COLUMN corrected_errors INTEGER NOT NULL DEFAULT 0;
+-- Comma-separated character positions, e.g. "2,13".
+ALTER TABLE typing_attempts ADD COLUMN error_positions TEXT NOT NULL DEFAULT '';
+
+-- NOTE: Earlier attempts were not scored from a keystroke log, so their net WPM is unknown.
+UPDATE typing_attempts SET net_wpm = gross_wpm;
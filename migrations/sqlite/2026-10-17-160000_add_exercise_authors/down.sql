@@ -0,0 +1,34 @@
+-- NOTE: The table is rebuilt without the column, since SQLite refuses to drop columns used in a
+-- foreign key constraint (and any column before version 3.35). Dropping the table drops its
+-- full-text search triggers as well, which are recreated below. Run with foreign key enforcement
+-- off (as by default), so that dropping the table does not cascade to typing attempts.
+DROP INDEX exercises_author_id_idx;
+
+CREATE TABLE exercises_new (
+    id VARCHAR PRIMARY KEY,
+    title VARCHAR NOT NULL,
+    body TEXT NOT NULL,
+    topic VARCHAR,
+    created_on TIMESTAMP NOT NULL,
+    modified_on TIMESTAMP NOT NULL
+);
+
+INSERT INTO exercises_new (id, title, body, topic, created_on, modified_on)
+SELECT id, title, body, topic, created_on, modified_on FROM exercises;
+
+DROP TABLE exercises;
+
+ALTER TABLE exercises_new RENAME TO exercises;
+
+CREATE TRIGGER exercises_fts_insert AFTER INSERT ON exercises BEGIN
+    INSERT INTO exercises_fts (id, title, body) VALUES (new.id, new.title, new.body);
+END;
+
+CREATE TRIGGER exercises_fts_update AFTER UPDATE OF id, title, body ON exercises BEGIN
+    DELETE FROM exercises_fts WHERE id = old.id;
+    INSERT INTO exercises_fts (id, title, body) VALUES (new.id, new.title, new.body);
+END;
+
+CREATE TRIGGER exercises_fts_delete AFTER DELETE ON exercises BEGIN
+    DELETE FROM exercises_fts WHERE id = old.id;
+END;
@@ -0,0 +1,4 @@
+-- NOTE: Exercises created before users existed have no author.
+ALTER TABLE exercises ADD COLUMN author_id VARCHAR REFERENCES users (id) ON DELETE SET NULL;
+
+CREATE INDEX exercises_author_id_idx ON exercises (author_id);
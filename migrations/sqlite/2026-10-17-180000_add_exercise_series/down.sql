@@ -0,0 +1,3 @@
+DROP INDEX exercises_series_part_idx;
+ALTER TABLE exercises DROP COLUMN part_index;
+ALTER TABLE exercises DROP COLUMN series_id;
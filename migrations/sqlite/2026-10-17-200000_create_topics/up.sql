@@ -0,0 +1,23 @@
+-- Topics form a tree of categories, each exercise belonging to any number of topics.
+CREATE TABLE topics (
+    id VARCHAR PRIMARY KEY,
+    parent_id VARCHAR NULL REFERENCES topics (id),
+    slug VARCHAR NOT NULL UNIQUE,
+    name VARCHAR NOT NULL,
+    description TEXT NULL,
+    created_on TIMESTAMP NOT NULL
+);
+
+CREATE INDEX topics_parent_id_idx ON topics (parent_id);
+
+CREATE TABLE exercise_topics (
+    exercise_id VARCHAR NOT NULL REFERENCES exercises (id) ON DELETE CASCADE,
+    topic_id VARCHAR NOT NULL REFERENCES topics (id) ON DELETE CASCADE,
+    PRIMARY KEY (exercise_id, topic_id)
+);
+
+CREATE INDEX exercise_topics_topic_id_idx ON exercise_topics (topic_id);
+
+-- NOTE: The free-text topics of existing exercises are converted into top-level topics by the
+-- migration runner (see `migrations::run_migration`), since slugs are derived from topics by
+-- `models::slugify`, which SQL cannot express portably.
@@ -0,0 +1,14 @@
+-- Free-form tags of exercises (e.g. "numbers-heavy" or "quotes"), created when first used.
+CREATE TABLE tags (
+    id VARCHAR PRIMARY KEY,
+    name VARCHAR NOT NULL UNIQUE,
+    created_on TIMESTAMP NOT NULL
+);
+
+CREATE TABLE exercise_tags (
+    exercise_id VARCHAR NOT NULL REFERENCES exercises (id) ON DELETE CASCADE,
+    tag_id VARCHAR NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+    PRIMARY KEY (exercise_id, tag_id)
+);
+
+CREATE INDEX exercise_tags_tag_id_idx ON exercise_tags (tag_id);
@@ -0,0 +1,4 @@
+-- NOTE: Deleted exercises are kept in the trash (with the time they were deleted) until they are
+-- restored or purged.
+ALTER TABLE exercises ADD COLUMN deleted_on TIMESTAMP NULL;
+CREATE INDEX exercises_deleted_on_idx ON exercises (deleted_on);
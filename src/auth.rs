@@ -0,0 +1,342 @@
+use crate::database;
+use crate::database::IntoDatabaseError;
+use crate::models::{Role, User};
+
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// Error type returned when a caller cannot be authenticated.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The username or password is wrong.
+    InvalidCredentials,
+
+    /// The token is malformed, was not signed by this server, or is of the wrong kind.
+    InvalidToken,
+
+    /// The token has expired.
+    ExpiredToken,
+
+    /// The password could not be hashed or the token could not be signed.
+    Internal(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidCredentials => write!(f, "Invalid username or password"),
+            Error::InvalidToken => write!(f, "Invalid token"),
+            Error::ExpiredToken => write!(f, "Token expired"),
+            Error::Internal(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl IntoDatabaseError for Error {
+    fn into_database_error(self) -> database::Error {
+        match self {
+            Error::Internal(e) => database::Error::ServerError(Some(e)),
+            e => database::Error::Unauthorized(e.to_string()),
+        }
+    }
+}
+
+impl From<jsonwebtoken::errors::Error> for Error {
+    fn from(e: jsonwebtoken::errors::Error) -> Error {
+        match e.kind() {
+            ErrorKind::ExpiredSignature => Error::ExpiredToken,
+            _ => Error::InvalidToken,
+        }
+    }
+}
+
+/// Hashes a password with Argon2id and a random salt, returning the encoded hash (which includes
+/// the salt and parameters).
+pub fn hash_password(password: &str) -> Result<String, Error> {
+    // NOTE: A v4 UUID consists of (122) random bits from the OS random number generator, which
+    // makes for a fine salt.
+    let salt = uuid::Uuid::new_v4();
+    let config = argon2::Config {
+        variant: argon2::Variant::Argon2id,
+        ..argon2::Config::default()
+    };
+    argon2::hash_encoded(password.as_bytes(), salt.as_bytes(), &config)
+        .map_err(|e| Error::Internal(format!("Error hashing password: {}", e)))
+}
+
+/// Returns whether `password` matches a hash returned by `hash_password`.
+pub fn verify_password(password_hash: &str, password: &str) -> bool {
+    argon2::verify_encoded(password_hash, password.as_bytes()).unwrap_or(false)
+}
+
+/// Hash of a random password, with the parameters of `hash_password`.
+const DUMMY_PASSWORD_HASH: &str =
+    "$argon2id$v=19$m=4096,t=3,p=1$vSzjdgjtR3OLS5uzpubbBg$Ppfs+DjcnT42uBnzJQZpviDEuJgGPAeS9qfQYwCXZPM";
+
+/// Verifies `password` against a dummy hash, taking as long as `verify_password`.
+///
+/// Call it when there is no hash to verify against (e.g. for an unknown username), so that the
+/// time taken to reject credentials does not reveal which usernames exist.
+pub fn verify_dummy_password(password: &str) {
+    verify_password(DUMMY_PASSWORD_HASH, password);
+}
+
+/// Kinds of tokens issued by a `TokenSigner`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenKind {
+    /// Short-lived token authenticating requests (as `Authorization: Bearer <token>`).
+    Access,
+
+    /// Long-lived token which can only be exchanged for a new pair of tokens.
+    Refresh,
+}
+
+/// Claims of a token issued by a `TokenSigner`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Claims {
+    /// Id of the user the token was issued to.
+    pub sub: String,
+
+    /// Role of the user at the time the token was issued.
+    pub role: String,
+
+    /// Kind of token.
+    pub kind: TokenKind,
+
+    /// Time the token was issued at (seconds since the Unix epoch).
+    pub iat: i64,
+
+    /// Time the token expires at (seconds since the Unix epoch).
+    pub exp: i64,
+}
+
+/// An authenticated user making a request.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Caller {
+    /// Id of the user.
+    pub user_id: String,
+
+    /// Role of the user (as of when the access token was issued).
+    pub role: Role,
+}
+
+/// A pair of tokens issued on login or refresh.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenPair {
+    /// Token authenticating requests.
+    pub access_token: String,
+
+    /// Token to exchange for a new pair of tokens once the access token expires.
+    pub refresh_token: String,
+
+    /// Number of seconds until the access token expires.
+    pub expires_in: i64,
+}
+
+/// Issues and verifies tokens signed with a shared secret (as HS256 [JSON Web Tokens]).
+///
+/// Tokens are stateless: they stay valid until they expire, even if the user is deleted or
+/// changes their password.
+///
+/// [JSON Web Tokens]: https://tools.ietf.org/html/rfc7519
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use wikitype_api::auth::{self, TokenKind, TokenSigner};
+/// use wikitype_api::models::{Role, User};
+///
+/// let signer = TokenSigner::new(b"secret", Duration::from_secs(900), Duration::from_secs(3600));
+/// let user = User {
+///     id: String::from("3f1c5b0e-6f1d-4f55-9a4c-0d2b9f1f6a11"),
+///     username: String::from("albatross"),
+///     password_hash: auth::hash_password("password").unwrap(),
+///     role: Role::User,
+///     created_on: chrono::Utc::now().naive_utc(),
+/// };
+///
+/// let tokens = signer.issue(&user).unwrap();
+/// assert_eq!(tokens.expires_in, 900);
+///
+/// let caller = signer.authenticate(&tokens.access_token).unwrap();
+/// assert_eq!(caller.user_id, user.id);
+/// assert_eq!(caller.role, Role::User);
+///
+/// // Refresh tokens do not authenticate requests.
+/// assert_eq!(
+///     signer.authenticate(&tokens.refresh_token),
+///     Err(auth::Error::InvalidToken)
+/// );
+/// let claims = signer.verify(&tokens.refresh_token, TokenKind::Refresh).unwrap();
+/// assert_eq!(claims.sub, user.id);
+/// ```
+#[derive(Clone)]
+pub struct TokenSigner {
+    secret: Vec<u8>,
+    access_token_lifetime: Duration,
+    refresh_token_lifetime: Duration,
+}
+
+impl TokenSigner {
+    /// Creates a signer with the given secret and token lifetimes.
+    pub fn new(
+        secret: &[u8],
+        access_token_lifetime: Duration,
+        refresh_token_lifetime: Duration,
+    ) -> TokenSigner {
+        TokenSigner {
+            secret: secret.to_vec(),
+            access_token_lifetime,
+            refresh_token_lifetime,
+        }
+    }
+
+    /// Issues a new access and refresh token to `user`.
+    pub fn issue(&self, user: &User) -> Result<TokenPair, Error> {
+        let now = chrono::Utc::now().timestamp();
+        Ok(TokenPair {
+            access_token: self.sign(user, TokenKind::Access, now)?,
+            refresh_token: self.sign(user, TokenKind::Refresh, now)?,
+            expires_in: self.access_token_lifetime.as_secs() as i64,
+        })
+    }
+
+    /// Verifies the signature, expiry and kind of `token`, returning its claims.
+    pub fn verify(&self, token: &str, kind: TokenKind) -> Result<Claims, Error> {
+        let data = jsonwebtoken::decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(&self.secret),
+            &Validation::default(),
+        )?;
+        if data.claims.kind != kind {
+            return Err(Error::InvalidToken);
+        }
+        Ok(data.claims)
+    }
+
+    /// Verifies an access token, returning the caller it was issued to.
+    pub fn authenticate(&self, access_token: &str) -> Result<Caller, Error> {
+        let claims = self.verify(access_token, TokenKind::Access)?;
+        let role = claims.role.parse().map_err(|_| Error::InvalidToken)?;
+        Ok(Caller {
+            user_id: claims.sub,
+            role,
+        })
+    }
+
+    /// Signs a token of the given kind for `user`, issued at `issued_at` (seconds since the Unix
+    /// epoch).
+    fn sign(&self, user: &User, kind: TokenKind, issued_at: i64) -> Result<String, Error> {
+        let lifetime = match kind {
+            TokenKind::Access => self.access_token_lifetime,
+            TokenKind::Refresh => self.refresh_token_lifetime,
+        };
+        let claims = Claims {
+            sub: user.id.clone(),
+            role: user.role.as_str().to_string(),
+            kind,
+            iat: issued_at,
+            exp: issued_at + lifetime.as_secs() as i64,
+        };
+        jsonwebtoken::encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(&self.secret),
+        )
+        .map_err(|e| Error::Internal(format!("Error signing token: {}", e)))
+    }
+}
+
+impl fmt::Debug for TokenSigner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // NOTE: Never print the secret.
+        f.debug_struct("TokenSigner")
+            .field("access_token_lifetime", &self.access_token_lifetime)
+            .field("refresh_token_lifetime", &self.refresh_token_lifetime)
+            .finish()
+    }
+}
+
+/// Extracts the token from the value of an `Authorization: Bearer <token>` header.
+pub fn bearer_token(authorization: &str) -> Result<&str, Error> {
+    let mut parts = authorization.trim().splitn(2, ' ');
+    match (parts.next(), parts.next()) {
+        (Some(scheme), Some(token)) if scheme.eq_ignore_ascii_case("bearer") => Ok(token.trim()),
+        _ => Err(Error::InvalidToken),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn user(role: Role) -> User {
+        User {
+            id: String::from("3f1c5b0e-6f1d-4f55-9a4c-0d2b9f1f6a11"),
+            username: String::from("albatross"),
+            password_hash: String::new(),
+            role,
+            created_on: NaiveDateTime::from_timestamp(0, 0),
+        }
+    }
+
+    /// Test that only the hashed password verifies, and that hashes are salted.
+    #[test]
+    fn hash_passwords() {
+        let hash = hash_password("hunter2").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password(&hash, "hunter2"));
+        assert!(!verify_password(&hash, "hunter3"));
+        assert!(!verify_password("not a hash", "hunter2"));
+        assert_ne!(hash_password("hunter2").unwrap(), hash);
+    }
+
+    /// Test that the dummy hash has the parameters of `hash_password`, so that verifying against it
+    /// takes as long.
+    #[test]
+    fn verify_dummy_passwords() {
+        let parameters = |hash: &str| hash.rsplitn(3, '$').nth(2).map(String::from);
+        let hash = hash_password("hunter2").unwrap();
+        assert_eq!(parameters(DUMMY_PASSWORD_HASH), parameters(&hash));
+    }
+
+    /// Test that expired, tampered and foreign tokens are rejected.
+    #[test]
+    fn reject_invalid_tokens() {
+        let lifetime = Duration::from_secs(60);
+        let signer = TokenSigner::new(b"secret", lifetime, lifetime);
+        let admin = user(Role::Admin);
+
+        let token = signer.sign(&admin, TokenKind::Access, 0).unwrap();
+        assert_eq!(signer.authenticate(&token), Err(Error::ExpiredToken));
+
+        let tokens = signer.issue(&admin).unwrap();
+        assert_eq!(
+            signer.authenticate(&tokens.access_token).unwrap().role,
+            Role::Admin
+        );
+        let other = TokenSigner::new(b"other secret", lifetime, lifetime);
+        assert_eq!(
+            other.authenticate(&tokens.access_token),
+            Err(Error::InvalidToken)
+        );
+        let tampered = format!("{}x", tokens.access_token);
+        assert_eq!(signer.authenticate(&tampered), Err(Error::InvalidToken));
+        assert_eq!(signer.authenticate("garbage"), Err(Error::InvalidToken));
+    }
+
+    /// Test parsing `Authorization` headers.
+    #[test]
+    fn parse_bearer_tokens() {
+        assert_eq!(bearer_token("Bearer abc.def.ghi"), Ok("abc.def.ghi"));
+        assert_eq!(bearer_token("bearer  abc "), Ok("abc"));
+        assert_eq!(bearer_token("Basic dXNlcjpwYXNz"), Err(Error::InvalidToken));
+        assert_eq!(bearer_token("abc"), Err(Error::InvalidToken));
+    }
+}
@@ -0,0 +1,133 @@
+extern crate log;
+
+use wikitype_api::config::Config;
+use wikitype_api::database::sql::pool::Pool;
+use wikitype_api::import::{self, ImportOptions, Progress};
+
+use dotenv::dotenv;
+use std::path::PathBuf;
+use std::process;
+
+const USAGE: &str = "\
+Imports the articles of a MediaWiki XML dump (e.g. enwiki-latest-pages-articles.xml.bz2) as
+exercises into the database at DATABASE_URL.
+
+USAGE:
+    wikitype-import [OPTIONS] <DUMP>
+
+OPTIONS:
+    --progress <FILE>        File recording the progress of the import
+                             (default: <DUMP>.progress). An interrupted import resumes from it.
+    --max-pages <N>          Import at most N articles.
+    --max-passages <N>       Import at most N passages per article (default: 5).
+    --min-length <N>         Minimum number of characters per passage (default: 200).
+    --max-length <N>         Maximum number of characters per passage (default: 800).
+    --normalize <STEPS>      Text normalization steps, comma-separated, \"all\" or \"none\"
+                             (default: all). See TEXT_NORMALIZATION.
+    -h, --help               Print this message.";
+
+/// Command line arguments.
+struct Args {
+    dump: PathBuf,
+    progress: PathBuf,
+    options: ImportOptions,
+}
+
+/// Parses the command line arguments, exiting with the usage on errors.
+fn parse_args() -> Args {
+    let fail = |message: &str| -> ! {
+        eprintln!("{}\n\n{}", message, USAGE);
+        process::exit(2)
+    };
+    let mut dump = None;
+    let mut progress = None;
+    let mut options = ImportOptions::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut number = |name: &str| -> usize {
+            args.next()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_else(|| fail(&format!("{} expects a number", name)))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            "--progress" => {
+                progress = Some(PathBuf::from(
+                    args.next()
+                        .unwrap_or_else(|| fail("--progress expects a file")),
+                ))
+            }
+            "--max-pages" => options.max_pages = Some(number("--max-pages")),
+            "--max-passages" => options.max_passages_per_page = number("--max-passages"),
+            "--min-length" => options.min_length = number("--min-length"),
+            "--max-length" => options.max_length = number("--max-length"),
+            "--normalize" => {
+                options.normalization = args
+                    .next()
+                    .ok_or_else(|| String::from("--normalize expects steps"))
+                    .and_then(|steps| steps.parse())
+                    .unwrap_or_else(|e| fail(&e))
+            }
+            _ if arg.starts_with('-') => fail(&format!("Unknown option {}", arg)),
+            _ if dump.is_none() => dump = Some(PathBuf::from(arg)),
+            _ => fail("Expected a single dump"),
+        }
+    }
+    let dump = dump.unwrap_or_else(|| fail("Missing dump"));
+    let progress = progress.unwrap_or_else(|| {
+        let mut progress = dump.clone().into_os_string();
+        progress.push(".progress");
+        PathBuf::from(progress)
+    });
+    if options.min_length > options.max_length {
+        fail("--min-length must not exceed --max-length");
+    }
+    Args {
+        dump,
+        progress,
+        options,
+    }
+}
+
+fn main() {
+    dotenv().ok();
+
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var("RUST_LOG", "info");
+    }
+    env_logger::init();
+
+    let mut args = parse_args();
+    let config = Config::from_env().unwrap_or_else(|e| panic!("{}", e));
+    args.options.limits = config.exercise_limits;
+    let pool =
+        Pool::new(&config).unwrap_or_else(|e| panic!("Error creating the connection pool: {}", e));
+    let conn = pool
+        .get()
+        .unwrap_or_else(|e| panic!("Error connecting to the database: {}", e));
+
+    let mut progress = Progress::load(&args.progress)
+        .unwrap_or_else(|e| panic!("Error loading {}: {}", args.progress.display(), e));
+    if let Some(page_id) = progress.last_page_id() {
+        log::info!("Resuming after page {}", page_id);
+    }
+    let pages = import::dump::open(&args.dump)
+        .unwrap_or_else(|e| panic!("Error opening {}: {}", args.dump.display(), e));
+
+    match import::import(&*conn, pages, &args.options, &mut progress) {
+        Ok(summary) => log::info!(
+            "Created {} exercises from {} articles ({} pages read, {} exercises failed)",
+            summary.exercises_created,
+            summary.pages_imported,
+            summary.pages_read,
+            summary.exercises_failed
+        ),
+        Err(e) => {
+            log::error!("Error importing {}: {}", args.dump.display(), e);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::text::NormalizeOptions;
+use crate::validation::{self, ExerciseLimits};
+
+/// Default maximum number of pooled database connections.
+const DEFAULT_POOL_MAX_SIZE: u32 = 10;
+
+/// Default number of seconds to wait for a pooled connection before giving up.
+const DEFAULT_POOL_CONNECTION_TIMEOUT_SECS: u64 = 30;
+
+/// Default number of seconds after which an idle pooled connection is closed.
+const DEFAULT_POOL_IDLE_TIMEOUT_SECS: u64 = 600;
+
+/// Default number of seconds after which an access token expires.
+const DEFAULT_ACCESS_TOKEN_LIFETIME_SECS: u64 = 15 * 60;
+
+/// Default number of seconds after which a refresh token expires.
+const DEFAULT_REFRESH_TOKEN_LIFETIME_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// Default number of seconds after which a trashed exercise is purged.
+const DEFAULT_TRASH_RETENTION_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// Error type returned when the configuration cannot be read.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// A required environment variable is not set.
+    Missing(&'static str),
+
+    /// An environment variable is set, but its value could not be parsed.
+    Invalid(&'static str, String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Missing(key) => write!(f, "{} must be set", key),
+            Error::Invalid(key, value) => write!(f, "{} has an invalid value: {:?}", key, value),
+        }
+    }
+}
+
+/// Server configuration.
+///
+/// A `Config` is read once at startup (usually via `Config::from_env`) and shared by every request
+/// through `graphql::Context`.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use wikitype_api::config::Config;
+///
+/// let config = Config::new("postgres://localhost/wikitype");
+///
+/// assert_eq!(config.database_url, "postgres://localhost/wikitype");
+/// assert_eq!(config.pool_max_size, 10);
+/// assert_eq!(config.pool_connection_timeout, Duration::from_secs(30));
+/// assert!(!config.run_migrations);
+/// assert_eq!(config.token_secret, None);
+/// assert_eq!(config.access_token_lifetime, Duration::from_secs(900));
+/// assert_eq!(config.text_normalization.to_string(), "citations,nfc,quotes,dashes,ellipses,whitespace");
+/// assert_eq!(config.trash_retention, Some(Duration::from_secs(30 * 24 * 60 * 60)));
+/// assert_eq!(config.exercise_limits.max_body_length, 20_000);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    /// URL of the database to connect to (`DATABASE_URL`).
+    pub database_url: String,
+
+    /// Maximum number of connections managed by the connection pool (`DATABASE_POOL_MAX_SIZE`).
+    pub pool_max_size: u32,
+
+    /// Minimum number of idle connections kept open by the connection pool
+    /// (`DATABASE_POOL_MIN_IDLE`).
+    ///
+    /// If `None`, the pool keeps `pool_max_size` idle connections.
+    pub pool_min_idle: Option<u32>,
+
+    /// Time to wait for a pooled connection before failing a request
+    /// (`DATABASE_POOL_CONNECTION_TIMEOUT`, in seconds).
+    pub pool_connection_timeout: Duration,
+
+    /// Time after which an idle pooled connection is closed (`DATABASE_POOL_IDLE_TIMEOUT`, in
+    /// seconds).
+    ///
+    /// If `None` (i.e. the variable is set to `0`), idle connections are never closed.
+    pub pool_idle_timeout: Option<Duration>,
+
+    /// Whether to run pending migrations against the database at startup
+    /// (`DATABASE_RUN_MIGRATIONS`, `true` or `false`).
+    ///
+    /// If `false`, the server refuses to start while migrations are pending.
+    pub run_migrations: bool,
+
+    /// Secret with which access and refresh tokens are signed (`AUTH_TOKEN_SECRET`).
+    ///
+    /// If `None`, a random secret is generated at startup, so tokens do not survive a restart
+    /// (and are not accepted by other instances of the server).
+    pub token_secret: Option<String>,
+
+    /// Time after which an access token expires (`AUTH_ACCESS_TOKEN_LIFETIME`, in seconds).
+    pub access_token_lifetime: Duration,
+
+    /// Time after which a refresh token expires (`AUTH_REFRESH_TOKEN_LIFETIME`, in seconds).
+    pub refresh_token_lifetime: Duration,
+
+    /// Normalization applied to the bodies of created and updated exercises
+    /// (`TEXT_NORMALIZATION`, a comma-separated list of steps, `all` or `none`).
+    ///
+    /// See `text::NormalizeOptions`.
+    pub text_normalization: NormalizeOptions,
+
+    /// Time after which exercises in the trash are purged (`TRASH_RETENTION`, in seconds).
+    ///
+    /// If `None` (i.e. the variable is set to `0`), trashed exercises are kept until they are
+    /// purged explicitly.
+    pub trash_retention: Option<Duration>,
+
+    /// Limits on the fields of created and updated exercises (`EXERCISE_MAX_TITLE_LENGTH`,
+    /// `EXERCISE_MAX_BODY_LENGTH` and `EXERCISE_MAX_TOPIC_LENGTH`, in characters).
+    ///
+    /// See `validation::ExerciseValidator`.
+    pub exercise_limits: ExerciseLimits,
+}
+
+impl Config {
+    /// Creates a configuration for the given database with default pool settings.
+    pub fn new(database_url: &str) -> Config {
+        Config {
+            database_url: String::from(database_url),
+            pool_max_size: DEFAULT_POOL_MAX_SIZE,
+            pool_min_idle: None,
+            pool_connection_timeout: Duration::from_secs(DEFAULT_POOL_CONNECTION_TIMEOUT_SECS),
+            pool_idle_timeout: Some(Duration::from_secs(DEFAULT_POOL_IDLE_TIMEOUT_SECS)),
+            run_migrations: false,
+            token_secret: None,
+            access_token_lifetime: Duration::from_secs(DEFAULT_ACCESS_TOKEN_LIFETIME_SECS),
+            refresh_token_lifetime: Duration::from_secs(DEFAULT_REFRESH_TOKEN_LIFETIME_SECS),
+            text_normalization: NormalizeOptions::default(),
+            trash_retention: Some(Duration::from_secs(DEFAULT_TRASH_RETENTION_SECS)),
+            exercise_limits: ExerciseLimits::default(),
+        }
+    }
+
+    /// Reads the configuration from environment variables.
+    ///
+    /// `DATABASE_URL` is required. All other variables are optional and fall back to the defaults
+    /// of `Config::new`.
+    pub fn from_env() -> Result<Config, Error> {
+        Config::from_vars(&|key| env::var(key).ok())
+    }
+
+    /// Reads the configuration from the variables returned by `var` (see `Config::from_env`).
+    fn from_vars(var: Vars) -> Result<Config, Error> {
+        let database_url = var("DATABASE_URL").ok_or(Error::Missing("DATABASE_URL"))?;
+        let mut config = Config::new(&database_url);
+
+        if let Some(max_size) = parse_var::<u32>(var, "DATABASE_POOL_MAX_SIZE")? {
+            if max_size == 0 {
+                return Err(Error::Invalid(
+                    "DATABASE_POOL_MAX_SIZE",
+                    max_size.to_string(),
+                ));
+            }
+            config.pool_max_size = max_size;
+        }
+        if let Some(min_idle) = parse_var::<u32>(var, "DATABASE_POOL_MIN_IDLE")? {
+            if min_idle > config.pool_max_size {
+                return Err(Error::Invalid(
+                    "DATABASE_POOL_MIN_IDLE",
+                    min_idle.to_string(),
+                ));
+            }
+            config.pool_min_idle = Some(min_idle);
+        }
+        if let Some(secs) = parse_var::<u64>(var, "DATABASE_POOL_CONNECTION_TIMEOUT")? {
+            if secs == 0 {
+                return Err(Error::Invalid(
+                    "DATABASE_POOL_CONNECTION_TIMEOUT",
+                    secs.to_string(),
+                ));
+            }
+            config.pool_connection_timeout = Duration::from_secs(secs);
+        }
+        if let Some(secs) = parse_var(var, "DATABASE_POOL_IDLE_TIMEOUT")? {
+            config.pool_idle_timeout = match secs {
+                0 => None,
+                secs => Some(Duration::from_secs(secs)),
+            };
+        }
+        if let Some(run_migrations) = parse_var(var, "DATABASE_RUN_MIGRATIONS")? {
+            config.run_migrations = run_migrations;
+        }
+        if let Some(secret) = var("AUTH_TOKEN_SECRET") {
+            if secret.is_empty() {
+                return Err(Error::Invalid("AUTH_TOKEN_SECRET", secret));
+            }
+            config.token_secret = Some(secret);
+        }
+        if let Some(secs) = parse_var::<u64>(var, "AUTH_ACCESS_TOKEN_LIFETIME")? {
+            if secs == 0 {
+                return Err(Error::Invalid(
+                    "AUTH_ACCESS_TOKEN_LIFETIME",
+                    secs.to_string(),
+                ));
+            }
+            config.access_token_lifetime = Duration::from_secs(secs);
+        }
+        if let Some(secs) = parse_var::<u64>(var, "AUTH_REFRESH_TOKEN_LIFETIME")? {
+            if secs == 0 {
+                return Err(Error::Invalid(
+                    "AUTH_REFRESH_TOKEN_LIFETIME",
+                    secs.to_string(),
+                ));
+            }
+            config.refresh_token_lifetime = Duration::from_secs(secs);
+        }
+        if let Some(text_normalization) = parse_var(var, "TEXT_NORMALIZATION")? {
+            config.text_normalization = text_normalization;
+        }
+        if let Some(secs) = parse_var(var, "TRASH_RETENTION")? {
+            config.trash_retention = match secs {
+                0 => None,
+                secs => Some(Duration::from_secs(secs)),
+            };
+        }
+        if let Some(length) = parse_length(
+            var,
+            "EXERCISE_MAX_TITLE_LENGTH",
+            validation::MAX_COLUMN_LENGTH,
+        )? {
+            config.exercise_limits.max_title_length = length;
+        }
+        if let Some(length) = parse_length(var, "EXERCISE_MAX_BODY_LENGTH", usize::MAX)? {
+            config.exercise_limits.max_body_length = length;
+        }
+        if let Some(length) = parse_length(
+            var,
+            "EXERCISE_MAX_TOPIC_LENGTH",
+            validation::MAX_COLUMN_LENGTH,
+        )? {
+            config.exercise_limits.max_topic_length = length;
+        }
+
+        Ok(config)
+    }
+}
+
+/// Looks up the value of a configuration variable, if set.
+type Vars<'a> = &'a dyn Fn(&str) -> Option<String>;
+
+/// Parses the variable `key` as a length between 1 and `max`, if set.
+fn parse_length(var: Vars, key: &'static str, max: usize) -> Result<Option<usize>, Error> {
+    match parse_var::<usize>(var, key)? {
+        Some(length) if length == 0 || length > max => Err(Error::Invalid(key, length.to_string())),
+        length => Ok(length),
+    }
+}
+
+/// Parses the variable `key`, if set.
+fn parse_var<T: FromStr>(var: Vars, key: &'static str) -> Result<Option<T>, Error> {
+    match var(key) {
+        Some(value) => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| Error::Invalid(key, value)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Reads a configuration from the given variables.
+    fn read_config(vars: &[(&str, &str)]) -> Result<Config, Error> {
+        let vars: HashMap<&str, &str> = vars.iter().cloned().collect();
+        Config::from_vars(&|key| vars.get(key).map(|value| String::from(*value)))
+    }
+
+    /// Test that the pool may not keep more idle connections than it may hold.
+    #[test]
+    fn reject_more_idle_connections_than_pool_size() {
+        let url = ("DATABASE_URL", ":memory:");
+        let config = read_config(&[url, ("DATABASE_POOL_MIN_IDLE", "10")]).unwrap();
+        assert_eq!(config.pool_min_idle, Some(10));
+        assert_eq!(
+            read_config(&[url, ("DATABASE_POOL_MIN_IDLE", "11")]),
+            Err(Error::Invalid("DATABASE_POOL_MIN_IDLE", String::from("11")))
+        );
+        assert_eq!(
+            read_config(&[
+                url,
+                ("DATABASE_POOL_MAX_SIZE", "2"),
+                ("DATABASE_POOL_MIN_IDLE", "3"),
+            ]),
+            Err(Error::Invalid("DATABASE_POOL_MIN_IDLE", String::from("3")))
+        );
+        assert_eq!(read_config(&[]), Err(Error::Missing("DATABASE_URL")));
+    }
+}
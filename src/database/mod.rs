@@ -1,10 +1,24 @@
-use crate::models::{Exercise, NewExercise, UpdatedExercise};
+use crate::models::{
+    BuilderError, Exercise, ExerciseRevision, NewExercise, NewTopic, NewTypingAttempt, NewUser,
+    Tag, Topic, TypingAttempt, UpdatedExercise, User,
+};
+use crate::validation;
+use query::{AttemptQuery, ExerciseQuery};
 
+use chrono::NaiveDateTime;
+use diesel::migration::RunMigrationsError;
+use diesel::r2d2::PoolError;
 use diesel::result::ConnectionError as DieselConnectionError;
 use diesel::result::Error as DieselError;
 use std::fmt;
 use std::result;
 
+/// Database-agnostic queries for listing resources.
+pub mod query;
+
+/// Portable full-text search for backends without native support.
+pub mod search;
+
 /// SQL schemas and DAO implementations.
 pub mod sql;
 
@@ -23,6 +37,18 @@ pub enum Error {
     /// An error occurred serializing the data being sent to the database.
     SerializationError(String),
 
+    /// The caller could not be authenticated (e.g. due to wrong credentials or an expired token).
+    Unauthorized(String),
+
+    /// The caller is authenticated, but may not perform the requested operation.
+    Forbidden(String),
+
+    /// The resource was modified concurrently (e.g. an update expected an older version).
+    Conflict(String),
+
+    /// Fields of the input failed validation (see `validation::ExerciseValidator`).
+    ValidationError(Vec<validation::FieldError>),
+
     /// A catchall error for general server errors.
     ServerError(Option<String>),
 }
@@ -50,6 +76,14 @@ impl IntoDatabaseError for DieselError {
     }
 }
 
+// NOTE: Allows `?` on diesel results in functions returning `database::Result`, e.g. in
+// transactions.
+impl From<DieselError> for Error {
+    fn from(e: DieselError) -> Error {
+        e.into_database_error()
+    }
+}
+
 impl IntoDatabaseError for DieselConnectionError {
     fn into_database_error(self) -> Error {
         match self {
@@ -58,6 +92,34 @@ impl IntoDatabaseError for DieselConnectionError {
     }
 }
 
+impl IntoDatabaseError for PoolError {
+    fn into_database_error(self) -> Error {
+        Error::ServerError(Some(self.to_string()))
+    }
+}
+
+impl IntoDatabaseError for RunMigrationsError {
+    fn into_database_error(self) -> Error {
+        match self {
+            RunMigrationsError::QueryError(e) => e.into_database_error(),
+            e => Error::ServerError(Some(e.to_string())),
+        }
+    }
+}
+
+impl IntoDatabaseError for BuilderError {
+    fn into_database_error(self) -> Error {
+        Error::QueryError(self.to_string())
+    }
+}
+
+// NOTE: Allows `?` on builder results in functions returning `database::Result`.
+impl From<BuilderError> for Error {
+    fn from(e: BuilderError) -> Error {
+        e.into_database_error()
+    }
+}
+
 /// Result type returned by databases-related functions.
 pub type Result<T> = result::Result<T, Error>;
 
@@ -71,18 +133,148 @@ pub trait FindById<ID, R> {
     fn find_by_id(&self, id: ID) -> Result<R>;
 }
 
+/// Generic find-by-unique-name operation.
+pub trait FindByName<N, R> {
+    fn find_by_name(&self, name: N) -> Result<R>;
+}
+
+/// Generic listing operation over the ordered collection selected by a query.
+///
+/// Together, `count` and `list` allow callers to page through a collection without loading it
+/// all at once.
+pub trait List<Q, R> {
+    /// Returns the total number of items matching `query`.
+    fn count(&self, query: Q) -> Result<i64>;
+
+    /// Returns at most `limit` items matching `query`, in order, skipping the first `offset`
+    /// items.
+    fn list(&self, query: Q, offset: i64, limit: i64) -> Result<Vec<R>>;
+}
+
+/// A resource matching a search, along with its relevance.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SearchHit<R> {
+    /// The matching resource.
+    pub item: R,
+
+    /// Relevance of the resource (higher is more relevant).
+    ///
+    /// Ranks are only comparable within the results of one search on one backend.
+    pub rank: f64,
+
+    /// Excerpt of the resource with the matching terms highlighted between `<b>` and `</b>`.
+    pub snippet: String,
+}
+
+/// Generic full-text search operation.
+pub trait Search<Q, R> {
+    /// Returns at most `limit` resources matching `query`, from most to least relevant.
+    fn search(&self, query: Q, limit: i64) -> Result<Vec<SearchHit<R>>>;
+}
+
 /// Generic update operation.
 pub trait Update<T, R> {
     fn update(&self, obj: T) -> Result<R>;
 }
 
 /// Generic delete operation.
+///
+/// Resources with a `Trash` are moved to the trash rather than deleted.
 pub trait DeleteById<ID, R> {
     fn delete_by_id(&self, id: ID) -> Result<R>;
 }
 
+/// Generic trash of deleted resources.
+///
+/// Trashed resources are hidden from `FindById` and (by default) from `List`, and can be restored
+/// until they are purged.
+pub trait Trash<ID, R> {
+    /// Returns the trashed resource with the given id. Fails with `Error::NotFound` unless the
+    /// resource is trashed.
+    fn find_trashed(&self, id: ID) -> Result<R>;
+
+    /// Moves the trashed resource with the given id out of the trash, returning it. Fails with
+    /// `Error::NotFound` unless the resource is trashed.
+    fn restore(&self, id: ID) -> Result<R>;
+
+    /// Permanently deletes the resource with the given id, whether it is trashed or not,
+    /// returning it.
+    fn purge(&self, id: ID) -> Result<R>;
+
+    /// Permanently deletes every resource trashed before `deleted_before`, returning the number of
+    /// purged resources.
+    fn purge_trashed(&self, deleted_before: NaiveDateTime) -> Result<usize>;
+}
+
+// NOTE: Batch operations run in a single transaction, but each item is written in its own nested
+// transaction (savepoint). The result of each item is returned in order, so that failing items
+// neither abort the batch nor leave partial writes behind.
+
+/// Generic batch create operation.
+pub trait CreateBatch<T, R> {
+    fn create_batch(&self, objs: T) -> Result<Vec<Result<R>>>;
+}
+
+/// Generic batch update operation.
+pub trait UpdateBatch<T, R> {
+    fn update_batch(&self, objs: T) -> Result<Vec<Result<R>>>;
+}
+
+/// Generic batch delete operation.
+pub trait DeleteByIds<IDS, R> {
+    fn delete_by_ids(&self, ids: IDS) -> Result<Vec<Result<R>>>;
+}
+
+/// Generic all-or-nothing batch create operation.
+///
+/// Unlike `CreateBatch`, the first failing item aborts the transaction, so either every item is
+/// created or none is.
+pub trait CreateAll<T, R> {
+    fn create_all(&self, objs: T) -> Result<Vec<R>>;
+}
+
+/// Rating of the difficulty of stored resources.
+pub trait RateDifficulty {
+    /// Stores the difficulty of every resource that has not been rated yet (e.g. since it was
+    /// created before difficulties were introduced), returning the number of rated resources.
+    fn rate_unrated(&self) -> Result<usize>;
+}
+
+/// Generic many-to-many links between exercises and other resources.
+///
+/// Resources are identified by id, or by name for resources created on demand (e.g. tags).
+/// Linked resources are returned sorted by name. Linking or unlinking resources of an unknown
+/// exercise fails with `Error::NotFound`.
+pub trait ExerciseLinks<R> {
+    /// Returns the resources linked to the exercise with id `exercise_id`.
+    fn linked(&self, exercise_id: &str) -> Result<Vec<R>>;
+
+    /// Links the resources identified by `ids` to the exercise (ignoring those already linked),
+    /// returning every resource linked to the exercise.
+    fn link(&self, exercise_id: &str, ids: &[&str]) -> Result<Vec<R>>;
+
+    /// Unlinks the resources identified by `ids` from the exercise (ignoring those not linked),
+    /// returning every resource still linked to the exercise.
+    fn unlink(&self, exercise_id: &str, ids: &[&str]) -> Result<Vec<R>>;
+}
+
+/// Generic listing of every resource of a collection, along with the number of exercises linked
+/// to each resource.
+pub trait CountExercises<R> {
+    fn count_exercises(&self) -> Result<Vec<(R, i64)>>;
+}
+
+/// Generic listing of the revisions of an exercise.
+pub trait ExerciseRevisions<R> {
+    /// Returns the revisions of the exercise with id `exercise_id`, from newest to oldest.
+    fn revisions(&self, exercise_id: &str) -> Result<Vec<R>>;
+}
+
 /// A [data access object] for exercises.
 ///
+/// Deleted exercises are moved to the trash (see `Trash`), keeping their typing attempts and
+/// revisions until they are purged.
+///
 /// Current implementors include
 /// - `diesel::PgConnection`
 /// - `diesel::MysqlConnection`
@@ -94,6 +286,8 @@ pub trait DeleteById<ID, R> {
 /// # Examples
 ///
 /// ```
+/// use database::sql::migrations;
+/// use database::sql::pool::Dialect;
 /// use database::ExerciseDao;
 /// use diesel::prelude::*;
 /// use diesel::r2d2;
@@ -108,24 +302,22 @@ pub trait DeleteById<ID, R> {
 ///      procellariids, storm petrels, and diving petrels in the order Procellariiformes (the \
 ///      tubenoses).";
 ///
-/// // Connect to a Postgres database.
-/// //
-/// // NOTE: This database should already contain the `exercises` table. Otherwise, run the
-/// // migrations against the database.
-/// //
-/// // i.e.
-/// //     $ diesel migration run
+/// // Connect to a Postgres database and run any pending migrations against it.
 /// dotenv().ok();
 ///
 /// let database_url = env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
-/// let dao: &dyn ExerciseDao = &PgConnection::establish(&database_url)
+/// let conn = PgConnection::establish(&database_url)
 ///     .expect(&format!("Error connecting to database {}.", database_url));
+/// migrations::run_pending_migrations(&conn, Dialect::Postgres).unwrap();
+///
+/// let dao: &dyn ExerciseDao = &conn;
 ///
 /// // Create a new exercise.
 /// let new_exercise = NewExerciseBuilder::new()
 ///     .title("Albatross")
 ///     .body(ALBATROSS_BODY)
-///     .build();
+///     .build()
+///     .unwrap();
 ///
 /// // Insert the new exercise into the database.
 /// let exercise = dao
@@ -140,7 +332,8 @@ pub trait DeleteById<ID, R> {
 /// let updated_exercise = UpdatedExerciseBuilder::new(&exercise.id)
 ///     .title("Albatross new")
 ///     .topic(Some("It's a topic!"))
-///     .build();
+///     .build()
+///     .unwrap();
 ///
 /// // Update the exercise.
 /// let exercise = dao
@@ -171,11 +364,17 @@ pub trait DeleteById<ID, R> {
 ///     assert_eq!(jh.join().unwrap(), Ok(exercise.clone()));
 /// }
 ///
-/// // Delete the exercise.
+/// // Move the exercise to the trash, hiding it.
 /// let deleted_exercise = dao
 ///     .delete_by_id(&exercise.id)
-///     .expect("Failed to create Albatross exercise.");
-/// assert_eq!(exercise, deleted_exercise);
+///     .expect("Failed to delete Albatross exercise.");
+/// assert_eq!(exercise.id, deleted_exercise.id);
+/// assert!(deleted_exercise.deleted_on.is_some());
+/// assert_eq!(dao.find_by_id(&exercise.id), Err(database::Error::NotFound));
+///
+/// // Restore the exercise, then delete it for good.
+/// assert_eq!(dao.restore(&exercise.id), Ok(exercise.clone()));
+/// assert_eq!(dao.purge(&exercise.id), Ok(exercise.clone()));
 ///
 /// let exercise = dao.find_by_id(&exercise.id);
 /// assert_eq!(
@@ -186,7 +385,321 @@ pub trait DeleteById<ID, R> {
 pub trait ExerciseDao:
     for<'a> Create<&'a NewExercise, Exercise>
     + for<'a> FindById<&'a str, Exercise>
+    + for<'a> List<&'a ExerciseQuery, Exercise>
+    + for<'a> Search<&'a str, Exercise>
     + for<'a> Update<&'a UpdatedExercise<'a>, Exercise>
     + for<'a> DeleteById<&'a str, Exercise>
+    + for<'a> Trash<&'a str, Exercise>
+    + for<'a> CreateBatch<&'a [NewExercise], Exercise>
+    + for<'a> UpdateBatch<&'a [UpdatedExercise<'a>], Exercise>
+    + for<'a> DeleteByIds<&'a [&'a str], Exercise>
+    + for<'a> CreateAll<&'a [NewExercise], Exercise>
+    + RateDifficulty
+{
+}
+
+/// A [data access object] for typing attempts.
+///
+/// Attempts are listed from newest to oldest. Creating an attempt at an exercise that does not
+/// exist fails with `Error::NotFound`.
+///
+/// Current implementors include
+/// - `diesel::PgConnection`
+/// - `diesel::MysqlConnection`
+/// - `diesel::r2d2::PooledConnection`
+/// - `wikitype_api::database::sql::SqliteConnection`
+///
+/// [data access object]: https://en.wikipedia.org/wiki/Data_access_object
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use database::query::AttemptQuery;
+/// use database::sql::pool::Pool;
+/// use wikitype_api::config::Config;
+/// use wikitype_api::database;
+/// use wikitype_api::models::{NewExerciseBuilder, NewTypingAttemptBuilder, TypingAttempt};
+/// use wikitype_api::scoring::{self, Key, Keystroke};
+///
+/// let pool = Pool::new(&Config::new(":memory:")).unwrap();
+/// pool.run_pending_migrations().unwrap();
+/// let dao = pool.get().unwrap();
+///
+/// let new_exercise = NewExerciseBuilder::new()
+///     .title("Albatross")
+///     .body("Albatross body")
+///     .build()
+///     .unwrap();
+/// let exercise = dao.create(&new_exercise).unwrap();
+///
+/// // Record an attempt at typing the exercise.
+/// let log = vec![
+///     Keystroke { key: Key::Char('A'), elapsed_ms: 1_000 },
+///     Keystroke { key: Key::Char('l'), elapsed_ms: 3_000 },
+/// ];
+/// let score = scoring::score(&exercise.body, &log).unwrap();
+/// let started_on = NaiveDateTime::from_timestamp(0, 0);
+/// let new_attempt = NewTypingAttemptBuilder::new()
+///     .exercise_id(&exercise.id)
+///     .started_on(started_on)
+///     .score(&score)
+///     .build()
+///     .unwrap();
+/// let attempt = dao.create(&new_attempt).unwrap();
+/// assert_eq!(attempt.exercise_id, exercise.id);
+/// assert_eq!(attempt.gross_wpm, 8.0);
+/// assert_eq!(attempt.accuracy, 1.0);
+///
+/// // List the attempts at the exercise.
+/// let query = AttemptQuery::for_exercise(&exercise.id);
+/// assert_eq!(dao.count(&query), Ok(1));
+/// assert_eq!(dao.list(&query, 0, 10), Ok(vec![attempt]));
+///
+/// // Attempts at unknown exercises are rejected.
+/// let new_attempt = NewTypingAttemptBuilder::new()
+///     .exercise_id("unknown")
+///     .started_on(started_on)
+///     .score(&score)
+///     .build()
+///     .unwrap();
+/// let attempt: database::Result<TypingAttempt> = dao.create(&new_attempt);
+/// assert_eq!(attempt, Err(database::Error::NotFound));
+/// ```
+pub trait AttemptDao:
+    for<'a> Create<&'a NewTypingAttempt, TypingAttempt>
+    + for<'a> FindById<&'a str, TypingAttempt>
+    + for<'a> List<&'a AttemptQuery, TypingAttempt>
 {
 }
+
+/// A [data access object] for users.
+///
+/// Users are found by id or by (unique) username. Creating a user with a username that is
+/// already taken fails with `Error::Conflict`.
+///
+/// Current implementors include
+/// - `diesel::PgConnection`
+/// - `diesel::MysqlConnection`
+/// - `diesel::r2d2::PooledConnection`
+/// - `wikitype_api::database::sql::SqliteConnection`
+///
+/// [data access object]: https://en.wikipedia.org/wiki/Data_access_object
+///
+/// # Examples
+///
+/// ```
+/// use database::sql::pool::Pool;
+/// use wikitype_api::config::Config;
+/// use wikitype_api::database;
+/// use wikitype_api::models::{NewUserBuilder, Role, User};
+///
+/// let pool = Pool::new(&Config::new(":memory:")).unwrap();
+/// pool.run_pending_migrations().unwrap();
+/// let dao = pool.get().unwrap();
+///
+/// // Register a new user.
+/// let new_user = NewUserBuilder::new()
+///     .username("albatross")
+///     .password_hash("not really a hash")
+///     .role(Role::Admin)
+///     .build()
+///     .unwrap();
+/// let user: User = dao.create(&new_user).unwrap();
+/// assert_eq!(user.id, new_user.get_id());
+/// assert_eq!(user.role, Role::Admin);
+///
+/// // Find the user by id or by name.
+/// assert_eq!(dao.find_by_id(user.id.as_str()), Ok(user.clone()));
+/// assert_eq!(dao.find_by_name("albatross"), Ok(user));
+///
+/// // Usernames are unique.
+/// let new_user = NewUserBuilder::new()
+///     .username("albatross")
+///     .password_hash("not really a hash")
+///     .build()
+///     .unwrap();
+/// let user: database::Result<User> = dao.create(&new_user);
+/// assert!(matches!(user, Err(database::Error::Conflict(_))));
+/// ```
+pub trait UserDao:
+    for<'a> Create<&'a NewUser, User>
+    + for<'a> FindById<&'a str, User>
+    + for<'a> FindByName<&'a str, User>
+{
+}
+
+/// A [data access object] for topics.
+///
+/// Topics are found by id or by (unique) slug, and linked to exercises by id. Creating a topic
+/// with a slug that is already taken fails with `Error::Conflict`, and creating a subtopic of
+/// an unknown topic (or linking an unknown topic) fails with `Error::NotFound`.
+///
+/// Current implementors include
+/// - `diesel::PgConnection`
+/// - `diesel::MysqlConnection`
+/// - `diesel::r2d2::PooledConnection`
+/// - `wikitype_api::database::sql::SqliteConnection`
+///
+/// [data access object]: https://en.wikipedia.org/wiki/Data_access_object
+///
+/// # Examples
+///
+/// ```
+/// use database::sql::pool::Pool;
+/// use wikitype_api::config::Config;
+/// use wikitype_api::database;
+/// use wikitype_api::models::{self, NewExerciseBuilder, NewTopicBuilder, Topic};
+///
+/// let pool = Pool::new(&Config::new(":memory:")).unwrap();
+/// pool.run_pending_migrations().unwrap();
+/// let dao = pool.get().unwrap();
+///
+/// // Create a topic and a subtopic.
+/// let new_biology = NewTopicBuilder::new().name("Biology").build().unwrap();
+/// let biology: Topic = dao.create(&new_biology).unwrap();
+/// let birds: Topic = dao
+///     .create(
+///         &NewTopicBuilder::new()
+///             .name("Birds")
+///             .parent_id(Some(&biology.id))
+///             .build()
+///             .unwrap(),
+///     )
+///     .unwrap();
+/// assert_eq!(dao.find_by_name("birds"), Ok(birds.clone()));
+///
+/// // Link an exercise to the subtopic.
+/// let new_exercise = NewExerciseBuilder::new()
+///     .title("Albatross")
+///     .body("Albatross body")
+///     .build()
+///     .unwrap();
+/// let exercise = dao.create(&new_exercise).unwrap();
+/// assert_eq!(dao.link(&exercise.id, &[&birds.id]), Ok(vec![birds.clone()]));
+///
+/// // Browse the topic tree.
+/// let tree = models::topic_tree(dao.count_exercises().unwrap());
+/// assert_eq!(tree[0].topic, biology);
+/// assert_eq!(tree[0].exercise_count, 0);
+/// assert_eq!(tree[0].children[0].topic, birds);
+/// assert_eq!(tree[0].children[0].exercise_count, 1);
+///
+/// // Slugs are unique.
+/// let new_birds = NewTopicBuilder::new().name("Birds").build().unwrap();
+/// let topic: database::Result<Topic> = dao.create(&new_birds);
+/// assert!(matches!(topic, Err(database::Error::Conflict(_))));
+/// ```
+pub trait TopicDao:
+    for<'a> Create<&'a NewTopic, Topic>
+    + for<'a> FindById<&'a str, Topic>
+    + for<'a> FindByName<&'a str, Topic>
+    + ExerciseLinks<Topic>
+    + CountExercises<Topic>
+{
+}
+
+/// A [data access object] for tags.
+///
+/// Tags are identified by their (unique) names, and created when first linked to an exercise.
+///
+/// Current implementors include
+/// - `diesel::PgConnection`
+/// - `diesel::MysqlConnection`
+/// - `diesel::r2d2::PooledConnection`
+/// - `wikitype_api::database::sql::SqliteConnection`
+///
+/// [data access object]: https://en.wikipedia.org/wiki/Data_access_object
+///
+/// # Examples
+///
+/// ```
+/// use database::query::ExerciseQueryBuilder;
+/// use database::sql::pool::Pool;
+/// use wikitype_api::config::Config;
+/// use wikitype_api::database;
+/// use wikitype_api::models::{NewExerciseBuilder, Tag};
+///
+/// let pool = Pool::new(&Config::new(":memory:")).unwrap();
+/// pool.run_pending_migrations().unwrap();
+/// let dao = pool.get().unwrap();
+///
+/// let new_exercise = NewExerciseBuilder::new()
+///     .title("Pi")
+///     .body("3.14159 26535 89793")
+///     .build()
+///     .unwrap();
+/// let exercise = dao.create(&new_exercise).unwrap();
+///
+/// // Tag the exercise, creating the tags.
+/// let tags: Vec<Tag> = dao.link(&exercise.id, &["short", "numbers-heavy"]).unwrap();
+/// assert_eq!(tags[0].name, "numbers-heavy");
+/// assert_eq!(dao.find_by_name("short"), Ok(tags[1].clone()));
+///
+/// // List the exercises with any or all of the given tags.
+/// let query = ExerciseQueryBuilder::new().tags_any(&["short", "quotes"]).build();
+/// assert_eq!(dao.count(&query), Ok(1));
+/// let query = ExerciseQueryBuilder::new().tags_all(&["short", "quotes"]).build();
+/// assert_eq!(dao.count(&query), Ok(0));
+/// ```
+pub trait TagDao: for<'a> FindByName<&'a str, Tag> + ExerciseLinks<Tag> {}
+
+/// A [data access object] for exercise revisions.
+///
+/// A revision is written, in the same transaction, whenever an exercise is created or updated.
+/// Revisions are deleted along with their exercise.
+///
+/// Current implementors include
+/// - `diesel::PgConnection`
+/// - `diesel::MysqlConnection`
+/// - `diesel::r2d2::PooledConnection`
+/// - `wikitype_api::database::sql::SqliteConnection`
+///
+/// [data access object]: https://en.wikipedia.org/wiki/Data_access_object
+///
+/// # Examples
+///
+/// ```
+/// use database::sql::pool::Pool;
+/// use wikitype_api::config::Config;
+/// use wikitype_api::database;
+/// use wikitype_api::models::{ExerciseRevision, NewExerciseBuilder, UpdatedExerciseBuilder};
+///
+/// let pool = Pool::new(&Config::new(":memory:")).unwrap();
+/// pool.run_pending_migrations().unwrap();
+/// let dao = pool.get().unwrap();
+///
+/// let new_exercise = NewExerciseBuilder::new()
+///     .title("Albatross")
+///     .body("Albatross body")
+///     .build()
+///     .unwrap();
+/// let exercise = dao.create(&new_exercise).unwrap();
+/// let exercise = dao
+///     .update(
+///         &UpdatedExerciseBuilder::new(&exercise.id)
+///             .body("Albatross new body")
+///             .build()
+///             .unwrap(),
+///     )
+///     .unwrap();
+///
+/// // Every version of the exercise has a revision.
+/// let revisions = dao.revisions(&exercise.id).unwrap();
+/// assert_eq!(revisions.len(), 2);
+/// assert_eq!(revisions[0].version, 2);
+/// assert_eq!(revisions[0].body, "Albatross new body");
+/// assert_eq!(revisions[1].body, "Albatross body");
+///
+/// let revision: ExerciseRevision = dao.find_by_id(revisions[1].id.as_str()).unwrap();
+/// assert_eq!(revision, revisions[1]);
+/// ```
+pub trait RevisionDao:
+    for<'a> FindById<&'a str, ExerciseRevision> + ExerciseRevisions<ExerciseRevision>
+{
+}
+
+/// A data access object for every resource (e.g. as checked out from a connection pool).
+pub trait Dao: ExerciseDao + AttemptDao + UserDao + TopicDao + TagDao + RevisionDao {}
+
+impl<T> Dao for T where T: ExerciseDao + AttemptDao + UserDao + TopicDao + TagDao + RevisionDao {}
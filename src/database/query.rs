@@ -0,0 +1,280 @@
+use chrono::NaiveDateTime;
+
+/// Columns by which exercises can be sorted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExerciseSortField {
+    Topic,
+    Title,
+    /// Number of characters in the body.
+    BodyLength,
+    CreatedOn,
+    ModifiedOn,
+    /// Index of the exercise within its series.
+    PartIndex,
+    Difficulty,
+}
+
+/// Direction in which exercises are sorted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+/// Sort order of an `ExerciseQuery`.
+///
+/// Exercises that compare equal are further ordered by their id, so the order is always total.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ExerciseSort {
+    pub field: ExerciseSortField,
+    pub direction: SortDirection,
+}
+
+impl Default for ExerciseSort {
+    /// Sorts exercises from oldest to newest.
+    fn default() -> ExerciseSort {
+        ExerciseSort {
+            field: ExerciseSortField::CreatedOn,
+            direction: SortDirection::Ascending,
+        }
+    }
+}
+
+/// Which exercises an `ExerciseFilter` selects with regard to the trash.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Trashed {
+    /// Only exercises that are not in the trash.
+    Exclude,
+
+    /// Exercises whether they are in the trash or not.
+    Include,
+
+    /// Only exercises that are in the trash.
+    Only,
+}
+
+impl Default for Trashed {
+    /// Hides trashed exercises.
+    fn default() -> Trashed {
+        Trashed::Exclude
+    }
+}
+
+/// Filter of an `ExerciseQuery`.
+///
+/// Every criterion that is set must hold for an exercise to match. Bounds are inclusive.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExerciseFilter {
+    /// Free-text topic the exercise must have.
+    pub topic: Option<String>,
+
+    /// Id of a `Topic` the exercise must be linked to.
+    pub topic_id: Option<String>,
+
+    /// Names of tags of which the exercise must have at least one (ignored if empty).
+    pub tags_any: Option<Vec<String>>,
+
+    /// Names of tags which the exercise must all have.
+    pub tags_all: Option<Vec<String>>,
+
+    /// Substring the title must contain (case-insensitively).
+    pub title_contains: Option<String>,
+
+    /// Minimum number of characters in the body.
+    pub min_body_length: Option<i32>,
+
+    /// Maximum number of characters in the body.
+    pub max_body_length: Option<i32>,
+
+    /// Earliest date and time of creation.
+    pub created_after: Option<NaiveDateTime>,
+
+    /// Latest date and time of creation.
+    pub created_before: Option<NaiveDateTime>,
+
+    /// Earliest date and time of the last modification.
+    pub modified_after: Option<NaiveDateTime>,
+
+    /// Latest date and time of the last modification.
+    pub modified_before: Option<NaiveDateTime>,
+
+    /// Id of the series the exercise must be a part of.
+    pub series_id: Option<String>,
+
+    /// Index the exercise must have within its series.
+    pub part_index: Option<i32>,
+
+    /// Minimum index within its series (exercises outside a series never match).
+    pub min_part_index: Option<i32>,
+
+    /// Maximum index within its series (exercises outside a series never match).
+    pub max_part_index: Option<i32>,
+
+    /// Minimum difficulty (exercises that have not been rated yet never match).
+    pub min_difficulty: Option<i32>,
+
+    /// Maximum difficulty (exercises that have not been rated yet never match).
+    pub max_difficulty: Option<i32>,
+
+    /// Whether trashed exercises match (by default, they do not).
+    pub trashed: Trashed,
+}
+
+/// A query selecting and ordering exercises, as consumed by `List` implementations.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExerciseQuery {
+    pub filter: ExerciseFilter,
+    pub sort: ExerciseSort,
+}
+
+/// Type for creating an `ExerciseQuery`.
+///
+/// # Examples
+///
+/// ```
+/// use wikitype_api::database::query::{
+///     ExerciseQueryBuilder, ExerciseSortField, SortDirection,
+/// };
+///
+/// // Query the longest exercises about birds.
+/// let query = ExerciseQueryBuilder::new()
+///     .topic("Birds")
+///     .min_body_length(100)
+///     .sort_by(ExerciseSortField::BodyLength, SortDirection::Descending)
+///     .build();
+///
+/// assert_eq!(query.filter.topic, Some(String::from("Birds")));
+/// assert_eq!(query.filter.min_body_length, Some(100));
+/// assert_eq!(query.filter.max_body_length, None);
+/// assert_eq!(query.sort.field, ExerciseSortField::BodyLength);
+/// ```
+#[derive(Default)]
+pub struct ExerciseQueryBuilder {
+    query: ExerciseQuery,
+}
+
+impl ExerciseQueryBuilder {
+    pub fn new() -> ExerciseQueryBuilder {
+        ExerciseQueryBuilder::default()
+    }
+
+    pub fn topic(&mut self, topic: &str) -> &mut ExerciseQueryBuilder {
+        self.query.filter.topic = Some(String::from(topic));
+        self
+    }
+
+    pub fn topic_id(&mut self, topic_id: &str) -> &mut ExerciseQueryBuilder {
+        self.query.filter.topic_id = Some(String::from(topic_id));
+        self
+    }
+
+    pub fn tags_any(&mut self, tags: &[&str]) -> &mut ExerciseQueryBuilder {
+        self.query.filter.tags_any = Some(tags.iter().map(|&tag| String::from(tag)).collect());
+        self
+    }
+
+    pub fn tags_all(&mut self, tags: &[&str]) -> &mut ExerciseQueryBuilder {
+        self.query.filter.tags_all = Some(tags.iter().map(|&tag| String::from(tag)).collect());
+        self
+    }
+
+    pub fn title_contains(&mut self, title: &str) -> &mut ExerciseQueryBuilder {
+        self.query.filter.title_contains = Some(String::from(title));
+        self
+    }
+
+    pub fn min_body_length(&mut self, length: i32) -> &mut ExerciseQueryBuilder {
+        self.query.filter.min_body_length = Some(length);
+        self
+    }
+
+    pub fn max_body_length(&mut self, length: i32) -> &mut ExerciseQueryBuilder {
+        self.query.filter.max_body_length = Some(length);
+        self
+    }
+
+    pub fn created_after(&mut self, after: NaiveDateTime) -> &mut ExerciseQueryBuilder {
+        self.query.filter.created_after = Some(after);
+        self
+    }
+
+    pub fn created_before(&mut self, before: NaiveDateTime) -> &mut ExerciseQueryBuilder {
+        self.query.filter.created_before = Some(before);
+        self
+    }
+
+    pub fn modified_after(&mut self, after: NaiveDateTime) -> &mut ExerciseQueryBuilder {
+        self.query.filter.modified_after = Some(after);
+        self
+    }
+
+    pub fn modified_before(&mut self, before: NaiveDateTime) -> &mut ExerciseQueryBuilder {
+        self.query.filter.modified_before = Some(before);
+        self
+    }
+
+    pub fn series_id(&mut self, series_id: &str) -> &mut ExerciseQueryBuilder {
+        self.query.filter.series_id = Some(String::from(series_id));
+        self
+    }
+
+    pub fn part_index(&mut self, part_index: i32) -> &mut ExerciseQueryBuilder {
+        self.query.filter.part_index = Some(part_index);
+        self
+    }
+
+    pub fn min_part_index(&mut self, part_index: i32) -> &mut ExerciseQueryBuilder {
+        self.query.filter.min_part_index = Some(part_index);
+        self
+    }
+
+    pub fn max_part_index(&mut self, part_index: i32) -> &mut ExerciseQueryBuilder {
+        self.query.filter.max_part_index = Some(part_index);
+        self
+    }
+
+    pub fn min_difficulty(&mut self, difficulty: i32) -> &mut ExerciseQueryBuilder {
+        self.query.filter.min_difficulty = Some(difficulty);
+        self
+    }
+
+    pub fn max_difficulty(&mut self, difficulty: i32) -> &mut ExerciseQueryBuilder {
+        self.query.filter.max_difficulty = Some(difficulty);
+        self
+    }
+
+    pub fn trashed(&mut self, trashed: Trashed) -> &mut ExerciseQueryBuilder {
+        self.query.filter.trashed = trashed;
+        self
+    }
+
+    pub fn sort_by(
+        &mut self,
+        field: ExerciseSortField,
+        direction: SortDirection,
+    ) -> &mut ExerciseQueryBuilder {
+        self.query.sort = ExerciseSort { field, direction };
+        self
+    }
+
+    pub fn build(&mut self) -> ExerciseQuery {
+        self.query.clone()
+    }
+}
+
+/// A query selecting the typing attempts at an exercise, from newest to oldest, as consumed by
+/// `List` implementations.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AttemptQuery {
+    /// Id of the exercise whose attempts are selected.
+    pub exercise_id: String,
+}
+
+impl AttemptQuery {
+    /// Creates a query selecting the typing attempts at the exercise with the given id.
+    pub fn for_exercise(exercise_id: &str) -> AttemptQuery {
+        AttemptQuery {
+            exercise_id: String::from(exercise_id),
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use crate::database::SearchHit;
+use crate::models::Exercise;
+
+use std::cmp::Ordering;
+
+/// Marker inserted before each highlighted term in a snippet.
+pub const HIGHLIGHT_START: &str = "<b>";
+
+/// Marker inserted after each highlighted term in a snippet.
+pub const HIGHLIGHT_END: &str = "</b>";
+
+/// Marker inserted where a snippet omits part of the text.
+pub const ELLIPSIS: &str = "…";
+
+/// Maximum number of words in a snippet.
+const SNIPPET_WORDS: usize = 16;
+
+/// Number of words shown before the first highlighted term in a snippet.
+const SNIPPET_LEADING_WORDS: usize = 4;
+
+/// Splits a search query into its distinct, lowercase terms.
+///
+/// Any character that is not alphanumeric separates terms, so query syntax (quotes, operators,
+/// etc.) is ignored.
+///
+/// # Examples
+///
+/// ```
+/// use wikitype_api::database::search::search_terms;
+///
+/// assert_eq!(search_terms("Large  SEA-birds, large!"), vec!["large", "sea", "birds"]);
+/// ```
+pub fn search_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for term in query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+    {
+        if !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+/// Ranks and highlights `candidates` against the search `terms`, returning at most `limit` hits
+/// ordered from most to least relevant.
+///
+/// Candidates must contain every term in their title or body to match. Matches in the title
+/// weigh twice as much as matches in the body, and matches in long bodies weigh less than
+/// matches in short ones.
+///
+/// This is the portable fallback for backends without native full-text search: it only needs
+/// the backend to narrow down `candidates` (e.g. with `LIKE`).
+pub fn rank_exercises(
+    candidates: Vec<Exercise>,
+    terms: &[String],
+    limit: usize,
+) -> Vec<SearchHit<Exercise>> {
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    let mut hits: Vec<SearchHit<Exercise>> = candidates
+        .into_iter()
+        .filter_map(|exercise| {
+            let title = exercise.title.to_lowercase();
+            let body = exercise.body.to_lowercase();
+            let mut score = 0.0;
+            for term in terms {
+                let matches =
+                    2 * title.matches(term.as_str()).count() + body.matches(term.as_str()).count();
+                if matches == 0 {
+                    return None;
+                }
+                score += matches as f64;
+            }
+            let words = body.split_whitespace().count() as f64;
+            Some(SearchHit {
+                rank: score / (1.0 + words).ln().max(1.0),
+                snippet: snippet(&exercise.body, terms),
+                item: exercise,
+            })
+        })
+        .collect();
+
+    hits.sort_by(|a, b| {
+        b.rank
+            .partial_cmp(&a.rank)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.item.id.cmp(&b.item.id))
+    });
+    hits.truncate(limit);
+    hits
+}
+
+/// Returns an excerpt of `text` around the first word containing one of `terms`, with every
+/// such word highlighted.
+///
+/// # Examples
+///
+/// ```
+/// use wikitype_api::database::search::snippet;
+///
+/// let terms = vec![String::from("seabird")];
+/// assert_eq!(
+///     snippet("Albatrosses are large seabirds.", &terms),
+///     "Albatrosses are large <b>seabirds.</b>"
+/// );
+/// ```
+pub fn snippet(text: &str, terms: &[String]) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let is_match = |word: &str| {
+        let word = word.to_lowercase();
+        terms.iter().any(|term| word.contains(term.as_str()))
+    };
+
+    let first_match = words.iter().position(|word| is_match(word)).unwrap_or(0);
+    let start = first_match
+        .saturating_sub(SNIPPET_LEADING_WORDS)
+        .min(words.len().saturating_sub(SNIPPET_WORDS));
+    let end = (start + SNIPPET_WORDS).min(words.len());
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push_str(ELLIPSIS);
+    }
+    for (i, word) in words[start..end].iter().enumerate() {
+        if i > 0 {
+            snippet.push(' ');
+        }
+        if is_match(word) {
+            snippet.push_str(HIGHLIGHT_START);
+            snippet.push_str(word);
+            snippet.push_str(HIGHLIGHT_END);
+        } else {
+            snippet.push_str(word);
+        }
+    }
+    if end < words.len() {
+        snippet.push_str(ELLIPSIS);
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn exercise(id: &str, title: &str, body: &str) -> Exercise {
+        Exercise {
+            id: String::from(id),
+            title: String::from(title),
+            body: String::from(body),
+            topic: None,
+            created_on: NaiveDateTime::from_timestamp(0, 0),
+            modified_on: NaiveDateTime::from_timestamp(0, 0),
+            author_id: None,
+            version: 1,
+            series_id: None,
+            part_index: None,
+            difficulty: None,
+            deleted_on: None,
+        }
+    }
+
+    /// Test that every term must match and that title matches rank higher than body matches.
+    #[test]
+    fn rank_portable_search_hits() {
+        let terms = search_terms("petrel");
+        let candidates = vec![
+            exercise(
+                "1",
+                "Albatross",
+                "Related to storm petrels and diving petrels.",
+            ),
+            exercise("2", "Petrel", "A tubenosed seabird."),
+            exercise("3", "Penguin", "A flightless bird."),
+        ];
+
+        let hits = rank_exercises(candidates.clone(), &terms, 10);
+        let ids: Vec<&str> = hits.iter().map(|hit| hit.item.id.as_str()).collect();
+        assert_eq!(ids, vec!["2", "1"]);
+        assert_eq!(
+            hits[1].snippet,
+            "Related to storm <b>petrels</b> and diving <b>petrels.</b>"
+        );
+
+        let terms = search_terms("diving petrel");
+        let hits = rank_exercises(candidates.clone(), &terms, 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].item.id, "1");
+
+        assert!(rank_exercises(candidates, &search_terms("!?"), 10).is_empty());
+    }
+
+    /// Test that long snippets are truncated around the first match.
+    #[test]
+    fn truncate_snippets() {
+        let text = (1..=40)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_eq!(
+            snippet(&text, &[String::from("20")]),
+            "…16 17 18 19 <b>20</b> 21 22 23 24 25 26 27 28 29 30 31…"
+        );
+        assert_eq!(
+            snippet(&text, &[String::from("39")]),
+            "…25 26 27 28 29 30 31 32 33 34 35 36 37 38 <b>39</b> 40"
+        );
+    }
+}
@@ -0,0 +1,462 @@
+use crate::database;
+use crate::database::sql::pool::Dialect;
+use crate::database::sql::schema::{exercise_topics, exercises, topics};
+use crate::database::IntoDatabaseError;
+use crate::models::{slugify, NewTopic, NewTopicBuilder};
+
+use diesel::backend::Backend;
+use diesel::connection::SimpleConnection;
+use diesel::migration::{Migration, RunMigrationsError};
+use diesel::prelude::*;
+use diesel::query_builder::InsertStatement;
+use diesel::query_dsl::methods::ExecuteDsl;
+use diesel::sql_types::{Nullable, Varchar};
+use diesel_migrations::{setup_database, MigrationConnection};
+use std::collections::BTreeMap;
+
+/// Embeds the `up.sql` and `down.sql` scripts of the named migrations of a dialect.
+///
+/// NOTE: The build script lists every directory in "migrations/<dialect>", in order, in
+/// "<dialect>_migrations.rs" in `OUT_DIR`, which is included below.
+macro_rules! embed_dialect_migrations {
+    ($dialect:literal, [$($name:literal),* $(,)?]) => {
+        &[$(
+            (
+                $name,
+                include_str!(concat!(
+                    env!("CARGO_MANIFEST_DIR"), "/migrations/", $dialect, "/", $name, "/up.sql"
+                )),
+                include_str!(concat!(
+                    env!("CARGO_MANIFEST_DIR"), "/migrations/", $dialect, "/", $name, "/down.sql"
+                )),
+            ),
+        )*]
+    };
+}
+
+/// Name, `up.sql` and `down.sql` of every Postgres migration.
+const POSTGRES: &[(&str, &str, &str)] =
+    include!(concat!(env!("OUT_DIR"), "/postgres_migrations.rs"));
+
+/// Name, `up.sql` and `down.sql` of every MySQL migration.
+const MYSQL: &[(&str, &str, &str)] = include!(concat!(env!("OUT_DIR"), "/mysql_migrations.rs"));
+
+/// Name, `up.sql` and `down.sql` of every SQLite migration.
+const SQLITE: &[(&str, &str, &str)] = include!(concat!(env!("OUT_DIR"), "/sqlite_migrations.rs"));
+
+/// Name of the migration creating topics, after which the free-text topics of existing exercises
+/// are converted (see `convert_exercise_topics`).
+const CREATE_TOPICS: &str = "2026-10-17-200000_create_topics";
+
+/// Statement inserting a topic.
+///
+/// NOTE: Data conversions are bound by the statements they execute (rather than by the backend),
+/// since Diesel implements inserts for SQLite separately from the other backends.
+type InsertTopic = InsertStatement<topics::table, <NewTopic as Insertable<topics::table>>::Values>;
+
+/// Statement linking an exercise to a topic.
+type InsertExerciseTopic = InsertStatement<
+    exercise_topics::table,
+    <(
+        diesel::dsl::Eq<exercise_topics::exercise_id, String>,
+        diesel::dsl::Eq<exercise_topics::topic_id, String>,
+    ) as Insertable<exercise_topics::table>>::Values,
+>;
+
+/// A migration embedded in the binary.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmbeddedMigration {
+    name: &'static str,
+    version: String,
+    up_sql: &'static str,
+    down_sql: &'static str,
+}
+
+impl EmbeddedMigration {
+    fn new((name, up_sql, down_sql): (&'static str, &'static str, &'static str)) -> Self {
+        // NOTE: Diesel identifies a migration by the digits of the date its name starts with.
+        let version = name.split('_').next().unwrap_or(name).replace('-', "");
+        EmbeddedMigration {
+            name,
+            version,
+            up_sql,
+            down_sql,
+        }
+    }
+
+    /// Returns the name of the migration's directory, e.g. "2019-06-02-153217_create_exercises".
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl Migration for EmbeddedMigration {
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn run(&self, conn: &dyn SimpleConnection) -> Result<(), RunMigrationsError> {
+        conn.batch_execute(self.up_sql).map_err(Into::into)
+    }
+
+    fn revert(&self, conn: &dyn SimpleConnection) -> Result<(), RunMigrationsError> {
+        conn.batch_execute(self.down_sql).map_err(Into::into)
+    }
+}
+
+/// Returns every migration embedded for `dialect`, from oldest to newest.
+pub fn embedded_migrations(dialect: Dialect) -> Vec<EmbeddedMigration> {
+    let migrations = match dialect {
+        Dialect::Postgres => POSTGRES,
+        Dialect::Mysql => MYSQL,
+        Dialect::Sqlite => SQLITE,
+    };
+    migrations
+        .iter()
+        .cloned()
+        .map(EmbeddedMigration::new)
+        .collect()
+}
+
+/// Returns the migrations embedded for `dialect` which have not been run against the database
+/// yet, from oldest to newest.
+pub fn pending_migrations<C>(conn: &C, dialect: Dialect) -> database::Result<Vec<EmbeddedMigration>>
+where
+    C: MigrationConnection,
+{
+    setup_database(conn).map_err(IntoDatabaseError::into_database_error)?;
+    let already_run = conn
+        .previously_run_migration_versions()
+        .map_err(IntoDatabaseError::into_database_error)?;
+    Ok(embedded_migrations(dialect)
+        .into_iter()
+        .filter(|migration| !already_run.contains(migration.version()))
+        .collect())
+}
+
+/// Runs the pending migrations embedded for `dialect` against the database, returning the
+/// migrations that were run.
+///
+/// Each migration runs in its own transaction (where the dialect supports transactional DDL).
+///
+/// # Examples
+///
+/// ```
+/// use diesel::prelude::*;
+/// use wikitype_api::database::sql::migrations;
+/// use wikitype_api::database::sql::pool::Dialect;
+///
+/// let conn = SqliteConnection::establish(":memory:").unwrap();
+///
+/// let run = migrations::run_pending_migrations(&conn, Dialect::Sqlite).unwrap();
+/// assert_eq!(run, migrations::embedded_migrations(Dialect::Sqlite));
+///
+/// // The schema is up to date.
+/// assert!(migrations::pending_migrations(&conn, Dialect::Sqlite)
+///     .unwrap()
+///     .is_empty());
+/// ```
+pub fn run_pending_migrations<C, DB>(
+    conn: &C,
+    dialect: Dialect,
+) -> database::Result<Vec<EmbeddedMigration>>
+where
+    C: MigrationConnection + Connection<Backend = DB>,
+    DB: Backend,
+    (String, Option<String>): Queryable<(Varchar, Nullable<Varchar>), DB>,
+    InsertTopic: ExecuteDsl<C>,
+    InsertExerciseTopic: ExecuteDsl<C>,
+{
+    let pending = pending_migrations(conn, dialect)?;
+    for migration in &pending {
+        conn.transaction(|| {
+            run_migration(conn, migration)?;
+            conn.insert_new_migration(migration.version())
+                .map_err(RunMigrationsError::from)
+        })
+        .map_err(IntoDatabaseError::into_database_error)?;
+    }
+    Ok(pending)
+}
+
+/// Runs `migration` against the database, along with the conversion of existing data that it
+/// requires but cannot express in SQL.
+pub fn run_migration<C, DB>(
+    conn: &C,
+    migration: &EmbeddedMigration,
+) -> Result<(), RunMigrationsError>
+where
+    C: Connection<Backend = DB>,
+    DB: Backend,
+    (String, Option<String>): Queryable<(Varchar, Nullable<Varchar>), DB>,
+    InsertTopic: ExecuteDsl<C>,
+    InsertExerciseTopic: ExecuteDsl<C>,
+{
+    migration.run(conn)?;
+    if migration.name() == CREATE_TOPICS {
+        convert_exercise_topics(conn)?;
+    }
+    Ok(())
+}
+
+/// Converts the free-text topics of existing exercises into top-level topics, linking each
+/// exercise to its topic.
+///
+/// Topics with the same slug (see `slugify`), e.g. differing only in case or punctuation, are
+/// merged into the topic with the (lexicographically) first name. Topics without any letters or
+/// digits are not converted.
+fn convert_exercise_topics<C, DB>(conn: &C) -> QueryResult<()>
+where
+    C: Connection<Backend = DB>,
+    DB: Backend,
+    (String, Option<String>): Queryable<(Varchar, Nullable<Varchar>), DB>,
+    InsertTopic: ExecuteDsl<C>,
+    InsertExerciseTopic: ExecuteDsl<C>,
+{
+    let existing: Vec<(String, Option<String>)> = exercises::table
+        .select((exercises::id, exercises::topic))
+        .filter(exercises::topic.is_not_null())
+        .load(conn)?;
+    // Name and exercise ids of each slug.
+    let mut converted: BTreeMap<String, (String, Vec<String>)> = BTreeMap::new();
+    for (exercise_id, topic) in existing {
+        let name = topic.as_deref().unwrap_or("").trim();
+        let slug = slugify(name);
+        if slug.is_empty() {
+            continue;
+        }
+        let entry = converted
+            .entry(slug)
+            .or_insert_with(|| (String::from(name), Vec::new()));
+        if name < entry.0.as_str() {
+            entry.0 = String::from(name);
+        }
+        entry.1.push(exercise_id);
+    }
+    for (name, exercise_ids) in converted.values() {
+        let new_topic = NewTopicBuilder::new()
+            .name(name)
+            .build()
+            .map_err(|e| diesel::result::Error::QueryBuilderError(Box::new(e)))?;
+        let topic_id = String::from(new_topic.get_id());
+        diesel::insert_into(topics::table)
+            .values(new_topic)
+            .execute(conn)?;
+        for exercise_id in exercise_ids {
+            diesel::insert_into(exercise_topics::table)
+                .values((
+                    exercise_topics::exercise_id.eq(exercise_id.clone()),
+                    exercise_topics::topic_id.eq(topic_id.clone()),
+                ))
+                .execute(conn)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::sql::schema::{exercise_revisions, exercise_topics, topics};
+
+    /// Test that every migration directory is embedded, in order.
+    #[test]
+    fn embed_every_migration() {
+        for &dialect in &[Dialect::Postgres, Dialect::Mysql, Dialect::Sqlite] {
+            let dir = format!("./migrations/{}", dialect.to_string().to_lowercase());
+            let mut names: Vec<String> = std::fs::read_dir(dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+                .collect();
+            names.sort();
+
+            let embedded: Vec<&str> = embedded_migrations(dialect)
+                .iter()
+                .map(EmbeddedMigration::name)
+                .collect();
+            assert_eq!(embedded, names, "{} migrations", dialect);
+        }
+    }
+
+    /// Test that versions are derived from migration names the way Diesel does.
+    #[test]
+    fn version_embedded_migrations() {
+        let migrations = embedded_migrations(Dialect::Postgres);
+        assert_eq!(migrations[0].version(), "00000000000000");
+        assert_eq!(migrations[1].version(), "20190602153217");
+    }
+
+    /// Test that reverting the authors of exercises keeps the exercises, their typing attempts
+    /// and their full-text search index.
+    #[test]
+    fn revert_exercise_authors() {
+        let conn = SqliteConnection::establish(":memory:").unwrap();
+        let migrations = embedded_migrations(Dialect::Sqlite);
+        let (before, _): (Vec<_>, Vec<_>) = migrations
+            .iter()
+            .partition(|migration| migration.name() <= "2026-10-17-160000_add_exercise_authors");
+        for migration in &before {
+            migration.run(&conn).unwrap();
+        }
+        conn.batch_execute(
+            "INSERT INTO users (id, username, password_hash, role, created_on) VALUES \
+                ('u', 'author', 'hash', 'user', '2019-06-02'); \
+             INSERT INTO exercises (id, title, body, topic, created_on, modified_on, author_id) \
+                VALUES ('1', 'Albatross', 'Albatross body', NULL, '2019-06-02', '2019-06-02', 'u'); \
+             INSERT INTO typing_attempts (id, exercise_id, started_on, finished_on, typed_text, \
+                keystrokes, errors, gross_wpm, accuracy, net_wpm, corrected_errors, \
+                error_positions) \
+                VALUES ('a', '1', '2019-06-02', '2019-06-02', 'Albatross', 9, 0, 60, 1, 60, 0, '')",
+        )
+        .unwrap();
+        before.last().unwrap().revert(&conn).unwrap();
+
+        conn.batch_execute(
+            "INSERT INTO exercises (id, title, body, topic, created_on, modified_on) \
+                VALUES ('2', 'Penguin', 'Penguin body', NULL, '2019-06-02', '2019-06-02')",
+        )
+        .unwrap();
+        let count = |sql: &str| -> i64 {
+            diesel::select(diesel::dsl::sql::<diesel::sql_types::BigInt>(sql))
+                .get_result(&conn)
+                .unwrap()
+        };
+        assert_eq!(count("(SELECT COUNT(*) FROM exercises)"), 2);
+        assert_eq!(count("(SELECT COUNT(*) FROM typing_attempts)"), 1);
+        assert_eq!(
+            count("(SELECT COUNT(*) FROM exercises_fts WHERE exercises_fts MATCH 'penguin')"),
+            1
+        );
+    }
+
+    /// Test that the free-text topics of existing exercises are converted into linked topics, with
+    /// the slugs `slugify` derives from their names.
+    #[test]
+    fn convert_exercise_topics() {
+        let conn = SqliteConnection::establish(":memory:").unwrap();
+        let migrations = embedded_migrations(Dialect::Sqlite);
+        let (before, after): (Vec<_>, Vec<_>) = migrations
+            .iter()
+            .partition(|migration| migration.name() < "2026-10-17-200000_create_topics");
+        for migration in before {
+            migration.run(&conn).unwrap();
+        }
+        conn.batch_execute(
+            "INSERT INTO exercises (id, title, body, topic, created_on, modified_on) VALUES \
+                ('1', 'Albatross', 'Albatross body', 'Sea birds', '2019-06-02', '2019-06-02'), \
+                ('2', 'Penguin', 'Penguin body', ' sea Birds', '2019-06-02', '2019-06-02'), \
+                ('3', 'Mozart', 'Mozart body', 'Music', '2019-06-02', '2019-06-02'), \
+                ('4', 'Untitled', 'Untitled body', '', '2019-06-02', '2019-06-02'), \
+                ('5', 'Untopical', 'Untopical body', NULL, '2019-06-02', '2019-06-02'), \
+                ('6', 'Quilts', 'Quilts body', 'Arts & Crafts', '2019-06-02', '2019-06-02'), \
+                ('7', 'Pottery', 'Pottery body', 'arts-crafts!', '2019-06-02', '2019-06-02'), \
+                ('8', 'Magnets', 'Magnets body', 'Ørsted''s law', '2019-06-02', '2019-06-02'), \
+                ('9', 'Noise', 'Noise body', '!!!', '2019-06-02', '2019-06-02')",
+        )
+        .unwrap();
+        run_migration(&conn, after[0]).unwrap();
+
+        let converted: Vec<(String, String, Option<String>)> = topics::table
+            .select((topics::slug, topics::name, topics::parent_id))
+            .order(topics::slug)
+            .load(&conn)
+            .unwrap();
+        assert_eq!(
+            converted,
+            vec![
+                (
+                    String::from("arts-crafts"),
+                    String::from("Arts & Crafts"),
+                    None
+                ),
+                (String::from("music"), String::from("Music"), None),
+                (String::from("sea-birds"), String::from("Sea birds"), None),
+                (
+                    String::from("ørsted-s-law"),
+                    String::from("Ørsted's law"),
+                    None
+                ),
+            ]
+        );
+        let links: Vec<(String, String)> = exercise_topics::table
+            .inner_join(topics::table)
+            .select((exercise_topics::exercise_id, topics::slug))
+            .order(exercise_topics::exercise_id)
+            .load(&conn)
+            .unwrap();
+        assert_eq!(
+            links,
+            vec![
+                (String::from("1"), String::from("sea-birds")),
+                (String::from("2"), String::from("sea-birds")),
+                (String::from("3"), String::from("music")),
+                (String::from("6"), String::from("arts-crafts")),
+                (String::from("7"), String::from("arts-crafts")),
+                (String::from("8"), String::from("ørsted-s-law")),
+            ]
+        );
+
+        after[0].revert(&conn).unwrap();
+    }
+
+    /// Test that the current version of existing exercises is recorded as a revision.
+    #[test]
+    fn snapshot_existing_exercises() {
+        let conn = SqliteConnection::establish(":memory:").unwrap();
+        let migrations = embedded_migrations(Dialect::Sqlite);
+        let (before, after): (Vec<_>, Vec<_>) = migrations.iter().partition(|migration| {
+            migration.name() < "2026-10-17-220000_create_exercise_revisions"
+        });
+        for migration in before {
+            migration.run(&conn).unwrap();
+        }
+        conn.batch_execute(
+            "INSERT INTO users (id, username, password_hash, role, created_on) VALUES \
+                ('u', 'author', 'hash', 'user', '2019-06-02'); \
+             INSERT INTO exercises \
+                (id, title, body, topic, created_on, modified_on, author_id, version) VALUES \
+                ('1', 'Albatross', 'Albatross body', 'Birds', '2019-06-02', '2019-06-02', 'u', 1), \
+                ('2', 'Penguin', 'Penguin body', NULL, '2019-06-02', '2019-06-03', 'u', 3)",
+        )
+        .unwrap();
+        after[0].run(&conn).unwrap();
+
+        let revisions: Vec<(String, i32, Option<String>)> = exercise_revisions::table
+            .select((
+                exercise_revisions::exercise_id,
+                exercise_revisions::version,
+                exercise_revisions::author_id,
+            ))
+            .order(exercise_revisions::exercise_id)
+            .load(&conn)
+            .unwrap();
+        assert_eq!(
+            revisions,
+            vec![
+                (String::from("1"), 1, Some(String::from("u"))),
+                (String::from("2"), 3, None),
+            ]
+        );
+        let content: (String, Option<String>) = exercise_revisions::table
+            .filter(exercise_revisions::exercise_id.eq("1"))
+            .select((exercise_revisions::body, exercise_revisions::topic))
+            .first(&conn)
+            .unwrap();
+        assert_eq!(
+            content,
+            (String::from("Albatross body"), Some(String::from("Birds")))
+        );
+        let ids: Vec<String> = exercise_revisions::table
+            .select(exercise_revisions::id)
+            .load(&conn)
+            .unwrap();
+        for id in ids {
+            let uuid = uuid::Uuid::parse_str(&id).unwrap();
+            assert_eq!(uuid.to_hyphenated().to_string(), id);
+            assert_eq!(uuid.get_version(), Some(uuid::Version::Random));
+            assert_eq!(uuid.get_variant(), Some(uuid::Variant::RFC4122));
+        }
+
+        after[0].revert(&conn).unwrap();
+    }
+}
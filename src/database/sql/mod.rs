@@ -1,18 +1,46 @@
 use crate::database;
-use crate::models::{Exercise, NewExercise, UpdatedExercise};
+use crate::difficulty;
+use crate::models::{
+    slugify, Exercise, ExerciseRevision, NewExercise, NewExerciseRevision,
+    NewExerciseRevisionBuilder, NewTag, NewTagBuilder, NewTopic, NewTopicBuilder, NewTypingAttempt,
+    NewUser, Tag, Topic, TypingAttempt, UpdatedExercise, User,
+};
+use database::query::{
+    AttemptQuery, ExerciseFilter, ExerciseQuery, ExerciseSort, ExerciseSortField, SortDirection,
+    Trashed,
+};
+use database::search;
 use database::IntoDatabaseError;
-use database::{Create, DeleteById, ExerciseDao, FindById, Update};
+use database::{
+    AttemptDao, CountExercises, Create, CreateAll, CreateBatch, DeleteById, DeleteByIds,
+    ExerciseDao, ExerciseLinks, ExerciseRevisions, FindById, FindByName, List, RateDifficulty,
+    RevisionDao, Search, SearchHit, TagDao, TopicDao, Trash, Update, UpdateBatch, UserDao,
+};
 
 use diesel::backend::{Backend, SupportsDefaultKeyword, UsesAnsiSavepointSyntax};
+use diesel::expression::BoxableExpression;
 use diesel::prelude::*;
+use diesel::query_dsl::GroupByDsl;
+use diesel::r2d2::ManageConnection;
+use diesel::result::DatabaseErrorKind;
+use diesel::sql_types::{BigInt, Double, Integer, Nullable, Text, Timestamp, Varchar};
 
 pub use diesel::mysql::MysqlConnection;
 pub use diesel::pg::PgConnection;
 pub use diesel::r2d2::ConnectionManager;
 pub use diesel::r2d2::PooledConnection;
 
+use diesel::sqlite::Sqlite;
+use std::collections::{HashMap, HashSet};
+
 use schema::*;
 
+/// Migrations embedded in the binary for each of the supported SQL dialects.
+pub mod migrations;
+
+/// Connection pools for the supported SQL dialects.
+pub mod pool;
+
 /// Auto-generated module created by Diesel from the schema defined by the migrations in
 /// "migrations/" for the purpose of constructing and validating SQL queries at compile-time.
 ///
@@ -29,177 +57,3017 @@ pub mod schema;
 //
 // See https://doc.rust-lang.org/book/ch19-02-advanced-lifetimes.html#lifetime-bounds-on-references-to-generic-types.
 
-/// Blanket `ExerciseDao` implementation for SQL backends.
-impl<Conn, DB: 'static> ExerciseDao for Conn
+sql_function!(fn lower(x: Text) -> Text);
+sql_function!(fn char_length(x: Text) -> Integer);
+sql_function!(fn length(x: Text) -> Integer);
+sql_function!(fn coalesce(x: Nullable<Integer>, y: Integer) -> Integer);
+
+/// SQL backends that can count the characters (as opposed to the bytes) of a text column.
+pub trait CharLength: Backend {
+    /// Returns an expression evaluating to the number of characters in `column`.
+    fn char_length<'a, C>(
+        column: C,
+    ) -> Box<dyn BoxableExpression<exercises::table, Self, SqlType = Integer> + 'a>
+    where
+        C: BoxableExpression<exercises::table, Self, SqlType = Text> + 'a;
+}
+
+impl CharLength for diesel::pg::Pg {
+    fn char_length<'a, C>(
+        column: C,
+    ) -> Box<dyn BoxableExpression<exercises::table, Self, SqlType = Integer> + 'a>
+    where
+        C: BoxableExpression<exercises::table, Self, SqlType = Text> + 'a,
+    {
+        Box::new(char_length(column))
+    }
+}
+
+impl CharLength for diesel::mysql::Mysql {
+    fn char_length<'a, C>(
+        column: C,
+    ) -> Box<dyn BoxableExpression<exercises::table, Self, SqlType = Integer> + 'a>
+    where
+        C: BoxableExpression<exercises::table, Self, SqlType = Text> + 'a,
+    {
+        Box::new(char_length(column))
+    }
+}
+
+impl CharLength for diesel::sqlite::Sqlite {
+    // NOTE: SQLite has no `char_length` function, but its `length` function counts characters.
+    fn char_length<'a, C>(
+        column: C,
+    ) -> Box<dyn BoxableExpression<exercises::table, Self, SqlType = Integer> + 'a>
+    where
+        C: BoxableExpression<exercises::table, Self, SqlType = Text> + 'a,
+    {
+        Box::new(length(column))
+    }
+}
+
+/// SQL backends that can write an exercise and return the affected row.
+///
+/// The methods are called inside a transaction, so the returned row is exactly the row that was
+/// written (or removed), even with concurrent writers.
+pub trait WriteExercise: Backend + Sized {
+    /// Inserts `obj`, returning the inserted row.
+    fn insert_exercise<C>(conn: &C, obj: &NewExercise) -> QueryResult<Exercise>
+    where
+        C: Connection<Backend = Self>;
+
+    /// Updates the exercise identified by `obj` if it is at the expected version (if any),
+    /// returning the updated row, or `None` if no row was updated.
+    fn update_exercise<C>(conn: &C, obj: &UpdatedExercise) -> QueryResult<Option<Exercise>>
+    where
+        C: Connection<Backend = Self>;
+
+    /// Moves the exercise with the given id to the trash (if `deleted_on` is set and it is not
+    /// trashed) or out of it (if `deleted_on` is `None` and it is trashed), returning the updated
+    /// row, or `None` if no row was updated.
+    fn set_exercise_deleted_on<C>(
+        conn: &C,
+        id: &str,
+        deleted_on: Option<chrono::NaiveDateTime>,
+    ) -> QueryResult<Option<Exercise>>
+    where
+        C: Connection<Backend = Self>;
+
+    /// Deletes the exercise with the given id, returning the deleted row.
+    fn delete_exercise<C>(conn: &C, id: &str) -> QueryResult<Exercise>
+    where
+        C: Connection<Backend = Self>;
+
+    /// Finds the exercise with the given id if it is not trashed, locking it so that it cannot be
+    /// trashed or purged until the transaction ends.
+    fn lock_exercise<C>(conn: &C, id: &str) -> QueryResult<Exercise>
+    where
+        C: Connection<Backend = Self>;
+
+    /// Inserts `obj`.
+    fn insert_revision<C>(conn: &C, obj: &NewExerciseRevision) -> QueryResult<()>
+    where
+        C: Connection<Backend = Self>;
+
+    /// Inserts `obj`.
+    fn insert_topic<C>(conn: &C, obj: &NewTopic) -> QueryResult<()>
+    where
+        C: Connection<Backend = Self>;
+
+    /// Links the exercise with id `exercise_id` to the topic with id `topic_id`.
+    fn insert_exercise_topic<C>(conn: &C, exercise_id: &str, topic_id: &str) -> QueryResult<()>
+    where
+        C: Connection<Backend = Self>;
+}
+
+/// Exercises targeted by an `UpdatedExercise` (see `updated_exercise_target`).
+type UpdatedExerciseTarget<'a> = diesel::dsl::Filter<
+    diesel::dsl::Filter<
+        diesel::dsl::Find<exercises::table, &'a str>,
+        diesel::dsl::Eq<exercises::version, coalesce::HelperType<Option<i32>, exercises::version>>,
+    >,
+    diesel::dsl::IsNull<exercises::deleted_on>,
+>;
+
+/// Returns the exercises targeted by `obj`: the exercise with its id, if it is at the expected
+/// version (if any) and not trashed.
+fn updated_exercise_target<'a>(obj: &'a UpdatedExercise) -> UpdatedExerciseTarget<'a> {
+    exercises::table
+        .find(obj.get_id())
+        .filter(exercises::version.eq(coalesce(obj.expected_version, exercises::version)))
+        .filter(exercises::deleted_on.is_null())
+}
+
+/// Exercises targeted by `WriteExercise::set_exercise_deleted_on` (see `trash_target`).
+type TrashTarget<'a> = diesel::dsl::Filter<
+    diesel::dsl::Find<exercises::table, &'a str>,
+    diesel::dsl::Eq<diesel::dsl::IsNull<exercises::deleted_on>, bool>,
+>;
+
+/// Returns the exercise with the given id if it is not trashed (when `trash`ing it) or trashed
+/// (when restoring it).
+fn trash_target(id: &str, trash: bool) -> TrashTarget<'_> {
+    exercises::table
+        .find(id)
+        .filter(exercises::deleted_on.is_null().eq(trash))
+}
+
+/// PostgreSQL returns the affected rows with a `RETURNING` clause.
+impl WriteExercise for diesel::pg::Pg {
+    fn insert_exercise<C>(conn: &C, obj: &NewExercise) -> QueryResult<Exercise>
+    where
+        C: Connection<Backend = Self>,
+    {
+        diesel::insert_into(exercises::table)
+            .values(obj)
+            .get_result(conn)
+    }
+
+    fn update_exercise<C>(conn: &C, obj: &UpdatedExercise) -> QueryResult<Option<Exercise>>
+    where
+        C: Connection<Backend = Self>,
+    {
+        diesel::update(updated_exercise_target(obj))
+            .set((obj, exercises::version.eq(exercises::version + 1)))
+            .get_result(conn)
+            .optional()
+    }
+
+    fn set_exercise_deleted_on<C>(
+        conn: &C,
+        id: &str,
+        deleted_on: Option<chrono::NaiveDateTime>,
+    ) -> QueryResult<Option<Exercise>>
+    where
+        C: Connection<Backend = Self>,
+    {
+        diesel::update(trash_target(id, deleted_on.is_some()))
+            .set(exercises::deleted_on.eq(deleted_on))
+            .get_result(conn)
+            .optional()
+    }
+
+    fn delete_exercise<C>(conn: &C, id: &str) -> QueryResult<Exercise>
+    where
+        C: Connection<Backend = Self>,
+    {
+        diesel::delete(exercises::table.find(id)).get_result(conn)
+    }
+
+    fn lock_exercise<C>(conn: &C, id: &str) -> QueryResult<Exercise>
+    where
+        C: Connection<Backend = Self>,
+    {
+        exercises::table
+            .find(id)
+            .filter(exercises::deleted_on.is_null())
+            .for_update()
+            .first(conn)
+    }
+
+    fn insert_revision<C>(conn: &C, obj: &NewExerciseRevision) -> QueryResult<()>
+    where
+        C: Connection<Backend = Self>,
+    {
+        diesel::insert_into(exercise_revisions::table)
+            .values(obj)
+            .execute(conn)
+            .map(|_| ())
+    }
+
+    fn insert_topic<C>(conn: &C, obj: &NewTopic) -> QueryResult<()>
+    where
+        C: Connection<Backend = Self>,
+    {
+        diesel::insert_into(topics::table)
+            .values(obj)
+            .execute(conn)
+            .map(|_| ())
+    }
+
+    fn insert_exercise_topic<C>(conn: &C, exercise_id: &str, topic_id: &str) -> QueryResult<()>
+    where
+        C: Connection<Backend = Self>,
+    {
+        diesel::insert_into(exercise_topics::table)
+            .values((
+                exercise_topics::exercise_id.eq(exercise_id),
+                exercise_topics::topic_id.eq(topic_id),
+            ))
+            .execute(conn)
+            .map(|_| ())
+    }
+}
+
+/// MySQL has no `RETURNING` clause, so the affected row is read (and locked) in the same
+/// transaction.
+impl WriteExercise for diesel::mysql::Mysql {
+    fn insert_exercise<C>(conn: &C, obj: &NewExercise) -> QueryResult<Exercise>
+    where
+        C: Connection<Backend = Self>,
+    {
+        diesel::insert_into(exercises::table)
+            .values(obj)
+            .execute(conn)?;
+        exercises::table.find(obj.get_id()).first(conn)
+    }
+
+    // NOTE: MySQL counts the rows that were changed rather than matched. Updates always change the
+    // modification time and version, so a matched row is always changed.
+    fn update_exercise<C>(conn: &C, obj: &UpdatedExercise) -> QueryResult<Option<Exercise>>
+    where
+        C: Connection<Backend = Self>,
+    {
+        let updated = diesel::update(updated_exercise_target(obj))
+            .set((obj, exercises::version.eq(exercises::version + 1)))
+            .execute(conn)?;
+        if updated == 0 {
+            return Ok(None);
+        }
+        exercises::table.find(obj.get_id()).first(conn).map(Some)
+    }
+
+    fn set_exercise_deleted_on<C>(
+        conn: &C,
+        id: &str,
+        deleted_on: Option<chrono::NaiveDateTime>,
+    ) -> QueryResult<Option<Exercise>>
+    where
+        C: Connection<Backend = Self>,
+    {
+        let updated = diesel::update(trash_target(id, deleted_on.is_some()))
+            .set(exercises::deleted_on.eq(deleted_on))
+            .execute(conn)?;
+        if updated == 0 {
+            return Ok(None);
+        }
+        exercises::table.find(id).first(conn).map(Some)
+    }
+
+    fn delete_exercise<C>(conn: &C, id: &str) -> QueryResult<Exercise>
+    where
+        C: Connection<Backend = Self>,
+    {
+        let exercise = exercises::table.find(id).for_update().first(conn)?;
+        diesel::delete(exercises::table.find(id)).execute(conn)?;
+        Ok(exercise)
+    }
+
+    fn lock_exercise<C>(conn: &C, id: &str) -> QueryResult<Exercise>
+    where
+        C: Connection<Backend = Self>,
+    {
+        exercises::table
+            .find(id)
+            .filter(exercises::deleted_on.is_null())
+            .for_update()
+            .first(conn)
+    }
+
+    fn insert_revision<C>(conn: &C, obj: &NewExerciseRevision) -> QueryResult<()>
+    where
+        C: Connection<Backend = Self>,
+    {
+        diesel::insert_into(exercise_revisions::table)
+            .values(obj)
+            .execute(conn)
+            .map(|_| ())
+    }
+
+    fn insert_topic<C>(conn: &C, obj: &NewTopic) -> QueryResult<()>
+    where
+        C: Connection<Backend = Self>,
+    {
+        diesel::insert_into(topics::table)
+            .values(obj)
+            .execute(conn)
+            .map(|_| ())
+    }
+
+    fn insert_exercise_topic<C>(conn: &C, exercise_id: &str, topic_id: &str) -> QueryResult<()>
+    where
+        C: Connection<Backend = Self>,
+    {
+        diesel::insert_into(exercise_topics::table)
+            .values((
+                exercise_topics::exercise_id.eq(exercise_id),
+                exercise_topics::topic_id.eq(topic_id),
+            ))
+            .execute(conn)
+            .map(|_| ())
+    }
+}
+
+/// Diesel does not support SQLite's `RETURNING` clause, so the affected row is read in the same
+/// transaction. SQLite locks the whole database for writing, so no row locks are needed.
+impl WriteExercise for diesel::sqlite::Sqlite {
+    fn insert_exercise<C>(conn: &C, obj: &NewExercise) -> QueryResult<Exercise>
+    where
+        C: Connection<Backend = Self>,
+    {
+        diesel::insert_into(exercises::table)
+            .values(obj)
+            .execute(conn)?;
+        exercises::table.find(obj.get_id()).first(conn)
+    }
+
+    fn update_exercise<C>(conn: &C, obj: &UpdatedExercise) -> QueryResult<Option<Exercise>>
+    where
+        C: Connection<Backend = Self>,
+    {
+        let updated = diesel::update(updated_exercise_target(obj))
+            .set((obj, exercises::version.eq(exercises::version + 1)))
+            .execute(conn)?;
+        if updated == 0 {
+            return Ok(None);
+        }
+        exercises::table.find(obj.get_id()).first(conn).map(Some)
+    }
+
+    fn set_exercise_deleted_on<C>(
+        conn: &C,
+        id: &str,
+        deleted_on: Option<chrono::NaiveDateTime>,
+    ) -> QueryResult<Option<Exercise>>
+    where
+        C: Connection<Backend = Self>,
+    {
+        let updated = diesel::update(trash_target(id, deleted_on.is_some()))
+            .set(exercises::deleted_on.eq(deleted_on))
+            .execute(conn)?;
+        if updated == 0 {
+            return Ok(None);
+        }
+        exercises::table.find(id).first(conn).map(Some)
+    }
+
+    fn delete_exercise<C>(conn: &C, id: &str) -> QueryResult<Exercise>
+    where
+        C: Connection<Backend = Self>,
+    {
+        let exercise = exercises::table.find(id).first(conn)?;
+        diesel::delete(exercises::table.find(id)).execute(conn)?;
+        Ok(exercise)
+    }
+
+    fn lock_exercise<C>(conn: &C, id: &str) -> QueryResult<Exercise>
+    where
+        C: Connection<Backend = Self>,
+    {
+        exercises::table
+            .find(id)
+            .filter(exercises::deleted_on.is_null())
+            .first(conn)
+    }
+
+    fn insert_revision<C>(conn: &C, obj: &NewExerciseRevision) -> QueryResult<()>
+    where
+        C: Connection<Backend = Self>,
+    {
+        diesel::insert_into(exercise_revisions::table)
+            .values(obj)
+            .execute(conn)
+            .map(|_| ())
+    }
+
+    fn insert_topic<C>(conn: &C, obj: &NewTopic) -> QueryResult<()>
+    where
+        C: Connection<Backend = Self>,
+    {
+        diesel::insert_into(topics::table)
+            .values(obj)
+            .execute(conn)
+            .map(|_| ())
+    }
+
+    fn insert_exercise_topic<C>(conn: &C, exercise_id: &str, topic_id: &str) -> QueryResult<()>
+    where
+        C: Connection<Backend = Self>,
+    {
+        diesel::insert_into(exercise_topics::table)
+            .values((
+                exercise_topics::exercise_id.eq(exercise_id),
+                exercise_topics::topic_id.eq(topic_id),
+            ))
+            .execute(conn)
+            .map(|_| ())
+    }
+}
+
+/// Records the current version of `exercise` as a revision made by `author_id`.
+fn record_revision<C, DB>(conn: &C, exercise: &Exercise, author_id: Option<&str>) -> QueryResult<()>
 where
-    Conn: for<'a> FindById<&'a str, Exercise>,
-    Conn: Connection<Backend = DB>,
-    DB: Backend<RawValue = [u8]>,
-    DB: SupportsDefaultKeyword,
-    DB: UsesAnsiSavepointSyntax,
-    chrono::NaiveDateTime: diesel::serialize::ToSql<diesel::sql_types::Timestamp, DB>,
+    C: Connection<Backend = DB>,
+    DB: WriteExercise,
 {
+    let revision = NewExerciseRevisionBuilder::new(exercise)
+        .author_id(author_id)
+        .build();
+    DB::insert_revision(conn, &revision)
 }
 
-impl<'a, Conn, DB: 'static> Create<&'a NewExercise, Exercise> for Conn
+/// Links `exercise` to the topic named by its free-text topic (if any), creating the topic if no
+/// topic has its slug yet, the way `migrations::convert_exercise_topics` converts existing
+/// exercises. If `previous_topic` is given, the exercise is unlinked from the topic it names
+/// first.
+fn link_topic_by_name<C, DB>(
+    conn: &C,
+    exercise: &Exercise,
+    previous_topic: Option<&str>,
+) -> database::Result<()>
 where
-    Conn: for<'b> FindById<&'b str, Exercise>,
-    Conn: Connection<Backend = DB>,
-    DB: Backend,
-    DB: SupportsDefaultKeyword,
-    chrono::NaiveDateTime: diesel::serialize::ToSql<diesel::sql_types::Timestamp, DB>,
+    C: Connection<Backend = DB>,
+    DB: WriteExercise,
+    String: Queryable<Varchar, DB>,
 {
-    fn create(&self, obj: &NewExercise) -> database::Result<Exercise> {
-        diesel::insert_into(exercises::table)
-            .values(obj)
-            .execute(self)
-            .map_err(IntoDatabaseError::into_database_error)?;
+    let slug = exercise.topic.as_deref().map(slugify).unwrap_or_default();
+    if let Some(previous_slug) = previous_topic.map(slugify) {
+        if previous_slug != slug {
+            diesel::delete(
+                exercise_topics::table
+                    .filter(exercise_topics::exercise_id.eq(&exercise.id))
+                    .filter(
+                        exercise_topics::topic_id.eq_any(
+                            topics::table
+                                .filter(topics::slug.eq(previous_slug))
+                                .select(topics::id),
+                        ),
+                    ),
+            )
+            .execute(conn)?;
+        }
+    }
+    let name = match exercise.topic.as_deref() {
+        Some(name) if !slug.is_empty() => name,
+        _ => return Ok(()),
+    };
+    let existing = topics::table
+        .filter(topics::slug.eq(&slug))
+        .select(topics::id)
+        .first::<String>(conn)
+        .optional()?;
+    let topic_id = match existing {
+        Some(topic_id) => topic_id,
+        None => {
+            let new_topic = NewTopicBuilder::new().name(name).slug(&slug).build()?;
+            DB::insert_topic(conn, &new_topic)?;
+            String::from(new_topic.get_id())
+        }
+    };
+    let linked = exercise_topics::table
+        .filter(exercise_topics::exercise_id.eq(&exercise.id))
+        .filter(exercise_topics::topic_id.eq(&topic_id))
+        .select(exercise_topics::topic_id)
+        .first::<String>(conn)
+        .optional()?;
+    if linked.is_none() {
+        DB::insert_exercise_topic(conn, &exercise.id, &topic_id)?;
+    }
+    Ok(())
+}
 
-        self.find_by_id(obj.get_id())
+/// Returns the free-text topic of the exercise `obj` updates, if `obj` changes it.
+fn previous_topic<C, DB>(conn: &C, obj: &UpdatedExercise) -> QueryResult<Option<String>>
+where
+    C: Connection<Backend = DB>,
+    DB: Backend,
+    Option<String>: Queryable<Nullable<Varchar>, DB>,
+{
+    if obj.topic.is_none() {
+        return Ok(None);
     }
+    exercises::table
+        .find(obj.get_id())
+        .select(exercises::topic)
+        .first::<Option<String>>(conn)
+        .optional()
+        .map(Option::flatten)
 }
 
-impl<'a, Conn, DB: 'static> FindById<&'a str, Exercise> for Conn
+/// Number of exercises rated per query by `rate_unrated_exercises`.
+const RATING_CHUNK_SIZE: i64 = 500;
+
+/// Stores the difficulty of every exercise that has none, returning the number of rated exercises.
+///
+/// Exercises are rated in chunks, each in its own transaction. An exercise updated concurrently is
+/// rated by its update, so it is skipped.
+fn rate_unrated_exercises<Conn, DB>(conn: &Conn) -> database::Result<usize>
 where
     Conn: Connection<Backend = DB>,
-    DB: Backend<RawValue = [u8]>,
-    DB: UsesAnsiSavepointSyntax,
-    chrono::NaiveDateTime: diesel::deserialize::FromSql<diesel::sql_types::Timestamp, DB>,
+    DB: Backend + 'static,
+    (String, String): Queryable<(Varchar, Text), DB>,
 {
-    fn find_by_id(&self, id: &'a str) -> database::Result<Exercise> {
-        exercises::table
-            .find(id)
-            .first(self)
-            .map_err(IntoDatabaseError::into_database_error)
+    let mut rated = 0;
+    loop {
+        let unrated: Vec<(String, String)> = exercises::table
+            .select((exercises::id, exercises::body))
+            .filter(exercises::difficulty.is_null())
+            .limit(RATING_CHUNK_SIZE)
+            .load(conn)?;
+        if unrated.is_empty() {
+            return Ok(rated);
+        }
+        conn.transaction::<_, database::Error, _>(|| {
+            for (id, body) in &unrated {
+                diesel::update(
+                    exercises::table
+                        .find(id)
+                        .filter(exercises::difficulty.is_null()),
+                )
+                .set(exercises::difficulty.eq(difficulty::difficulty(body)))
+                .execute(conn)?;
+            }
+            Ok(())
+        })?;
+        rated += unrated.len();
     }
 }
 
-impl<'a, Conn, DB: 'static> Update<&'a UpdatedExercise<'a>, Exercise> for Conn
+/// Escapes the `LIKE` wildcards in `s` (using `\` as the escape character).
+fn escape_like(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Builds a query selecting the exercises matching `filter`.
+fn filter_exercises<'a, DB>(filter: &'a ExerciseFilter) -> exercises::BoxedQuery<'a, DB>
 where
-    Conn: for<'b> FindById<&'b str, Exercise>,
-    Conn: Connection<Backend = DB>,
-    DB: Backend,
-    DB: SupportsDefaultKeyword,
+    DB: CharLength + 'static,
     chrono::NaiveDateTime: diesel::serialize::ToSql<diesel::sql_types::Timestamp, DB>,
 {
-    fn update(&self, obj: &'a UpdatedExercise<'a>) -> database::Result<Exercise> {
-        diesel::update(exercises::table)
-            .set(obj)
-            .execute(self)
-            .map_err(IntoDatabaseError::into_database_error)
-            .and_then(|_| self.find_by_id(&obj.get_id()))
+    let mut query = exercises::table.into_boxed();
+    if let Some(topic) = &filter.topic {
+        query = query.filter(exercises::topic.eq(topic));
+    }
+    if let Some(topic_id) = &filter.topic_id {
+        query = query.filter(
+            exercises::id.eq_any(
+                exercise_topics::table
+                    .filter(exercise_topics::topic_id.eq(topic_id))
+                    .select(exercise_topics::exercise_id),
+            ),
+        );
+    }
+    if let Some(names) = filter.tags_any.as_ref().filter(|names| !names.is_empty()) {
+        query = query.filter(
+            exercises::id.eq_any(
+                exercise_tags::table
+                    .inner_join(tags::table)
+                    .filter(tags::name.eq_any(names))
+                    .select(exercise_tags::exercise_id),
+            ),
+        );
+    }
+    for name in filter.tags_all.iter().flatten() {
+        query = query.filter(
+            exercises::id.eq_any(
+                exercise_tags::table
+                    .inner_join(tags::table)
+                    .filter(tags::name.eq(name))
+                    .select(exercise_tags::exercise_id),
+            ),
+        );
+    }
+    if let Some(title) = &filter.title_contains {
+        let pattern = format!("%{}%", escape_like(&title.to_lowercase()));
+        query = query.filter(lower(exercises::title).like(pattern).escape('\\'));
+    }
+    if let Some(min) = filter.min_body_length {
+        query = query.filter(DB::char_length(exercises::body).ge(min));
+    }
+    if let Some(max) = filter.max_body_length {
+        query = query.filter(DB::char_length(exercises::body).le(max));
+    }
+    if let Some(after) = filter.created_after {
+        query = query.filter(exercises::created_on.ge(after));
+    }
+    if let Some(before) = filter.created_before {
+        query = query.filter(exercises::created_on.le(before));
+    }
+    if let Some(after) = filter.modified_after {
+        query = query.filter(exercises::modified_on.ge(after));
+    }
+    if let Some(before) = filter.modified_before {
+        query = query.filter(exercises::modified_on.le(before));
+    }
+    if let Some(series_id) = &filter.series_id {
+        query = query.filter(exercises::series_id.eq(series_id));
+    }
+    if let Some(part_index) = filter.part_index {
+        query = query.filter(exercises::part_index.eq(part_index));
+    }
+    if let Some(min) = filter.min_part_index {
+        query = query.filter(exercises::part_index.ge(min));
+    }
+    if let Some(max) = filter.max_part_index {
+        query = query.filter(exercises::part_index.le(max));
+    }
+    if let Some(min) = filter.min_difficulty {
+        query = query.filter(exercises::difficulty.ge(min));
     }
+    if let Some(max) = filter.max_difficulty {
+        query = query.filter(exercises::difficulty.le(max));
+    }
+    match filter.trashed {
+        Trashed::Exclude => query = query.filter(exercises::deleted_on.is_null()),
+        Trashed::Include => (),
+        Trashed::Only => query = query.filter(exercises::deleted_on.is_not_null()),
+    }
+    query
 }
 
-impl<'a, Conn, DB: 'static> DeleteById<&'a str, Exercise> for Conn
+/// Orders the exercises selected by `query` by `sort`, breaking ties by id.
+fn sort_exercises<'a, DB>(
+    query: exercises::BoxedQuery<'a, DB>,
+    sort: ExerciseSort,
+) -> exercises::BoxedQuery<'a, DB>
 where
-    Conn: Connection<Backend = DB>,
-    Conn: for<'b> FindById<&'b str, Exercise>,
-    DB: Backend,
+    DB: CharLength + 'static,
 {
-    fn delete_by_id(&self, id: &'a str) -> database::Result<Exercise> {
-        let exercise = self.find_by_id(id);
-        diesel::delete(exercises::table.find(id))
-            .execute(self)
-            .map_err(IntoDatabaseError::into_database_error)
-            .and_then(|_| exercise)
+    use ExerciseSortField::*;
+    use SortDirection::*;
+
+    let query = match (sort.field, sort.direction) {
+        (Topic, Ascending) => query.order(exercises::topic.asc()),
+        (Topic, Descending) => query.order(exercises::topic.desc()),
+        (Title, Ascending) => query.order(exercises::title.asc()),
+        (Title, Descending) => query.order(exercises::title.desc()),
+        (BodyLength, Ascending) => query.order(DB::char_length(exercises::body).asc()),
+        (BodyLength, Descending) => query.order(DB::char_length(exercises::body).desc()),
+        (CreatedOn, Ascending) => query.order(exercises::created_on.asc()),
+        (CreatedOn, Descending) => query.order(exercises::created_on.desc()),
+        (ModifiedOn, Ascending) => query.order(exercises::modified_on.asc()),
+        (ModifiedOn, Descending) => query.order(exercises::modified_on.desc()),
+        (PartIndex, Ascending) => query.order(exercises::part_index.asc()),
+        (PartIndex, Descending) => query.order(exercises::part_index.desc()),
+        (Difficulty, Ascending) => query.order(exercises::difficulty.asc()),
+        (Difficulty, Descending) => query.order(exercises::difficulty.desc()),
+    };
+    query.then_order_by(exercises::id.asc())
+}
+
+/// Builds a query selecting the exercises whose title or body contains every one of `terms`
+/// (case-insensitively).
+fn exercises_containing<'a, DB>(terms: &[String]) -> exercises::BoxedQuery<'a, DB>
+where
+    DB: Backend + 'static,
+{
+    let mut query = exercises::table
+        .filter(exercises::deleted_on.is_null())
+        .into_boxed();
+    for term in terms {
+        let pattern = format!("%{}%", escape_like(term));
+        query = query.filter(
+            lower(exercises::title)
+                .like(pattern.clone())
+                .escape('\\')
+                .or(lower(exercises::body).like(pattern).escape('\\')),
+        );
     }
+    query
 }
 
-/// Newtype for implementing `ExerciseDao` on a `diesel::sqlite::SqliteConnection` without
-/// conflicting with the blanket `ExerciseDao` implementation for SQL backends.
-///
-/// # Examples
-///
-/// ```
-/// use database::ExerciseDao;
-/// use diesel::prelude::*;
-/// use wikitype_api::database;
-/// use wikitype_api::models::{Exercise, NewExerciseBuilder};
-///
-/// const ALBATROSS_BODY: &'static str =
-///     "Albatrosses, of the biological family Diomedeidae, are large seabirds related to the \
-///      procellariids, storm petrels, and diving petrels in the order Procellariiformes (the \
-///      tubenoses).";
-///
-/// // Create an in-memory SQLite database and create the `exercises` table.
-/// let dao = database::sql::SqliteConnection(
-///     SqliteConnection::establish(":memory:")
-///         .expect(&format!("Error creating in-memory SQLite database.")),
-/// );
-/// let create_table =
-///     std::fs::read_to_string("./migrations/2019-06-02-153217_create_exercises/up.sql").unwrap();
-/// diesel::sql_query(create_table).execute(&dao.0).unwrap();
-///
-/// let dao: &dyn ExerciseDao = &dao;
-///
-/// // Create a new exercise.
-/// let new_exercise = NewExerciseBuilder::new()
-///     .title("Albatross")
-///     .body(ALBATROSS_BODY)
-///     .build();
-///
-/// // Insert the new exercise into the database.
-/// let exercise = dao
-///     .create(&new_exercise)
-///     .expect("Failed to create Albatross exercise.");
-/// assert_eq!(&exercise.id, new_exercise.get_id());
-/// assert_eq!(exercise.title, new_exercise.title);
-/// assert_eq!(exercise.body, new_exercise.body);
-/// assert_eq!(exercise.topic.is_none(), new_exercise.topic.is_none());
-///
-/// // Delete the exercise.
-/// let deleted_exercise = dao
-///     .delete_by_id(&exercise.id)
-///     .expect("Failed to create Albatross exercise.");
-/// assert_eq!(exercise, deleted_exercise);
-///
-/// let exercise = dao.find_by_id(&exercise.id);
-/// assert_eq!(
-///     exercise,
-///     Err(database::Error::NotFound)
-/// );
-/// ```
-pub struct SqliteConnection(pub diesel::SqliteConnection);
+/// Builds a query selecting the typing attempts matching `query`, from newest to oldest.
+fn attempts_matching<'a, DB>(query: &'a AttemptQuery) -> typing_attempts::BoxedQuery<'a, DB>
+where
+    DB: Backend + 'static,
+{
+    typing_attempts::table
+        .filter(typing_attempts::exercise_id.eq(&query.exercise_id))
+        .order((
+            typing_attempts::started_on.desc(),
+            typing_attempts::id.asc(),
+        ))
+        .into_boxed()
+}
 
-impl ExerciseDao for SqliteConnection {}
+/// Checks the result of updating an exercise, calling `find_current` to tell a missing exercise
+/// from a stale `expected_version` if no row was updated.
+fn check_updated_exercise<F>(
+    updated: Option<Exercise>,
+    obj: &UpdatedExercise,
+    find_current: F,
+) -> database::Result<Exercise>
+where
+    F: FnOnce() -> database::Result<Exercise>,
+{
+    if let Some(exercise) = updated {
+        return Ok(exercise);
+    }
+    let current = find_current()?;
+    Err(database::Error::Conflict(format!(
+        "Exercise {} is at version {}, not the expected version {}",
+        current.id,
+        current.version,
+        obj.expected_version.unwrap_or(current.version)
+    )))
+}
 
-impl<'a> Create<&'a NewExercise, Exercise> for SqliteConnection {
-    fn create(&self, obj: &NewExercise) -> database::Result<Exercise> {
-        diesel::insert_into(exercises::table)
-            .values(obj)
-            .execute(&self.0)
-            .map_err(IntoDatabaseError::into_database_error)?;
+/// Checks the result of looking up a username that is about to be registered.
+fn check_username_available(existing: database::Result<User>) -> database::Result<()> {
+    match existing {
+        Ok(user) => Err(username_taken(&user.username)),
+        Err(database::Error::NotFound) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns the error of registering a username that is already taken.
+fn username_taken(username: &str) -> database::Error {
+    database::Error::Conflict(format!("Username {:?} is already taken", username))
+}
 
-        self.find_by_id(obj.get_id())
+/// Converts an error of an insert into a `database::Error`, reporting unique violations (e.g. of a
+/// name taken by a concurrent insert since it was checked) as `conflict()`.
+fn unique_violation_as<F>(e: diesel::result::Error, conflict: F) -> database::Error
+where
+    F: FnOnce() -> database::Error,
+{
+    match e {
+        diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => conflict(),
+        e => e.into_database_error(),
     }
 }
 
-impl<'a> FindById<&'a str, Exercise> for SqliteConnection {
-    fn find_by_id(&self, id: &'a str) -> database::Result<Exercise> {
-        exercises::table
-            .find(id)
-            .first(&self.0)
-            .map_err(IntoDatabaseError::into_database_error)
+/// Checks that `obj` can be created: that its slug is not taken yet, and that its parent (if any)
+/// exists.
+fn check_new_topic<D>(dao: &D, obj: &NewTopic) -> database::Result<()>
+where
+    D: for<'a> FindById<&'a str, Topic>,
+    D: for<'a> FindByName<&'a str, Topic>,
+{
+    match dao.find_by_name(obj.slug.as_str()) {
+        Ok(topic) => return Err(slug_taken(&topic.slug)),
+        Err(database::Error::NotFound) => (),
+        Err(e) => return Err(e),
     }
+    if let Some(parent_id) = &obj.parent_id {
+        let _: Topic = dao.find_by_id(parent_id.as_str())?;
+    }
+    Ok(())
 }
 
-impl<'a> Update<&'a UpdatedExercise<'a>, Exercise> for SqliteConnection {
-    fn update(&self, obj: &'a UpdatedExercise<'a>) -> database::Result<Exercise> {
-        diesel::update(exercises::table)
-            .set(obj)
-            .execute(&self.0)
-            .map_err(IntoDatabaseError::into_database_error)
-            .and_then(|_| self.find_by_id(&obj.get_id()))
+/// Converts an error of inserting a typing attempt into a `database::Error`, reporting foreign key
+/// violations (i.e. attempts at an exercise purged concurrently) as `Error::NotFound`.
+fn attempt_exercise_not_found(e: diesel::result::Error) -> database::Error {
+    match e {
+        diesel::result::Error::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => {
+            database::Error::NotFound
+        }
+        e => e.into_database_error(),
     }
 }
 
-impl<'a> DeleteById<&'a str, Exercise> for SqliteConnection {
-    fn delete_by_id(&self, id: &'a str) -> database::Result<Exercise> {
-        let exercise = self.find_by_id(id);
-        diesel::delete(exercises::table.find(id))
-            .execute(&self.0)
-            .map_err(IntoDatabaseError::into_database_error)
-            .and_then(|_| exercise)
+/// Returns the error of creating a topic with a slug that is already taken.
+fn slug_taken(slug: &str) -> database::Error {
+    database::Error::Conflict(format!("Slug {:?} is already taken", slug))
+}
+
+/// Fails with `Error::NotFound` unless the exercise with the given id exists (and is not trashed).
+fn check_exercise_exists<Conn, DB>(conn: &Conn, id: &str) -> database::Result<()>
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend + 'static,
+    String: Queryable<Varchar, DB>,
+{
+    exercises::table
+        .find(id)
+        .filter(exercises::deleted_on.is_null())
+        .select(exercises::id)
+        .first::<String>(conn)
+        .map(|_| ())
+        .map_err(IntoDatabaseError::into_database_error)
+}
+
+/// Loads the topics linked to the exercise with the given id, sorted by name.
+fn load_linked_topics<Conn, DB>(conn: &Conn, exercise_id: &str) -> database::Result<Vec<Topic>>
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend + 'static,
+    String: Queryable<Varchar, DB>,
+    Topic: Queryable<topics::SqlType, DB>,
+{
+    topics::table
+        .filter(
+            topics::id.eq_any(
+                exercise_topics::table
+                    .filter(exercise_topics::exercise_id.eq(exercise_id))
+                    .select(exercise_topics::topic_id),
+            ),
+        )
+        .order((topics::name.asc(), topics::id.asc()))
+        .load(conn)
+        .map_err(IntoDatabaseError::into_database_error)
+}
+
+/// Links the topics with the given ids to an exercise in a transaction, calling `insert_link` to
+/// link each topic that is not linked yet.
+fn link_topics<Conn, DB, F>(
+    conn: &Conn,
+    exercise_id: &str,
+    ids: &[&str],
+    insert_link: F,
+) -> database::Result<Vec<Topic>>
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend + 'static,
+    String: Queryable<Varchar, DB>,
+    Topic: Queryable<topics::SqlType, DB>,
+    F: Fn(&str) -> QueryResult<usize>,
+{
+    conn.transaction(|| {
+        check_exercise_exists(conn, exercise_id)?;
+        let mut linked: HashSet<String> = exercise_topics::table
+            .filter(exercise_topics::exercise_id.eq(exercise_id))
+            .select(exercise_topics::topic_id)
+            .load(conn)?
+            .into_iter()
+            .collect();
+        for &id in ids {
+            if linked.contains(id) {
+                continue;
+            }
+            topics::table
+                .find(id)
+                .select(topics::id)
+                .first::<String>(conn)?;
+            insert_link(id)?;
+            linked.insert(String::from(id));
+        }
+        load_linked_topics(conn, exercise_id)
+    })
+}
+
+/// Unlinks the topics with the given ids from an exercise in a transaction.
+fn unlink_topics<Conn, DB>(
+    conn: &Conn,
+    exercise_id: &str,
+    ids: &[&str],
+) -> database::Result<Vec<Topic>>
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend + 'static,
+    String: Queryable<Varchar, DB>,
+    Topic: Queryable<topics::SqlType, DB>,
+{
+    conn.transaction(|| {
+        check_exercise_exists(conn, exercise_id)?;
+        diesel::delete(
+            exercise_topics::table
+                .filter(exercise_topics::exercise_id.eq(exercise_id))
+                .filter(exercise_topics::topic_id.eq_any(ids)),
+        )
+        .execute(conn)?;
+        load_linked_topics(conn, exercise_id)
+    })
+}
+
+/// Loads every topic, sorted by name, along with the number of exercises linked to it.
+fn count_topic_exercises<Conn, DB>(conn: &Conn) -> database::Result<Vec<(Topic, i64)>>
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend + 'static,
+    (String, i64): Queryable<(Varchar, BigInt), DB>,
+    Topic: Queryable<topics::SqlType, DB>,
+{
+    let topics: Vec<Topic> = topics::table
+        .order((topics::name.asc(), topics::id.asc()))
+        .load(conn)?;
+    // NOTE: Diesel does not check `GROUP BY` clauses, so the count is selected as plain SQL.
+    let counts: HashMap<String, i64> = exercise_topics::table
+        .inner_join(exercises::table)
+        .filter(exercises::deleted_on.is_null())
+        .group_by(exercise_topics::topic_id)
+        .select((
+            exercise_topics::topic_id,
+            diesel::dsl::sql::<BigInt>("COUNT(*)"),
+        ))
+        .load(conn)?
+        .into_iter()
+        .collect();
+    Ok(topics
+        .into_iter()
+        .map(|topic| {
+            let count = counts.get(&topic.id).cloned().unwrap_or(0);
+            (topic, count)
+        })
+        .collect())
+}
+
+/// Loads the tags of the exercise with the given id, sorted by name.
+fn load_linked_tags<Conn, DB>(conn: &Conn, exercise_id: &str) -> database::Result<Vec<Tag>>
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend + 'static,
+    Tag: Queryable<tags::SqlType, DB>,
+{
+    tags::table
+        .filter(
+            tags::id.eq_any(
+                exercise_tags::table
+                    .filter(exercise_tags::exercise_id.eq(exercise_id))
+                    .select(exercise_tags::tag_id),
+            ),
+        )
+        .order(tags::name.asc())
+        .load(conn)
+        .map_err(IntoDatabaseError::into_database_error)
+}
+
+/// Tags an exercise with the given tag names in a transaction, calling `insert_tag` to create
+/// each tag that does not exist yet and `insert_link` to link each tag that is not linked yet.
+fn link_tags<Conn, DB, T, L>(
+    conn: &Conn,
+    exercise_id: &str,
+    names: &[&str],
+    insert_tag: T,
+    insert_link: L,
+) -> database::Result<Vec<Tag>>
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend + 'static,
+    String: Queryable<Varchar, DB>,
+    Tag: Queryable<tags::SqlType, DB>,
+    T: Fn(&NewTag) -> QueryResult<usize>,
+    L: Fn(&str) -> QueryResult<usize>,
+{
+    conn.transaction(|| {
+        check_exercise_exists(conn, exercise_id)?;
+        let mut linked: HashSet<String> = exercise_tags::table
+            .filter(exercise_tags::exercise_id.eq(exercise_id))
+            .select(exercise_tags::tag_id)
+            .load(conn)?
+            .into_iter()
+            .collect();
+        for &name in names {
+            let existing = tags::table
+                .filter(tags::name.eq(name))
+                .select(tags::id)
+                .first::<String>(conn)
+                .optional()?;
+            let tag_id = match existing {
+                Some(tag_id) => tag_id,
+                None => {
+                    let new_tag = NewTagBuilder::new().name(name).build()?;
+                    insert_tag(&new_tag)?;
+                    String::from(new_tag.get_id())
+                }
+            };
+            if !linked.contains(&tag_id) {
+                insert_link(&tag_id)?;
+                linked.insert(tag_id);
+            }
+        }
+        load_linked_tags(conn, exercise_id)
+    })
+}
+
+/// Removes the tags with the given names from an exercise in a transaction.
+fn unlink_tags<Conn, DB>(
+    conn: &Conn,
+    exercise_id: &str,
+    names: &[&str],
+) -> database::Result<Vec<Tag>>
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend + 'static,
+    String: Queryable<Varchar, DB>,
+    Tag: Queryable<tags::SqlType, DB>,
+{
+    conn.transaction(|| {
+        check_exercise_exists(conn, exercise_id)?;
+        diesel::delete(
+            exercise_tags::table
+                .filter(exercise_tags::exercise_id.eq(exercise_id))
+                .filter(
+                    exercise_tags::tag_id.eq_any(
+                        tags::table
+                            .filter(tags::name.eq_any(names))
+                            .select(tags::id),
+                    ),
+                ),
+        )
+        .execute(conn)?;
+        load_linked_tags(conn, exercise_id)
+    })
+}
+
+/// A row returned by a native full-text search query.
+#[derive(QueryableByName)]
+struct ExerciseSearchRow {
+    #[sql_type = "Varchar"]
+    id: String,
+    #[sql_type = "Varchar"]
+    title: String,
+    #[sql_type = "Text"]
+    body: String,
+    #[sql_type = "Nullable<Varchar>"]
+    topic: Option<String>,
+    #[sql_type = "Timestamp"]
+    created_on: chrono::NaiveDateTime,
+    #[sql_type = "Timestamp"]
+    modified_on: chrono::NaiveDateTime,
+    #[sql_type = "Nullable<Varchar>"]
+    author_id: Option<String>,
+    #[sql_type = "Integer"]
+    version: i32,
+    #[sql_type = "Nullable<Varchar>"]
+    series_id: Option<String>,
+    #[sql_type = "Nullable<Integer>"]
+    part_index: Option<i32>,
+    #[sql_type = "Nullable<Integer>"]
+    difficulty: Option<i32>,
+    #[sql_type = "Nullable<Timestamp>"]
+    deleted_on: Option<chrono::NaiveDateTime>,
+    #[sql_type = "Double"]
+    rank: f64,
+    #[sql_type = "Text"]
+    snippet: String,
+}
+
+impl From<ExerciseSearchRow> for SearchHit<Exercise> {
+    fn from(row: ExerciseSearchRow) -> SearchHit<Exercise> {
+        SearchHit {
+            item: Exercise {
+                id: row.id,
+                title: row.title,
+                body: row.body,
+                topic: row.topic,
+                created_on: row.created_on,
+                modified_on: row.modified_on,
+                author_id: row.author_id,
+                version: row.version,
+                series_id: row.series_id,
+                part_index: row.part_index,
+                difficulty: row.difficulty,
+                deleted_on: row.deleted_on,
+            },
+            rank: row.rank,
+            snippet: row.snippet,
+        }
+    }
+}
+
+/// Full-text search using Postgres' text search types and the `exercises_search_idx` index.
+///
+/// NOTE: The `tsvector` expression must match the one in the migration creating the index.
+const POSTGRES_SEARCH_QUERY: &str = "\
+    SELECT id, title, body, topic, created_on, modified_on, author_id, version, series_id, \
+        part_index, difficulty, deleted_on, \
+        ts_rank(setweight(to_tsvector('english', title), 'A') || \
+            setweight(to_tsvector('english', body), 'B'), query)::float8 AS rank, \
+        ts_headline('english', body, query, 'MaxWords=16, MinWords=8') AS snippet \
+    FROM exercises, plainto_tsquery('english', $1) AS query \
+    WHERE (setweight(to_tsvector('english', title), 'A') || \
+        setweight(to_tsvector('english', body), 'B')) @@ query \
+        AND deleted_on IS NULL \
+    ORDER BY rank DESC, id \
+    LIMIT $2";
+
+/// Full-text search using SQLite's FTS5 extension and the `exercises_fts` table.
+const SQLITE_SEARCH_QUERY: &str = "\
+    SELECT exercises.id, exercises.title, exercises.body, exercises.topic, \
+        exercises.created_on, exercises.modified_on, exercises.author_id, exercises.version, \
+        exercises.series_id, exercises.part_index, exercises.difficulty, exercises.deleted_on, \
+        -bm25(exercises_fts, 0.0, 2.0, 1.0) AS rank, \
+        snippet(exercises_fts, 2, '<b>', '</b>', '…', 16) AS snippet \
+    FROM exercises_fts \
+    JOIN exercises ON exercises.id = exercises_fts.id \
+    WHERE exercises_fts MATCH ? AND exercises.deleted_on IS NULL \
+    ORDER BY rank DESC, exercises.id \
+    LIMIT ?";
+
+impl<'a> Search<&'a str, Exercise> for PgConnection {
+    fn search(&self, query: &'a str, limit: i64) -> database::Result<Vec<SearchHit<Exercise>>> {
+        diesel::sql_query(POSTGRES_SEARCH_QUERY)
+            .bind::<Text, _>(query)
+            .bind::<BigInt, _>(limit)
+            .load::<ExerciseSearchRow>(self)
+            .map(|rows| rows.into_iter().map(SearchHit::from).collect())
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+/// MySQL uses the portable search implementation (see `database::search`).
+impl<'a> Search<&'a str, Exercise> for MysqlConnection {
+    fn search(&self, query: &'a str, limit: i64) -> database::Result<Vec<SearchHit<Exercise>>> {
+        let terms = search::search_terms(query);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let candidates = exercises_containing(&terms)
+            .load(self)
+            .map_err(IntoDatabaseError::into_database_error)?;
+        Ok(search::rank_exercises(candidates, &terms, limit as usize))
+    }
+}
+
+impl<'a, M> Search<&'a str, Exercise> for PooledConnection<M>
+where
+    M: ManageConnection,
+    M::Connection: Search<&'a str, Exercise>,
+{
+    fn search(&self, query: &'a str, limit: i64) -> database::Result<Vec<SearchHit<Exercise>>> {
+        (**self).search(query, limit)
+    }
+}
+
+impl<Conn, DB: 'static> RateDifficulty for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend<RawValue = [u8]>,
+{
+    fn rate_unrated(&self) -> database::Result<usize> {
+        rate_unrated_exercises(self)
+    }
+}
+
+/// Blanket `ExerciseDao` implementation for SQL backends.
+impl<Conn, DB: 'static> ExerciseDao for Conn
+where
+    Conn: for<'a> FindById<&'a str, Exercise>,
+    Conn: for<'a> List<&'a ExerciseQuery, Exercise>,
+    Conn: for<'a> Search<&'a str, Exercise>,
+    Conn: for<'a> Create<&'a NewExercise, Exercise>,
+    Conn: for<'a> Update<&'a UpdatedExercise<'a>, Exercise>,
+    Conn: for<'a> DeleteById<&'a str, Exercise>,
+    Conn: for<'a> Trash<&'a str, Exercise>,
+    Conn: Connection<Backend = DB>,
+    DB: Backend<RawValue = [u8]>,
+    DB: WriteExercise,
+    DB: SupportsDefaultKeyword,
+    DB: UsesAnsiSavepointSyntax,
+    chrono::NaiveDateTime: diesel::serialize::ToSql<diesel::sql_types::Timestamp, DB>,
+{
+}
+
+impl<Conn, DB: 'static> Create<&NewExercise, Exercise> for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: WriteExercise,
+    String: Queryable<Varchar, DB>,
+{
+    fn create(&self, obj: &NewExercise) -> database::Result<Exercise> {
+        self.transaction::<_, database::Error, _>(|| {
+            let exercise = DB::insert_exercise(self, obj)?;
+            record_revision(self, &exercise, obj.author_id.as_deref())?;
+            link_topic_by_name(self, &exercise, None)?;
+            Ok(exercise)
+        })
+    }
+}
+
+impl<'a, Conn, DB: 'static> FindById<&'a str, Exercise> for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend<RawValue = [u8]>,
+    DB: UsesAnsiSavepointSyntax,
+    chrono::NaiveDateTime: diesel::deserialize::FromSql<diesel::sql_types::Timestamp, DB>,
+{
+    fn find_by_id(&self, id: &'a str) -> database::Result<Exercise> {
+        exercises::table
+            .find(id)
+            .filter(exercises::deleted_on.is_null())
+            .first(self)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<'a, Conn, DB: 'static> List<&'a ExerciseQuery, Exercise> for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: CharLength,
+    DB: Backend<RawValue = [u8]>,
+    DB: UsesAnsiSavepointSyntax,
+    chrono::NaiveDateTime: diesel::deserialize::FromSql<diesel::sql_types::Timestamp, DB>,
+    chrono::NaiveDateTime: diesel::serialize::ToSql<diesel::sql_types::Timestamp, DB>,
+{
+    fn count(&self, query: &'a ExerciseQuery) -> database::Result<i64> {
+        filter_exercises(&query.filter)
+            .count()
+            .get_result(self)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+
+    fn list(
+        &self,
+        query: &'a ExerciseQuery,
+        offset: i64,
+        limit: i64,
+    ) -> database::Result<Vec<Exercise>> {
+        sort_exercises(filter_exercises(&query.filter), query.sort)
+            .offset(offset)
+            .limit(limit)
+            .load(self)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<'a, Conn, DB: 'static> Update<&'a UpdatedExercise<'a>, Exercise> for Conn
+where
+    Conn: for<'b> FindById<&'b str, Exercise>,
+    Conn: Connection<Backend = DB>,
+    DB: WriteExercise,
+    String: Queryable<Varchar, DB>,
+    Option<String>: Queryable<Nullable<Varchar>, DB>,
+{
+    fn update(&self, obj: &'a UpdatedExercise<'a>) -> database::Result<Exercise> {
+        self.transaction(|| {
+            let previous_topic = previous_topic(self, obj)?;
+            let updated = DB::update_exercise(self, obj)?;
+            let exercise = check_updated_exercise(updated, obj, || self.find_by_id(obj.get_id()))?;
+            record_revision(self, &exercise, obj.editor_id)?;
+            if obj.topic.is_some() {
+                link_topic_by_name(self, &exercise, previous_topic.as_deref())?;
+            }
+            Ok(exercise)
+        })
+    }
+}
+
+impl<'a, Conn, DB: 'static> DeleteById<&'a str, Exercise> for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: WriteExercise,
+{
+    fn delete_by_id(&self, id: &'a str) -> database::Result<Exercise> {
+        let deleted_on = chrono::Utc::now().naive_utc();
+        self.transaction(|| DB::set_exercise_deleted_on(self, id, Some(deleted_on)))
+            .map_err(IntoDatabaseError::into_database_error)?
+            .ok_or(database::Error::NotFound)
+    }
+}
+
+impl<'a, Conn, DB: 'static> Trash<&'a str, Exercise> for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend<RawValue = [u8]>,
+    DB: WriteExercise,
+    chrono::NaiveDateTime: diesel::serialize::ToSql<diesel::sql_types::Timestamp, DB>,
+    chrono::NaiveDateTime: diesel::deserialize::FromSql<diesel::sql_types::Timestamp, DB>,
+{
+    fn find_trashed(&self, id: &'a str) -> database::Result<Exercise> {
+        exercises::table
+            .find(id)
+            .filter(exercises::deleted_on.is_not_null())
+            .first(self)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+
+    fn restore(&self, id: &'a str) -> database::Result<Exercise> {
+        self.transaction(|| DB::set_exercise_deleted_on(self, id, None))
+            .map_err(IntoDatabaseError::into_database_error)?
+            .ok_or(database::Error::NotFound)
+    }
+
+    fn purge(&self, id: &'a str) -> database::Result<Exercise> {
+        self.transaction(|| DB::delete_exercise(self, id))
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+
+    fn purge_trashed(&self, deleted_before: chrono::NaiveDateTime) -> database::Result<usize> {
+        diesel::delete(exercises::table.filter(exercises::deleted_on.lt(deleted_before)))
+            .execute(self)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<'a, Conn, DB: 'static> CreateBatch<&'a [NewExercise], Exercise> for Conn
+where
+    Conn: for<'b> Create<&'b NewExercise, Exercise>,
+    Conn: Connection<Backend = DB>,
+    DB: Backend,
+{
+    fn create_batch(
+        &self,
+        objs: &'a [NewExercise],
+    ) -> database::Result<Vec<database::Result<Exercise>>> {
+        self.transaction(|| Ok(objs.iter().map(|obj| self.create(obj)).collect()))
+    }
+}
+
+impl<'a, Conn, DB: 'static> CreateAll<&'a [NewExercise], Exercise> for Conn
+where
+    Conn: for<'b> Create<&'b NewExercise, Exercise>,
+    Conn: Connection<Backend = DB>,
+    DB: Backend,
+{
+    fn create_all(&self, objs: &'a [NewExercise]) -> database::Result<Vec<Exercise>> {
+        self.transaction(|| objs.iter().map(|obj| self.create(obj)).collect())
+    }
+}
+
+impl<'a, Conn, DB: 'static> UpdateBatch<&'a [UpdatedExercise<'a>], Exercise> for Conn
+where
+    Conn: for<'b> Update<&'b UpdatedExercise<'b>, Exercise>,
+    Conn: Connection<Backend = DB>,
+    DB: Backend,
+{
+    fn update_batch(
+        &self,
+        objs: &'a [UpdatedExercise<'a>],
+    ) -> database::Result<Vec<database::Result<Exercise>>> {
+        self.transaction(|| Ok(objs.iter().map(|obj| self.update(obj)).collect()))
+    }
+}
+
+impl<'a, Conn, DB: 'static> DeleteByIds<&'a [&'a str], Exercise> for Conn
+where
+    Conn: for<'b> DeleteById<&'b str, Exercise>,
+    Conn: Connection<Backend = DB>,
+    DB: Backend,
+{
+    fn delete_by_ids(
+        &self,
+        ids: &'a [&'a str],
+    ) -> database::Result<Vec<database::Result<Exercise>>> {
+        self.transaction(|| Ok(ids.iter().map(|id| self.delete_by_id(id)).collect()))
+    }
+}
+
+/// Blanket `AttemptDao` implementation for SQL backends.
+impl<Conn, DB: 'static> AttemptDao for Conn
+where
+    Conn: for<'a> Create<&'a NewTypingAttempt, TypingAttempt>,
+    Conn: for<'a> FindById<&'a str, TypingAttempt>,
+    Conn: for<'a> List<&'a AttemptQuery, TypingAttempt>,
+    Conn: Connection<Backend = DB>,
+    DB: Backend,
+{
+}
+
+impl<'a, Conn, DB: 'static> Create<&'a NewTypingAttempt, TypingAttempt> for Conn
+where
+    Conn: for<'b> FindById<&'b str, TypingAttempt>,
+    Conn: Connection<Backend = DB>,
+    DB: WriteExercise,
+    DB: SupportsDefaultKeyword,
+    chrono::NaiveDateTime: diesel::serialize::ToSql<diesel::sql_types::Timestamp, DB>,
+    f64: diesel::serialize::ToSql<diesel::sql_types::Double, DB>,
+{
+    fn create(&self, obj: &'a NewTypingAttempt) -> database::Result<TypingAttempt> {
+        // NOTE: The exercise is locked in the same transaction as the attempt is inserted, so that
+        // it cannot be trashed or purged in between.
+        self.transaction(|| {
+            DB::lock_exercise(self, obj.exercise_id.as_str())?;
+            diesel::insert_into(typing_attempts::table)
+                .values(obj)
+                .execute(self)
+                .map_err(attempt_exercise_not_found)?;
+
+            self.find_by_id(obj.get_id())
+        })
+    }
+}
+
+impl<'a, Conn, DB: 'static> FindById<&'a str, TypingAttempt> for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend<RawValue = [u8]>,
+    DB: UsesAnsiSavepointSyntax,
+    chrono::NaiveDateTime: diesel::deserialize::FromSql<diesel::sql_types::Timestamp, DB>,
+    f64: diesel::deserialize::FromSql<diesel::sql_types::Double, DB>,
+{
+    fn find_by_id(&self, id: &'a str) -> database::Result<TypingAttempt> {
+        typing_attempts::table
+            .find(id)
+            .first(self)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<'a, Conn, DB: 'static> List<&'a AttemptQuery, TypingAttempt> for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend<RawValue = [u8]>,
+    DB: UsesAnsiSavepointSyntax,
+    chrono::NaiveDateTime: diesel::deserialize::FromSql<diesel::sql_types::Timestamp, DB>,
+    f64: diesel::deserialize::FromSql<diesel::sql_types::Double, DB>,
+{
+    fn count(&self, query: &'a AttemptQuery) -> database::Result<i64> {
+        typing_attempts::table
+            .filter(typing_attempts::exercise_id.eq(&query.exercise_id))
+            .count()
+            .get_result(self)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+
+    fn list(
+        &self,
+        query: &'a AttemptQuery,
+        offset: i64,
+        limit: i64,
+    ) -> database::Result<Vec<TypingAttempt>> {
+        attempts_matching(query)
+            .offset(offset)
+            .limit(limit)
+            .load(self)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+/// Blanket `UserDao` implementation for SQL backends.
+impl<Conn, DB: 'static> UserDao for Conn
+where
+    Conn: for<'a> Create<&'a NewUser, User>,
+    Conn: for<'a> FindById<&'a str, User>,
+    Conn: for<'a> FindByName<&'a str, User>,
+    Conn: Connection<Backend = DB>,
+    DB: Backend,
+{
+}
+
+impl<'a, Conn, DB: 'static> Create<&'a NewUser, User> for Conn
+where
+    Conn: for<'b> FindById<&'b str, User>,
+    Conn: for<'b> FindByName<&'b str, User>,
+    Conn: Connection<Backend = DB>,
+    DB: Backend,
+    DB: SupportsDefaultKeyword,
+    chrono::NaiveDateTime: diesel::serialize::ToSql<diesel::sql_types::Timestamp, DB>,
+{
+    fn create(&self, obj: &'a NewUser) -> database::Result<User> {
+        self.transaction(|| {
+            check_username_available(self.find_by_name(obj.username.as_str()))?;
+            diesel::insert_into(users::table)
+                .values(obj)
+                .execute(self)
+                .map_err(|e| unique_violation_as(e, || username_taken(&obj.username)))?;
+
+            self.find_by_id(obj.get_id())
+        })
+    }
+}
+
+impl<'a, Conn, DB: 'static> FindById<&'a str, User> for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend<RawValue = [u8]>,
+    DB: UsesAnsiSavepointSyntax,
+    chrono::NaiveDateTime: diesel::deserialize::FromSql<diesel::sql_types::Timestamp, DB>,
+{
+    fn find_by_id(&self, id: &'a str) -> database::Result<User> {
+        users::table
+            .find(id)
+            .first(self)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<'a, Conn, DB: 'static> FindByName<&'a str, User> for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend<RawValue = [u8]>,
+    DB: UsesAnsiSavepointSyntax,
+    chrono::NaiveDateTime: diesel::deserialize::FromSql<diesel::sql_types::Timestamp, DB>,
+{
+    fn find_by_name(&self, name: &'a str) -> database::Result<User> {
+        users::table
+            .filter(users::username.eq(name))
+            .first(self)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+/// Blanket `TopicDao` implementation for SQL backends.
+impl<Conn, DB: 'static> TopicDao for Conn
+where
+    Conn: for<'a> Create<&'a NewTopic, Topic>,
+    Conn: for<'a> FindById<&'a str, Topic>,
+    Conn: for<'a> FindByName<&'a str, Topic>,
+    Conn: ExerciseLinks<Topic>,
+    Conn: CountExercises<Topic>,
+    Conn: Connection<Backend = DB>,
+    DB: Backend,
+{
+}
+
+impl<'a, Conn, DB: 'static> Create<&'a NewTopic, Topic> for Conn
+where
+    Conn: for<'b> FindById<&'b str, Topic>,
+    Conn: for<'b> FindByName<&'b str, Topic>,
+    Conn: Connection<Backend = DB>,
+    DB: Backend,
+    DB: SupportsDefaultKeyword,
+    chrono::NaiveDateTime: diesel::serialize::ToSql<diesel::sql_types::Timestamp, DB>,
+{
+    fn create(&self, obj: &'a NewTopic) -> database::Result<Topic> {
+        self.transaction(|| {
+            check_new_topic(self, obj)?;
+            diesel::insert_into(topics::table)
+                .values(obj)
+                .execute(self)
+                .map_err(|e| unique_violation_as(e, || slug_taken(&obj.slug)))?;
+
+            self.find_by_id(obj.get_id())
+        })
+    }
+}
+
+impl<'a, Conn, DB: 'static> FindById<&'a str, Topic> for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend<RawValue = [u8]>,
+    DB: UsesAnsiSavepointSyntax,
+    chrono::NaiveDateTime: diesel::deserialize::FromSql<diesel::sql_types::Timestamp, DB>,
+{
+    fn find_by_id(&self, id: &'a str) -> database::Result<Topic> {
+        topics::table
+            .find(id)
+            .first(self)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<'a, Conn, DB: 'static> FindByName<&'a str, Topic> for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend<RawValue = [u8]>,
+    DB: UsesAnsiSavepointSyntax,
+    chrono::NaiveDateTime: diesel::deserialize::FromSql<diesel::sql_types::Timestamp, DB>,
+{
+    fn find_by_name(&self, slug: &'a str) -> database::Result<Topic> {
+        topics::table
+            .filter(topics::slug.eq(slug))
+            .first(self)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<Conn, DB: 'static> ExerciseLinks<Topic> for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend<RawValue = [u8]>,
+    DB: SupportsDefaultKeyword,
+    DB: UsesAnsiSavepointSyntax,
+    chrono::NaiveDateTime: diesel::deserialize::FromSql<diesel::sql_types::Timestamp, DB>,
+{
+    fn linked(&self, exercise_id: &str) -> database::Result<Vec<Topic>> {
+        load_linked_topics(self, exercise_id)
+    }
+
+    fn link(&self, exercise_id: &str, ids: &[&str]) -> database::Result<Vec<Topic>> {
+        link_topics(self, exercise_id, ids, |topic_id| {
+            diesel::insert_into(exercise_topics::table)
+                .values((
+                    exercise_topics::exercise_id.eq(exercise_id),
+                    exercise_topics::topic_id.eq(topic_id),
+                ))
+                .execute(self)
+        })
+    }
+
+    fn unlink(&self, exercise_id: &str, ids: &[&str]) -> database::Result<Vec<Topic>> {
+        unlink_topics(self, exercise_id, ids)
+    }
+}
+
+impl<Conn, DB: 'static> CountExercises<Topic> for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend<RawValue = [u8]>,
+    DB: UsesAnsiSavepointSyntax,
+    chrono::NaiveDateTime: diesel::deserialize::FromSql<diesel::sql_types::Timestamp, DB>,
+{
+    fn count_exercises(&self) -> database::Result<Vec<(Topic, i64)>> {
+        count_topic_exercises(self)
+    }
+}
+
+/// Blanket `TagDao` implementation for SQL backends.
+impl<Conn, DB: 'static> TagDao for Conn
+where
+    Conn: for<'a> FindByName<&'a str, Tag>,
+    Conn: ExerciseLinks<Tag>,
+    Conn: Connection<Backend = DB>,
+    DB: Backend,
+{
+}
+
+impl<'a, Conn, DB: 'static> FindByName<&'a str, Tag> for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend<RawValue = [u8]>,
+    DB: UsesAnsiSavepointSyntax,
+    chrono::NaiveDateTime: diesel::deserialize::FromSql<diesel::sql_types::Timestamp, DB>,
+{
+    fn find_by_name(&self, name: &'a str) -> database::Result<Tag> {
+        tags::table
+            .filter(tags::name.eq(name))
+            .first(self)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<Conn, DB: 'static> ExerciseLinks<Tag> for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend<RawValue = [u8]>,
+    DB: SupportsDefaultKeyword,
+    DB: UsesAnsiSavepointSyntax,
+    chrono::NaiveDateTime: diesel::deserialize::FromSql<diesel::sql_types::Timestamp, DB>,
+    chrono::NaiveDateTime: diesel::serialize::ToSql<diesel::sql_types::Timestamp, DB>,
+{
+    fn linked(&self, exercise_id: &str) -> database::Result<Vec<Tag>> {
+        load_linked_tags(self, exercise_id)
+    }
+
+    fn link(&self, exercise_id: &str, names: &[&str]) -> database::Result<Vec<Tag>> {
+        link_tags(
+            self,
+            exercise_id,
+            names,
+            |new_tag| {
+                diesel::insert_into(tags::table)
+                    .values(new_tag)
+                    .execute(self)
+            },
+            |tag_id| {
+                diesel::insert_into(exercise_tags::table)
+                    .values((
+                        exercise_tags::exercise_id.eq(exercise_id),
+                        exercise_tags::tag_id.eq(tag_id),
+                    ))
+                    .execute(self)
+            },
+        )
+    }
+
+    fn unlink(&self, exercise_id: &str, names: &[&str]) -> database::Result<Vec<Tag>> {
+        unlink_tags(self, exercise_id, names)
+    }
+}
+
+/// Blanket `RevisionDao` implementation for SQL backends.
+impl<Conn, DB: 'static> RevisionDao for Conn
+where
+    Conn: for<'a> FindById<&'a str, ExerciseRevision>,
+    Conn: ExerciseRevisions<ExerciseRevision>,
+    Conn: Connection<Backend = DB>,
+    DB: Backend,
+{
+}
+
+impl<'a, Conn, DB: 'static> FindById<&'a str, ExerciseRevision> for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend<RawValue = [u8]>,
+    DB: UsesAnsiSavepointSyntax,
+    chrono::NaiveDateTime: diesel::deserialize::FromSql<diesel::sql_types::Timestamp, DB>,
+{
+    fn find_by_id(&self, id: &'a str) -> database::Result<ExerciseRevision> {
+        exercise_revisions::table
+            .find(id)
+            .first(self)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<Conn, DB: 'static> ExerciseRevisions<ExerciseRevision> for Conn
+where
+    Conn: Connection<Backend = DB>,
+    DB: Backend<RawValue = [u8]>,
+    DB: UsesAnsiSavepointSyntax,
+    chrono::NaiveDateTime: diesel::deserialize::FromSql<diesel::sql_types::Timestamp, DB>,
+{
+    fn revisions(&self, exercise_id: &str) -> database::Result<Vec<ExerciseRevision>> {
+        exercise_revisions::table
+            .filter(exercise_revisions::exercise_id.eq(exercise_id))
+            .order(exercise_revisions::version.desc())
+            .load(self)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+/// Newtype for implementing `ExerciseDao` on a `diesel::sqlite::SqliteConnection` without
+/// conflicting with the blanket `ExerciseDao` implementation for SQL backends.
+///
+/// The newtype also wraps any other connection to a SQLite database, such as a
+/// `PooledConnection<ConnectionManager<diesel::SqliteConnection>>`.
+///
+/// # Examples
+///
+/// ```
+/// use database::query::ExerciseQueryBuilder;
+/// use database::ExerciseDao;
+/// use diesel::prelude::*;
+/// use wikitype_api::database;
+/// use wikitype_api::database::sql::pool::Dialect;
+/// use wikitype_api::models::{Exercise, NewExerciseBuilder};
+///
+/// const ALBATROSS_BODY: &'static str =
+///     "Albatrosses, of the biological family Diomedeidae, are large seabirds related to the \
+///      procellariids, storm petrels, and diving petrels in the order Procellariiformes (the \
+///      tubenoses).";
+///
+/// // Create an in-memory SQLite database and run the SQLite migrations against it.
+/// let dao = database::sql::SqliteConnection(
+///     SqliteConnection::establish(":memory:")
+///         .expect(&format!("Error creating in-memory SQLite database.")),
+/// );
+/// database::sql::migrations::run_pending_migrations(&dao.0, Dialect::Sqlite).unwrap();
+///
+/// let dao: &dyn ExerciseDao = &dao;
+///
+/// // Create a new exercise.
+/// let new_exercise = NewExerciseBuilder::new()
+///     .title("Albatross")
+///     .body(ALBATROSS_BODY)
+///     .build()
+///     .unwrap();
+///
+/// // Insert the new exercise into the database.
+/// let exercise = dao
+///     .create(&new_exercise)
+///     .expect("Failed to create Albatross exercise.");
+/// assert_eq!(&exercise.id, new_exercise.get_id());
+/// assert_eq!(exercise.title, new_exercise.title);
+/// assert_eq!(exercise.body, new_exercise.body);
+/// assert_eq!(exercise.topic.is_none(), new_exercise.topic.is_none());
+///
+/// // List the exercises whose title contains "albatross".
+/// let query = ExerciseQueryBuilder::new().title_contains("albatross").build();
+/// assert_eq!(dao.count(&query), Ok(1));
+/// assert_eq!(dao.list(&query, 0, 10), Ok(vec![exercise.clone()]));
+/// assert_eq!(dao.list(&query, 1, 10), Ok(vec![]));
+///
+/// // Search the exercises.
+/// let hits = dao.search("seabirds petrels", 10).unwrap();
+/// assert_eq!(hits.len(), 1);
+/// assert_eq!(hits[0].item, exercise);
+///
+/// // Move the exercise to the trash, then delete it for good.
+/// let deleted_exercise = dao
+///     .delete_by_id(&exercise.id)
+///     .expect("Failed to delete Albatross exercise.");
+/// assert_eq!(exercise.id, deleted_exercise.id);
+/// assert_eq!(dao.purge(&exercise.id), Ok(deleted_exercise));
+///
+/// let exercise = dao.find_by_id(&exercise.id);
+/// assert_eq!(
+///     exercise,
+///     Err(database::Error::NotFound)
+/// );
+/// ```
+pub struct SqliteConnection<C = diesel::SqliteConnection>(pub C);
+
+impl<C> ExerciseDao for SqliteConnection<C> where C: Connection<Backend = Sqlite> {}
+
+impl<C> RateDifficulty for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn rate_unrated(&self) -> database::Result<usize> {
+        rate_unrated_exercises(&self.0)
+    }
+}
+
+impl<C> Create<&NewExercise, Exercise> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn create(&self, obj: &NewExercise) -> database::Result<Exercise> {
+        self.0.transaction::<_, database::Error, _>(|| {
+            let exercise = Sqlite::insert_exercise(&self.0, obj)?;
+            record_revision(&self.0, &exercise, obj.author_id.as_deref())?;
+            link_topic_by_name(&self.0, &exercise, None)?;
+            Ok(exercise)
+        })
+    }
+}
+
+impl<'a, C> FindById<&'a str, Exercise> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn find_by_id(&self, id: &'a str) -> database::Result<Exercise> {
+        exercises::table
+            .find(id)
+            .filter(exercises::deleted_on.is_null())
+            .first(&self.0)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<'a, C> List<&'a ExerciseQuery, Exercise> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn count(&self, query: &'a ExerciseQuery) -> database::Result<i64> {
+        filter_exercises(&query.filter)
+            .count()
+            .get_result(&self.0)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+
+    fn list(
+        &self,
+        query: &'a ExerciseQuery,
+        offset: i64,
+        limit: i64,
+    ) -> database::Result<Vec<Exercise>> {
+        sort_exercises(filter_exercises(&query.filter), query.sort)
+            .offset(offset)
+            .limit(limit)
+            .load(&self.0)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<'a, C> Search<&'a str, Exercise> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn search(&self, query: &'a str, limit: i64) -> database::Result<Vec<SearchHit<Exercise>>> {
+        // NOTE: Quoting every term sidesteps the FTS5 query syntax, and implicitly requires every
+        // term to match.
+        let terms = search::search_terms(query);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let query = terms
+            .iter()
+            .map(|term| format!("\"{}\"", term))
+            .collect::<Vec<_>>()
+            .join(" ");
+        diesel::sql_query(SQLITE_SEARCH_QUERY)
+            .bind::<Text, _>(query)
+            .bind::<BigInt, _>(limit)
+            .load::<ExerciseSearchRow>(&self.0)
+            .map(|rows| rows.into_iter().map(SearchHit::from).collect())
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<'a, C> Update<&'a UpdatedExercise<'a>, Exercise> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn update(&self, obj: &'a UpdatedExercise<'a>) -> database::Result<Exercise> {
+        self.0.transaction(|| {
+            let previous_topic = previous_topic(&self.0, obj)?;
+            let updated = Sqlite::update_exercise(&self.0, obj)?;
+            let exercise = check_updated_exercise(updated, obj, || self.find_by_id(obj.get_id()))?;
+            record_revision(&self.0, &exercise, obj.editor_id)?;
+            if obj.topic.is_some() {
+                link_topic_by_name(&self.0, &exercise, previous_topic.as_deref())?;
+            }
+            Ok(exercise)
+        })
+    }
+}
+
+impl<'a, C> DeleteById<&'a str, Exercise> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn delete_by_id(&self, id: &'a str) -> database::Result<Exercise> {
+        let deleted_on = chrono::Utc::now().naive_utc();
+        self.0
+            .transaction(|| Sqlite::set_exercise_deleted_on(&self.0, id, Some(deleted_on)))
+            .map_err(IntoDatabaseError::into_database_error)?
+            .ok_or(database::Error::NotFound)
+    }
+}
+
+impl<'a, C> Trash<&'a str, Exercise> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn find_trashed(&self, id: &'a str) -> database::Result<Exercise> {
+        exercises::table
+            .find(id)
+            .filter(exercises::deleted_on.is_not_null())
+            .first(&self.0)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+
+    fn restore(&self, id: &'a str) -> database::Result<Exercise> {
+        self.0
+            .transaction(|| Sqlite::set_exercise_deleted_on(&self.0, id, None))
+            .map_err(IntoDatabaseError::into_database_error)?
+            .ok_or(database::Error::NotFound)
+    }
+
+    fn purge(&self, id: &'a str) -> database::Result<Exercise> {
+        self.0
+            .transaction(|| Sqlite::delete_exercise(&self.0, id))
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+
+    fn purge_trashed(&self, deleted_before: chrono::NaiveDateTime) -> database::Result<usize> {
+        diesel::delete(exercises::table.filter(exercises::deleted_on.lt(deleted_before)))
+            .execute(&self.0)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<'a, C> CreateBatch<&'a [NewExercise], Exercise> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn create_batch(
+        &self,
+        objs: &'a [NewExercise],
+    ) -> database::Result<Vec<database::Result<Exercise>>> {
+        self.0
+            .transaction(|| Ok(objs.iter().map(|obj| self.create(obj)).collect()))
+    }
+}
+
+impl<'a, C> CreateAll<&'a [NewExercise], Exercise> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn create_all(&self, objs: &'a [NewExercise]) -> database::Result<Vec<Exercise>> {
+        self.0
+            .transaction(|| objs.iter().map(|obj| self.create(obj)).collect())
+    }
+}
+
+impl<'a, C> UpdateBatch<&'a [UpdatedExercise<'a>], Exercise> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn update_batch(
+        &self,
+        objs: &'a [UpdatedExercise<'a>],
+    ) -> database::Result<Vec<database::Result<Exercise>>> {
+        self.0
+            .transaction(|| Ok(objs.iter().map(|obj| self.update(obj)).collect()))
+    }
+}
+
+impl<'a, C> DeleteByIds<&'a [&'a str], Exercise> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn delete_by_ids(
+        &self,
+        ids: &'a [&'a str],
+    ) -> database::Result<Vec<database::Result<Exercise>>> {
+        self.0
+            .transaction(|| Ok(ids.iter().map(|id| self.delete_by_id(id)).collect()))
+    }
+}
+
+impl<C> AttemptDao for SqliteConnection<C> where C: Connection<Backend = Sqlite> {}
+
+impl<'a, C> Create<&'a NewTypingAttempt, TypingAttempt> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn create(&self, obj: &'a NewTypingAttempt) -> database::Result<TypingAttempt> {
+        // NOTE: The exercise is looked up in the same (write) transaction as the attempt is
+        // inserted, which SQLite serializes, so that it cannot be trashed or purged in between.
+        self.0.transaction(|| {
+            Sqlite::lock_exercise(&self.0, obj.exercise_id.as_str())?;
+            diesel::insert_into(typing_attempts::table)
+                .values(obj)
+                .execute(&self.0)
+                .map_err(attempt_exercise_not_found)?;
+
+            self.find_by_id(obj.get_id())
+        })
+    }
+}
+
+impl<'a, C> FindById<&'a str, TypingAttempt> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn find_by_id(&self, id: &'a str) -> database::Result<TypingAttempt> {
+        typing_attempts::table
+            .find(id)
+            .first(&self.0)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<'a, C> List<&'a AttemptQuery, TypingAttempt> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn count(&self, query: &'a AttemptQuery) -> database::Result<i64> {
+        typing_attempts::table
+            .filter(typing_attempts::exercise_id.eq(&query.exercise_id))
+            .count()
+            .get_result(&self.0)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+
+    fn list(
+        &self,
+        query: &'a AttemptQuery,
+        offset: i64,
+        limit: i64,
+    ) -> database::Result<Vec<TypingAttempt>> {
+        attempts_matching(query)
+            .offset(offset)
+            .limit(limit)
+            .load(&self.0)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<C> UserDao for SqliteConnection<C> where C: Connection<Backend = Sqlite> {}
+
+impl<'a, C> Create<&'a NewUser, User> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn create(&self, obj: &'a NewUser) -> database::Result<User> {
+        self.0.transaction(|| {
+            check_username_available(self.find_by_name(obj.username.as_str()))?;
+            diesel::insert_into(users::table)
+                .values(obj)
+                .execute(&self.0)
+                .map_err(|e| unique_violation_as(e, || username_taken(&obj.username)))?;
+
+            self.find_by_id(obj.get_id())
+        })
+    }
+}
+
+impl<'a, C> FindById<&'a str, User> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn find_by_id(&self, id: &'a str) -> database::Result<User> {
+        users::table
+            .find(id)
+            .first(&self.0)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<'a, C> FindByName<&'a str, User> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn find_by_name(&self, name: &'a str) -> database::Result<User> {
+        users::table
+            .filter(users::username.eq(name))
+            .first(&self.0)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<C> TopicDao for SqliteConnection<C> where C: Connection<Backend = Sqlite> {}
+
+impl<'a, C> Create<&'a NewTopic, Topic> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn create(&self, obj: &'a NewTopic) -> database::Result<Topic> {
+        self.0.transaction(|| {
+            check_new_topic(self, obj)?;
+            diesel::insert_into(topics::table)
+                .values(obj)
+                .execute(&self.0)
+                .map_err(|e| unique_violation_as(e, || slug_taken(&obj.slug)))?;
+
+            self.find_by_id(obj.get_id())
+        })
+    }
+}
+
+impl<'a, C> FindById<&'a str, Topic> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn find_by_id(&self, id: &'a str) -> database::Result<Topic> {
+        topics::table
+            .find(id)
+            .first(&self.0)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<'a, C> FindByName<&'a str, Topic> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn find_by_name(&self, slug: &'a str) -> database::Result<Topic> {
+        topics::table
+            .filter(topics::slug.eq(slug))
+            .first(&self.0)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<C> ExerciseLinks<Topic> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn linked(&self, exercise_id: &str) -> database::Result<Vec<Topic>> {
+        load_linked_topics(&self.0, exercise_id)
+    }
+
+    fn link(&self, exercise_id: &str, ids: &[&str]) -> database::Result<Vec<Topic>> {
+        link_topics(&self.0, exercise_id, ids, |topic_id| {
+            diesel::insert_into(exercise_topics::table)
+                .values((
+                    exercise_topics::exercise_id.eq(exercise_id),
+                    exercise_topics::topic_id.eq(topic_id),
+                ))
+                .execute(&self.0)
+        })
+    }
+
+    fn unlink(&self, exercise_id: &str, ids: &[&str]) -> database::Result<Vec<Topic>> {
+        unlink_topics(&self.0, exercise_id, ids)
+    }
+}
+
+impl<C> CountExercises<Topic> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn count_exercises(&self) -> database::Result<Vec<(Topic, i64)>> {
+        count_topic_exercises(&self.0)
+    }
+}
+
+impl<C> TagDao for SqliteConnection<C> where C: Connection<Backend = Sqlite> {}
+
+impl<'a, C> FindByName<&'a str, Tag> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn find_by_name(&self, name: &'a str) -> database::Result<Tag> {
+        tags::table
+            .filter(tags::name.eq(name))
+            .first(&self.0)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<C> ExerciseLinks<Tag> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn linked(&self, exercise_id: &str) -> database::Result<Vec<Tag>> {
+        load_linked_tags(&self.0, exercise_id)
+    }
+
+    fn link(&self, exercise_id: &str, names: &[&str]) -> database::Result<Vec<Tag>> {
+        link_tags(
+            &self.0,
+            exercise_id,
+            names,
+            |new_tag| {
+                diesel::insert_into(tags::table)
+                    .values(new_tag)
+                    .execute(&self.0)
+            },
+            |tag_id| {
+                diesel::insert_into(exercise_tags::table)
+                    .values((
+                        exercise_tags::exercise_id.eq(exercise_id),
+                        exercise_tags::tag_id.eq(tag_id),
+                    ))
+                    .execute(&self.0)
+            },
+        )
+    }
+
+    fn unlink(&self, exercise_id: &str, names: &[&str]) -> database::Result<Vec<Tag>> {
+        unlink_tags(&self.0, exercise_id, names)
+    }
+}
+
+impl<C> RevisionDao for SqliteConnection<C> where C: Connection<Backend = Sqlite> {}
+
+impl<'a, C> FindById<&'a str, ExerciseRevision> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn find_by_id(&self, id: &'a str) -> database::Result<ExerciseRevision> {
+        exercise_revisions::table
+            .find(id)
+            .first(&self.0)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+impl<C> ExerciseRevisions<ExerciseRevision> for SqliteConnection<C>
+where
+    C: Connection<Backend = Sqlite>,
+{
+    fn revisions(&self, exercise_id: &str) -> database::Result<Vec<ExerciseRevision>> {
+        exercise_revisions::table
+            .filter(exercise_revisions::exercise_id.eq(exercise_id))
+            .order(exercise_revisions::version.desc())
+            .load(&self.0)
+            .map_err(IntoDatabaseError::into_database_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        NewExerciseBuilder, NewTopicBuilder, NewTypingAttemptBuilder, NewUserBuilder, Role,
+        UpdatedExerciseBuilder, Uuid,
+    };
+    use crate::scoring::{self, Key, Keystroke};
+    use chrono::Duration;
+    use database::query::ExerciseQueryBuilder;
+    use diesel::connection::SimpleConnection;
+    use pool::Dialect;
+
+    /// Create an in-memory SQLite database and run the SQLite migrations against it.
+    fn create_sqlite_dao() -> SqliteConnection {
+        let dao = SqliteConnection(
+            diesel::SqliteConnection::establish(":memory:")
+                .expect("Error creating in-memory SQLite database."),
+        );
+        migrations::run_pending_migrations(&dao.0, Dialect::Sqlite).unwrap();
+        dao.0.batch_execute("PRAGMA foreign_keys = ON").unwrap();
+        dao
+    }
+
+    /// Insert an exercise with the given title, body and topic.
+    fn create_exercise(dao: &dyn ExerciseDao, title: &str, body: &str, topic: &str) -> Exercise {
+        let new_exercise = NewExerciseBuilder::new()
+            .title(title)
+            .body(body)
+            .topic(Some(topic))
+            .build()
+            .unwrap();
+        dao.create(&new_exercise).unwrap()
+    }
+
+    /// Test that updating an exercise leaves every other exercise untouched.
+    #[test]
+    fn update_only_identified_exercise() {
+        let dao = create_sqlite_dao();
+        let dao: &dyn ExerciseDao = &dao;
+        let albatross = create_exercise(dao, "Albatross", "Large seabirds.", "Birds");
+        let penguin = create_exercise(dao, "Penguin", "Flightless birds.", "Birds");
+
+        let updated_exercise = UpdatedExerciseBuilder::new(&albatross.id)
+            .title("Albatross new")
+            .body("Very large seabirds.")
+            .topic(None)
+            .build()
+            .unwrap();
+        let updated = dao.update(&updated_exercise).unwrap();
+        assert_eq!(updated.id, albatross.id);
+        assert_eq!(updated.title, "Albatross new");
+        assert_eq!(updated.body, "Very large seabirds.");
+        assert_eq!(updated.topic, None);
+
+        assert_eq!(dao.find_by_id(albatross.id.as_str()), Ok(updated));
+        assert_eq!(dao.find_by_id(penguin.id.as_str()), Ok(penguin));
+    }
+
+    /// Test that updating an unknown exercise fails without touching any exercise.
+    #[test]
+    fn update_unknown_exercise() {
+        let dao = create_sqlite_dao();
+        let dao: &dyn ExerciseDao = &dao;
+        let albatross = create_exercise(dao, "Albatross", "Large seabirds.", "Birds");
+
+        let updated_exercise = UpdatedExerciseBuilder::new("unknown")
+            .title("Albatross new")
+            .build()
+            .unwrap();
+        assert_eq!(
+            dao.update(&updated_exercise),
+            Err(database::Error::NotFound)
+        );
+        assert_eq!(dao.find_by_id(albatross.id.as_str()), Ok(albatross));
+    }
+
+    /// Test that updates increment the version, and that updates based on a stale version fail.
+    #[test]
+    fn update_expected_version() {
+        let dao = create_sqlite_dao();
+        let dao: &dyn ExerciseDao = &dao;
+        let albatross = create_exercise(dao, "Albatross", "Large seabirds.", "Birds");
+        assert_eq!(albatross.version, 1);
+
+        let updated = dao
+            .update(
+                &UpdatedExerciseBuilder::new(&albatross.id)
+                    .title("Albatross 2")
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(updated.version, 2);
+
+        let stale = UpdatedExerciseBuilder::new(&albatross.id)
+            .title("Albatross stale")
+            .expected_version(1)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            dao.update(&stale),
+            Err(database::Error::Conflict(_))
+        ));
+        assert_eq!(dao.find_by_id(albatross.id.as_str()), Ok(updated));
+
+        let current = UpdatedExerciseBuilder::new(&albatross.id)
+            .title("Albatross 3")
+            .expected_version(2)
+            .build()
+            .unwrap();
+        let updated = dao.update(&current).unwrap();
+        assert_eq!(updated.title, "Albatross 3");
+        assert_eq!(updated.version, 3);
+
+        let unknown = UpdatedExerciseBuilder::new("unknown")
+            .title("Unknown")
+            .expected_version(1)
+            .build()
+            .unwrap();
+        assert_eq!(dao.update(&unknown), Err(database::Error::NotFound));
+    }
+
+    /// Test that creating and updating exercises writes revisions, and that failed updates and
+    /// deletions leave none behind.
+    #[test]
+    fn record_exercise_revisions() {
+        let dao = create_sqlite_dao();
+        let user = |username: &str| -> User {
+            let new_user = NewUserBuilder::new()
+                .username(username)
+                .password_hash("hash")
+                .role(Role::User)
+                .build()
+                .unwrap();
+            dao.create(&new_user).unwrap()
+        };
+        let author = user("author");
+        let editor = user("editor");
+
+        let new_exercise = NewExerciseBuilder::new()
+            .title("Albatross")
+            .body("Large seabirds.")
+            .author_id(Some(&author.id))
+            .build()
+            .unwrap();
+        let albatross: Exercise = dao.create(&new_exercise).unwrap();
+        let updated: Exercise = dao
+            .update(
+                &UpdatedExerciseBuilder::new(&albatross.id)
+                    .body("Large and heavy seabirds.")
+                    .topic(Some("Birds"))
+                    .editor_id(Some(&editor.id))
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        let stale = UpdatedExerciseBuilder::new(&albatross.id)
+            .title("Albatross stale")
+            .expected_version(1)
+            .build()
+            .unwrap();
+        let stale: database::Result<Exercise> = dao.update(&stale);
+        assert!(stale.is_err());
+
+        let revisions = dao.revisions(&albatross.id).unwrap();
+        let versions: Vec<(i32, &str, Option<&str>, Option<&str>)> = revisions
+            .iter()
+            .map(|revision| {
+                (
+                    revision.version,
+                    revision.body.as_str(),
+                    revision.topic.as_deref(),
+                    revision.author_id.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            versions,
+            vec![
+                (
+                    2,
+                    "Large and heavy seabirds.",
+                    Some("Birds"),
+                    Some(editor.id.as_str())
+                ),
+                (1, "Large seabirds.", None, Some(author.id.as_str())),
+            ]
+        );
+        assert_eq!(revisions[0].created_on, updated.modified_on);
+        assert_eq!(revisions[1].created_on, albatross.created_on);
+        let revision: ExerciseRevision = dao.find_by_id(revisions[1].id.as_str()).unwrap();
+        assert_eq!(revision, revisions[1]);
+
+        let deleted: Exercise = dao.delete_by_id(&albatross.id).unwrap();
+        let revision: ExerciseRevision = dao.find_by_id(revisions[0].id.as_str()).unwrap();
+        assert_eq!(revision, revisions[0]);
+        let purged: Exercise = dao.purge(&deleted.id).unwrap();
+        assert_eq!(dao.revisions(&purged.id), Ok(vec![]));
+        let revision: database::Result<ExerciseRevision> = dao.find_by_id(revisions[0].id.as_str());
+        assert_eq!(revision, Err(database::Error::NotFound));
+    }
+
+    /// Test that exercises are rated when created and rerated when their body is updated, and
+    /// rating exercises that have not been rated yet.
+    #[test]
+    fn rate_exercise_difficulty() {
+        let dao = create_sqlite_dao();
+        let exercises: &dyn ExerciseDao = &dao;
+        let easy_body = "The man went to the shop.";
+        let hard_body = "Set CFLAGS=\"-O2 -march=x86-64\" (v3.1; see §4.2b) & rebuild.";
+        let albatross = create_exercise(exercises, "Albatross", easy_body, "Birds");
+        assert_eq!(
+            albatross.difficulty,
+            Some(difficulty::difficulty(easy_body))
+        );
+
+        let retitled = exercises
+            .update(
+                &UpdatedExerciseBuilder::new(&albatross.id)
+                    .title("Albatross 2")
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(retitled.difficulty, albatross.difficulty);
+        let updated = exercises
+            .update(
+                &UpdatedExerciseBuilder::new(&albatross.id)
+                    .body(hard_body)
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(updated.difficulty, Some(difficulty::difficulty(hard_body)));
+        assert!(updated.difficulty > albatross.difficulty);
+
+        let query = ExerciseQueryBuilder::new().min_difficulty(50).build();
+        assert_eq!(exercises.list(&query, 0, 10), Ok(vec![updated.clone()]));
+        let query = ExerciseQueryBuilder::new().max_difficulty(50).build();
+        assert_eq!(exercises.count(&query), Ok(0));
+
+        dao.0
+            .batch_execute("UPDATE exercises SET difficulty = NULL")
+            .unwrap();
+        assert_eq!(exercises.count(&query), Ok(0));
+        assert_eq!(exercises.rate_unrated(), Ok(1));
+        assert_eq!(exercises.rate_unrated(), Ok(0));
+        assert_eq!(
+            exercises.find_by_id(albatross.id.as_str()),
+            Ok(updated.clone())
+        );
+    }
+
+    /// Test that taken usernames and slugs are reported as conflicts, including unique violations
+    /// of concurrent inserts that passed the check.
+    #[test]
+    fn report_taken_names_as_conflicts() {
+        let dao = create_sqlite_dao();
+        let new_user = |username| {
+            NewUserBuilder::new()
+                .username(username)
+                .password_hash("not really a hash")
+                .build()
+                .unwrap()
+        };
+        let _: User = dao.create(&new_user("albatross")).unwrap();
+        let taken: database::Result<User> = dao.create(&new_user("albatross"));
+        assert!(matches!(taken, Err(database::Error::Conflict(_))));
+
+        let inserted = diesel::insert_into(users::table)
+            .values(&new_user("albatross"))
+            .execute(&dao.0);
+        let conflict = unique_violation_as(inserted.unwrap_err(), || username_taken("albatross"));
+        assert_eq!(conflict, username_taken("albatross"));
+
+        let _: Topic = dao
+            .create(&NewTopicBuilder::new().name("Birds").build().unwrap())
+            .unwrap();
+        let taken: database::Result<Topic> =
+            dao.create(&NewTopicBuilder::new().name("Birds").build().unwrap());
+        assert!(matches!(taken, Err(database::Error::Conflict(_))));
+
+        let inserted = diesel::insert_into(topics::table)
+            .values(&NewTopicBuilder::new().name("Birds").build().unwrap())
+            .execute(&dao.0);
+        let conflict = unique_violation_as(inserted.unwrap_err(), || slug_taken("birds"));
+        assert_eq!(conflict, slug_taken("birds"));
+    }
+
+    /// Test creating topics, linking them to exercises and counting their exercises.
+    #[test]
+    fn create_and_link_topics() {
+        let dao = create_sqlite_dao();
+        let biology: Topic = dao
+            .create(&NewTopicBuilder::new().name("Biology").build().unwrap())
+            .unwrap();
+        let birds: Topic = dao
+            .create(
+                &NewTopicBuilder::new()
+                    .name("Birds")
+                    .parent_id(Some(&biology.id))
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(birds.parent_id, Some(biology.id.clone()));
+        assert_eq!(dao.find_by_name("birds"), Ok(birds.clone()));
+        let orphan: database::Result<Topic> = dao.create(
+            &NewTopicBuilder::new()
+                .name("Orphan")
+                .parent_id(Some("unknown"))
+                .build()
+                .unwrap(),
+        );
+        assert_eq!(orphan, Err(database::Error::NotFound));
+
+        let exercises: &dyn ExerciseDao = &dao;
+        let albatross = create_exercise(exercises, "Albatross", "Large seabirds.", "Birds");
+        let penguin = create_exercise(exercises, "Penguin", "Flightless birds.", "Birds");
+        assert_eq!(
+            dao.link(&albatross.id, &[&birds.id, &biology.id, &birds.id]),
+            Ok(vec![biology.clone(), birds.clone()])
+        );
+        assert_eq!(dao.link(&penguin.id, &[&birds.id]), Ok(vec![birds.clone()]));
+
+        // Linking an unknown topic links none of the topics.
+        let topics: database::Result<Vec<Topic>> = dao.link(&penguin.id, &[&biology.id, "unknown"]);
+        assert_eq!(topics, Err(database::Error::NotFound));
+        assert_eq!(dao.linked(&penguin.id), Ok(vec![birds.clone()]));
+        let topics: database::Result<Vec<Topic>> = dao.link("unknown", &[&birds.id]);
+        assert_eq!(topics, Err(database::Error::NotFound));
+
+        let query = ExerciseQueryBuilder::new().topic_id(&birds.id).build();
+        assert_eq!(
+            exercises.list(&query, 0, 10),
+            Ok(vec![albatross.clone(), penguin.clone()])
+        );
+        assert_eq!(
+            dao.count_exercises(),
+            Ok(vec![(biology.clone(), 1), (birds.clone(), 2)])
+        );
+
+        assert_eq!(
+            dao.unlink(&albatross.id, &[&birds.id, "unknown"]),
+            Ok(vec![biology.clone()])
+        );
+        exercises.delete_by_id(&penguin.id).unwrap();
+        assert_eq!(exercises.count(&query), Ok(0));
+        assert_eq!(dao.count_exercises(), Ok(vec![(biology, 1), (birds, 0)]));
+    }
+
+    /// Test tagging exercises and listing exercises by their tags.
+    #[test]
+    fn tag_exercises() {
+        let dao = create_sqlite_dao();
+        let exercises: &dyn ExerciseDao = &dao;
+        let pi = create_exercise(exercises, "Pi", "3.14159 26535 89793", "Numbers");
+        let hamlet = create_exercise(exercises, "Hamlet", "\"To be, or not to be.\"", "Plays");
+        let albatross = create_exercise(exercises, "Albatross", "Large seabirds.", "Birds");
+
+        let tags: Vec<Tag> = dao
+            .link(&pi.id, &["short", "numbers-heavy", "short"])
+            .unwrap();
+        let names: Vec<&str> = tags.iter().map(|tag| tag.name.as_str()).collect();
+        assert_eq!(names, vec!["numbers-heavy", "short"]);
+        let short: Tag = dao.find_by_name("short").unwrap();
+        assert_eq!(tags[1], short);
+        let tags: Vec<Tag> = dao.link(&hamlet.id, &["quotes", "short"]).unwrap();
+        assert_eq!(tags[1], short);
+        let tags: database::Result<Vec<Tag>> = dao.link("unknown", &["short"]);
+        assert_eq!(tags, Err(database::Error::NotFound));
+
+        let list = |query: &ExerciseQuery| {
+            let exercises: Vec<String> = exercises
+                .list(query, 0, 10)
+                .unwrap()
+                .into_iter()
+                .map(|exercise| exercise.title)
+                .collect();
+            exercises
+        };
+        let query = ExerciseQueryBuilder::new()
+            .tags_any(&["quotes", "numbers-heavy"])
+            .build();
+        assert_eq!(list(&query), vec!["Pi", "Hamlet"]);
+        let query = ExerciseQueryBuilder::new()
+            .tags_all(&["short", "quotes"])
+            .build();
+        assert_eq!(list(&query), vec!["Hamlet"]);
+        let query = ExerciseQueryBuilder::new()
+            .tags_any(&[])
+            .tags_all(&[])
+            .build();
+        assert_eq!(list(&query), vec!["Pi", "Hamlet", "Albatross"]);
+        let query = ExerciseQueryBuilder::new().tags_any(&["unknown"]).build();
+        assert_eq!(exercises.count(&query), Ok(0));
+
+        let tags: Vec<Tag> = dao.unlink(&hamlet.id, &["short", "unknown"]).unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].name, "quotes");
+        let tags: Vec<Tag> = dao.linked(&albatross.id).unwrap();
+        assert!(tags.is_empty());
+        let query = ExerciseQueryBuilder::new().tags_all(&["short"]).build();
+        assert_eq!(list(&query), vec!["Pi"]);
+    }
+
+    /// Test that deleting an exercise moves it to the trash, returning the trashed row, and that
+    /// deleting an unknown (or already trashed) exercise fails.
+    #[test]
+    fn delete_exercise() {
+        let dao = create_sqlite_dao();
+        let dao: &dyn ExerciseDao = &dao;
+        let albatross = create_exercise(dao, "Albatross", "Large seabirds.", "Birds");
+        let penguin = create_exercise(dao, "Penguin", "Flightless birds.", "Birds");
+
+        let deleted = dao.delete_by_id(&albatross.id).unwrap();
+        assert!(deleted.deleted_on.is_some());
+        assert_eq!(
+            Exercise {
+                deleted_on: None,
+                ..deleted
+            },
+            albatross
+        );
+        assert_eq!(
+            dao.find_by_id(albatross.id.as_str()),
+            Err(database::Error::NotFound)
+        );
+        assert_eq!(
+            dao.delete_by_id(&albatross.id),
+            Err(database::Error::NotFound)
+        );
+        assert_eq!(dao.find_by_id(penguin.id.as_str()), Ok(penguin));
+    }
+
+    /// Test listing, restoring and purging trashed exercises, and that trashed exercises keep
+    /// their typing attempts until they are purged.
+    #[test]
+    fn trash_exercises() {
+        let dao = create_sqlite_dao();
+        let albatross = create_exercise(&dao, "Albatross", "Large seabirds.", "Birds");
+        let penguin = create_exercise(&dao, "Penguin", "Flightless birds.", "Birds");
+        let score = scoring::score(&albatross.body, &[]).unwrap();
+        let new_attempt = NewTypingAttemptBuilder::new()
+            .exercise_id(&albatross.id)
+            .started_on(albatross.created_on)
+            .score(&score)
+            .build()
+            .unwrap();
+        let attempt: TypingAttempt = dao.create(&new_attempt).unwrap();
+        let exercises: &dyn ExerciseDao = &dao;
+        let attempts: &dyn AttemptDao = &dao;
+        let list = |trashed: Trashed| -> Vec<String> {
+            let query = ExerciseQueryBuilder::new().trashed(trashed).build();
+            let listed: Vec<Exercise> = exercises.list(&query, 0, 10).unwrap();
+            listed.into_iter().map(|exercise| exercise.title).collect()
+        };
+
+        assert_eq!(
+            exercises.restore(&albatross.id),
+            Err(database::Error::NotFound)
+        );
+        exercises.delete_by_id(&albatross.id).unwrap();
+        assert_eq!(list(Trashed::Exclude), vec!["Penguin"]);
+        assert_eq!(list(Trashed::Include), vec!["Albatross", "Penguin"]);
+        assert_eq!(list(Trashed::Only), vec!["Albatross"]);
+        let query = ExerciseQueryBuilder::new().build();
+        assert_eq!(exercises.count(&query), Ok(1));
+        assert_eq!(
+            exercises.update(
+                &UpdatedExerciseBuilder::new(&albatross.id)
+                    .title("A")
+                    .build()
+                    .unwrap()
+            ),
+            Err(database::Error::NotFound)
+        );
+        assert!(exercises.find_trashed(&albatross.id).is_ok());
+        assert_eq!(
+            attempts.find_by_id(attempt.id.as_str()),
+            Ok(attempt.clone())
+        );
+
+        assert_eq!(exercises.restore(&albatross.id), Ok(albatross.clone()));
+        assert_eq!(
+            exercises.find_trashed(&albatross.id),
+            Err(database::Error::NotFound)
+        );
+        assert_eq!(list(Trashed::Exclude), vec!["Albatross", "Penguin"]);
+
+        // Only exercises trashed before the given time are purged.
+        exercises.delete_by_id(&albatross.id).unwrap();
+        exercises.delete_by_id(&penguin.id).unwrap();
+        let deleted_on = exercises
+            .find_trashed(&albatross.id)
+            .unwrap()
+            .deleted_on
+            .unwrap();
+        assert_eq!(exercises.purge_trashed(deleted_on), Ok(0));
+        assert_eq!(
+            exercises.purge_trashed(deleted_on + Duration::seconds(1)),
+            Ok(2)
+        );
+        assert!(list(Trashed::Include).is_empty());
+        assert_eq!(
+            attempts.find_by_id(attempt.id.as_str()),
+            Err(database::Error::NotFound)
+        );
+
+        let kiwi = create_exercise(exercises, "Kiwi", "Flightless birds.", "Birds");
+        assert_eq!(exercises.purge(&kiwi.id), Ok(kiwi.clone()));
+        assert_eq!(exercises.purge(&kiwi.id), Err(database::Error::NotFound));
+    }
+
+    /// Test that failing items of a batch are reported without affecting the other items.
+    #[test]
+    fn write_exercise_batches() {
+        let dao = create_sqlite_dao();
+        let dao: &dyn ExerciseDao = &dao;
+        let new_exercises: Vec<NewExercise> = ["Albatross", "Penguin"]
+            .iter()
+            .map(|title| {
+                NewExerciseBuilder::new()
+                    .title(title)
+                    .body("Birds.")
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+        let created = dao.create_batch(&new_exercises).unwrap();
+        let created: Vec<Exercise> = created.into_iter().map(Result::unwrap).collect();
+        assert_eq!(created[0].title, "Albatross");
+        assert_eq!(created[1].title, "Penguin");
+        let (albatross, penguin) = (&created[0], &created[1]);
+
+        let updated_exercises = vec![
+            UpdatedExerciseBuilder::new(&albatross.id)
+                .title("Albatross new")
+                .build()
+                .unwrap(),
+            UpdatedExerciseBuilder::new(&penguin.id)
+                .title("Penguin new")
+                .expected_version(2)
+                .build()
+                .unwrap(),
+            UpdatedExerciseBuilder::new("unknown")
+                .title("Unknown")
+                .build()
+                .unwrap(),
+        ];
+        let updated = dao.update_batch(&updated_exercises).unwrap();
+        assert_eq!(updated[0].as_ref().unwrap().title, "Albatross new");
+        assert!(matches!(updated[1], Err(database::Error::Conflict(_))));
+        assert_eq!(updated[2], Err(database::Error::NotFound));
+        assert_eq!(dao.find_by_id(penguin.id.as_str()), Ok(penguin.clone()));
+
+        let deleted = dao
+            .delete_by_ids(&[penguin.id.as_str(), "unknown"])
+            .unwrap();
+        assert_eq!(deleted.len(), 2);
+        assert_eq!(deleted[0].as_ref().unwrap().id, penguin.id);
+        assert_eq!(deleted[1], Err(database::Error::NotFound));
+        assert_eq!(
+            dao.find_by_id(penguin.id.as_str()),
+            Err(database::Error::NotFound)
+        );
+        assert!(dao.find_by_id(albatross.id.as_str()).is_ok());
+    }
+
+    /// Test creating a series, listing its parts in order, and that a series with a duplicate
+    /// part is not created at all.
+    #[test]
+    fn create_and_list_exercise_series() {
+        let dao = create_sqlite_dao();
+        let dao: &dyn ExerciseDao = &dao;
+        let series_id = Uuid::new().to_string();
+        let new_exercises: Vec<NewExercise> = [1, 0]
+            .iter()
+            .map(|&part_index| {
+                NewExerciseBuilder::new()
+                    .title("Albatross")
+                    .body("Birds.")
+                    .series(&series_id, part_index)
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+        let created = dao.create_all(&new_exercises).unwrap();
+        assert_eq!(created[0].series_id.as_ref(), Some(&series_id));
+        assert_eq!(created[0].part_index, Some(1));
+
+        let query = ExerciseQueryBuilder::new()
+            .series_id(&series_id)
+            .sort_by(ExerciseSortField::PartIndex, SortDirection::Ascending)
+            .build();
+        let parts: Vec<Exercise> = dao.list(&query, 0, 10).unwrap();
+        assert_eq!(parts, vec![created[1].clone(), created[0].clone()]);
+        let query = ExerciseQueryBuilder::new()
+            .series_id(&series_id)
+            .part_index(1)
+            .build();
+        assert_eq!(dao.list(&query, 0, 10), Ok(vec![created[0].clone()]));
+
+        let other_series_id = Uuid::new().to_string();
+        let duplicates: Vec<NewExercise> = [0, 0]
+            .iter()
+            .map(|&part_index| {
+                NewExerciseBuilder::new()
+                    .title("Penguin")
+                    .body("Birds.")
+                    .series(&other_series_id, part_index)
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+        assert!(dao.create_all(&duplicates).is_err());
+        let query = ExerciseQueryBuilder::new()
+            .series_id(&other_series_id)
+            .build();
+        assert_eq!(dao.count(&query), Ok(0));
+    }
+
+    /// Test filtering and sorting exercises.
+    #[test]
+    fn list_filtered_and_sorted_exercises() {
+        let dao = create_sqlite_dao();
+        let dao: &dyn ExerciseDao = &dao;
+        let albatross = create_exercise(dao, "Albatross", "Large seabirds.", "Birds");
+        let penguin = create_exercise(dao, "Penguin", "Flightless birds.", "Birds");
+        let percent = create_exercise(dao, "100% Pure", "Percentages: é, ü.", "Numbers");
+
+        let list = |query: &ExerciseQuery| dao.list(query, 0, 10).unwrap();
+
+        // Filter by topic and sort by title, descending.
+        let query = ExerciseQueryBuilder::new()
+            .topic("Birds")
+            .sort_by(ExerciseSortField::Title, SortDirection::Descending)
+            .build();
+        assert_eq!(dao.count(&query), Ok(2));
+        assert_eq!(list(&query), vec![penguin.clone(), albatross.clone()]);
+
+        // Title substrings are matched case-insensitively and wildcards are matched literally.
+        let query = ExerciseQueryBuilder::new().title_contains("ALBA").build();
+        assert_eq!(list(&query), vec![albatross.clone()]);
+        let query = ExerciseQueryBuilder::new().title_contains("0%").build();
+        assert_eq!(list(&query), vec![percent.clone()]);
+        let query = ExerciseQueryBuilder::new().title_contains("_").build();
+        assert_eq!(list(&query), vec![]);
+
+        // Body lengths are counted in characters.
+        let query = ExerciseQueryBuilder::new()
+            .min_body_length(16)
+            .max_body_length(18)
+            .sort_by(ExerciseSortField::BodyLength, SortDirection::Ascending)
+            .build();
+        assert_eq!(list(&query), vec![penguin.clone(), percent.clone()]);
+
+        // Filter by creation time.
+        let query = ExerciseQueryBuilder::new()
+            .created_after(penguin.created_on)
+            .build();
+        assert_eq!(list(&query), vec![penguin.clone(), percent.clone()]);
+    }
+
+    /// Test that FTS5 searches rank title matches first and follow updates and deletions.
+    #[test]
+    fn search_exercises() {
+        let dao = create_sqlite_dao();
+        let dao: &dyn ExerciseDao = &dao;
+        let albatross = create_exercise(
+            dao,
+            "Albatross",
+            "Large seabirds related to the petrels.",
+            "Birds",
+        );
+        let petrel = create_exercise(dao, "Petrel", "Tubenosed seabirds.", "Birds");
+        create_exercise(dao, "Penguin", "Flightless birds.", "Birds");
+
+        let ids = |query: &str| -> Vec<String> {
+            dao.search(query, 10)
+                .unwrap()
+                .into_iter()
+                .map(|hit| hit.item.id)
+                .collect()
+        };
+        assert_eq!(ids("petrel"), vec![petrel.id.clone(), albatross.id.clone()]);
+        assert_eq!(ids("seabirds -large"), vec![albatross.id.clone()]);
+        assert_eq!(ids("\"unbalanced"), Vec::<String>::new());
+
+        let hits = dao.search("tubenosed", 10).unwrap();
+        assert_eq!(hits[0].snippet, "<b>Tubenosed</b> seabirds.");
+
+        let update = crate::models::UpdatedExerciseBuilder::new(&petrel.id)
+            .body("Storm petrels.")
+            .build()
+            .unwrap();
+        dao.update(&update).unwrap();
+        assert_eq!(ids("tubenosed"), Vec::<String>::new());
+
+        dao.delete_by_id(&albatross.id).unwrap();
+        assert_eq!(ids("large"), Vec::<String>::new());
+    }
+
+    /// Test that attempts are listed from newest to oldest, kept while their exercise is in the
+    /// trash, and deleted along with their purged exercise.
+    #[test]
+    fn list_exercise_attempts() {
+        let dao = create_sqlite_dao();
+        let albatross = create_exercise(&dao, "Albatross", "Large seabirds.", "Birds");
+        let penguin = create_exercise(&dao, "Penguin", "Flightless birds.", "Birds");
+
+        // Type "Lz<arhe" in a minute, where "<" is a backspace.
+        let mut log: Vec<Keystroke> = "Lz<arhe"
+            .chars()
+            .zip(1..)
+            .map(|(c, i)| Keystroke {
+                key: if c == '<' {
+                    Key::Backspace
+                } else {
+                    Key::Char(c)
+                },
+                elapsed_ms: i * 1_000,
+            })
+            .collect();
+        log.last_mut().unwrap().elapsed_ms = 60_000;
+
+        let started_on = albatross.created_on;
+        let create_attempt = |exercise: &Exercise, minutes: i64| -> TypingAttempt {
+            let score = scoring::score(&exercise.body, &log).unwrap();
+            let new_attempt = NewTypingAttemptBuilder::new()
+                .exercise_id(&exercise.id)
+                .started_on(started_on + Duration::minutes(minutes))
+                .score(&score)
+                .build()
+                .unwrap();
+            dao.create(&new_attempt).unwrap()
+        };
+        let first = create_attempt(&albatross, 0);
+        let second = create_attempt(&albatross, 2);
+        create_attempt(&penguin, 1);
+        assert_eq!(first.typed_text, "Larhe");
+        assert_eq!(
+            (first.keystrokes, first.errors, first.corrected_errors),
+            (7, 2, 1)
+        );
+        assert_eq!(first.error_positions, vec![1, 3]);
+        assert_eq!(first.gross_wpm, 1.2);
+        assert_eq!(dao.find_by_id(first.id.as_str()), Ok(first.clone()));
+
+        let query = AttemptQuery::for_exercise(&albatross.id);
+        assert_eq!(dao.count(&query), Ok(2));
+        assert_eq!(
+            dao.list(&query, 0, 10),
+            Ok(vec![second.clone(), first.clone()])
+        );
+        assert_eq!(dao.list(&query, 1, 10), Ok(vec![first.clone()]));
+
+        dao.delete_by_id(&albatross.id).unwrap();
+        assert_eq!(dao.count(&query), Ok(2));
+        dao.purge(&albatross.id).unwrap();
+        assert_eq!(dao.count(&query), Ok(0));
+        let attempt: database::Result<TypingAttempt> = dao.find_by_id(first.id.as_str());
+        assert_eq!(attempt, Err(database::Error::NotFound));
+        assert_eq!(dao.count(&AttemptQuery::for_exercise(&penguin.id)), Ok(1));
+
+        // Attempts at purged exercises are rejected, even if the exercise was purged after it was
+        // looked up.
+        let score = scoring::score(&albatross.body, &log).unwrap();
+        let new_attempt = NewTypingAttemptBuilder::new()
+            .exercise_id(&albatross.id)
+            .started_on(started_on)
+            .score(&score)
+            .build()
+            .unwrap();
+        let attempt: database::Result<TypingAttempt> = dao.create(&new_attempt);
+        assert_eq!(attempt, Err(database::Error::NotFound));
+        let inserted = diesel::insert_into(typing_attempts::table)
+            .values(&new_attempt)
+            .execute(&dao.0);
+        assert_eq!(
+            attempt_exercise_not_found(inserted.unwrap_err()),
+            database::Error::NotFound
+        );
     }
 }
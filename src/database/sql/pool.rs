@@ -0,0 +1,261 @@
+use crate::config::Config;
+use crate::database;
+use crate::database::sql::migrations::{self, EmbeddedMigration};
+use crate::database::sql::{ConnectionManager, MysqlConnection, PgConnection, SqliteConnection};
+use crate::database::{Dao, IntoDatabaseError};
+
+use diesel::connection::SimpleConnection;
+use diesel::r2d2;
+use std::fmt;
+
+/// SQL dialects (i.e. database systems) supported by `Pool`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Dialect {
+    Postgres,
+    Mysql,
+    Sqlite,
+}
+
+impl Dialect {
+    /// Determines the dialect of a database from its URL.
+    ///
+    /// Like the Diesel CLI, any URL that is not a `postgres://`, `postgresql://` or `mysql://` URL
+    /// is taken to be the path of a SQLite database (optionally prefixed with `sqlite://`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use wikitype_api::database::sql::pool::Dialect;
+    ///
+    /// assert_eq!(Dialect::from_url("postgres://localhost/wikitype"), Dialect::Postgres);
+    /// assert_eq!(Dialect::from_url("mysql://localhost/wikitype"), Dialect::Mysql);
+    /// assert_eq!(Dialect::from_url("sqlite://wikitype.db"), Dialect::Sqlite);
+    /// assert_eq!(Dialect::from_url(":memory:"), Dialect::Sqlite);
+    /// ```
+    pub fn from_url(url: &str) -> Dialect {
+        if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            Dialect::Postgres
+        } else if url.starts_with("mysql://") {
+            Dialect::Mysql
+        } else {
+            Dialect::Sqlite
+        }
+    }
+}
+
+impl fmt::Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Dialect::Postgres => write!(f, "Postgres"),
+            Dialect::Mysql => write!(f, "MySQL"),
+            Dialect::Sqlite => write!(f, "SQLite"),
+        }
+    }
+}
+
+/// Returns the path (or `:memory:`) that diesel expects from a SQLite database URL.
+fn sqlite_path(url: &str) -> &str {
+    url.strip_prefix("sqlite://").unwrap_or(url)
+}
+
+/// Creates a connection pool builder with the pool settings in `config`.
+fn pool_builder<M: r2d2::ManageConnection>(config: &Config) -> r2d2::Builder<M> {
+    r2d2::Pool::builder()
+        .max_size(config.pool_max_size)
+        .min_idle(config.pool_min_idle)
+        .connection_timeout(config.pool_connection_timeout)
+        .idle_timeout(config.pool_idle_timeout)
+}
+
+/// Per-connection settings for SQLite, which are not persisted in the database file.
+#[derive(Debug)]
+struct SqliteCustomizer;
+
+impl r2d2::CustomizeConnection<diesel::SqliteConnection, r2d2::Error> for SqliteCustomizer {
+    fn on_acquire(&self, conn: &mut diesel::SqliteConnection) -> Result<(), r2d2::Error> {
+        // NOTE: Wait for (rather than fail on) locks held by other pooled connections.
+        conn.batch_execute("PRAGMA busy_timeout = 5000; PRAGMA foreign_keys = ON;")
+            .map_err(r2d2::Error::QueryError)
+    }
+}
+
+/// A connection pool for any of the supported SQL dialects, chosen by the database URL.
+///
+/// # Examples
+///
+/// ```
+/// use wikitype_api::config::Config;
+/// use wikitype_api::database::sql::pool::{Dialect, Pool};
+///
+/// let pool = Pool::new(&Config::new(":memory:")).unwrap();
+/// assert_eq!(pool.dialect(), Dialect::Sqlite);
+///
+/// // Create the schema of the (empty) database.
+/// pool.run_pending_migrations().unwrap();
+/// assert!(pool.pending_migrations().unwrap().is_empty());
+///
+/// let dao = pool.get().unwrap();
+/// ```
+#[derive(Clone)]
+pub enum Pool {
+    Postgres(r2d2::Pool<ConnectionManager<PgConnection>>),
+    Mysql(r2d2::Pool<ConnectionManager<MysqlConnection>>),
+    Sqlite(r2d2::Pool<ConnectionManager<diesel::SqliteConnection>>),
+}
+
+impl Pool {
+    /// Opens a connection pool to the database at `config.database_url`.
+    ///
+    /// NOTE: Every connection to an in-memory SQLite database (`:memory:`) opens a new, empty
+    /// database. For such databases the pool therefore holds a single connection which is never
+    /// closed, regardless of the pool settings in `config`.
+    ///
+    /// Fails with `Error::ServerError` if `config.pool_max_size` is 0 or
+    /// `config.pool_min_idle` exceeds it, which `Config::from_env` rejects but `Config::new`
+    /// allows to be set directly.
+    pub fn new(config: &Config) -> database::Result<Pool> {
+        // NOTE: r2d2 panics rather than failing on such settings.
+        if config.pool_max_size == 0 {
+            return Err(database::Error::ServerError(Some(String::from(
+                "The pool size must be positive",
+            ))));
+        }
+        if let Some(min_idle) = config.pool_min_idle {
+            if min_idle > config.pool_max_size {
+                return Err(database::Error::ServerError(Some(format!(
+                    "The pool may keep at most {} idle connections, not {}",
+                    config.pool_max_size, min_idle
+                ))));
+            }
+        }
+        let url = config.database_url.as_str();
+        let pool = match Dialect::from_url(url) {
+            Dialect::Postgres => pool_builder(config)
+                .build(ConnectionManager::new(url))
+                .map(Pool::Postgres),
+            Dialect::Mysql => pool_builder(config)
+                .build(ConnectionManager::new(url))
+                .map(Pool::Mysql),
+            Dialect::Sqlite => {
+                let path = sqlite_path(url);
+                let builder = pool_builder(config);
+                let builder = if path == ":memory:" {
+                    builder
+                        .max_size(1)
+                        .min_idle(Some(1))
+                        .idle_timeout(None)
+                        .max_lifetime(None)
+                } else {
+                    builder
+                };
+                builder
+                    .connection_customizer(Box::new(SqliteCustomizer))
+                    .build(ConnectionManager::new(path))
+                    .map(Pool::Sqlite)
+            }
+        };
+        pool.map_err(IntoDatabaseError::into_database_error)
+    }
+
+    /// Returns the SQL dialect of the pooled connections.
+    pub fn dialect(&self) -> Dialect {
+        match self {
+            Pool::Postgres(_) => Dialect::Postgres,
+            Pool::Mysql(_) => Dialect::Mysql,
+            Pool::Sqlite(_) => Dialect::Sqlite,
+        }
+    }
+
+    /// Checks out a connection from the pool.
+    pub fn get(&self) -> database::Result<Box<dyn Dao>> {
+        let conn: Box<dyn Dao> = match self {
+            Pool::Postgres(pool) => {
+                Box::new(pool.get().map_err(IntoDatabaseError::into_database_error)?)
+            }
+            Pool::Mysql(pool) => {
+                Box::new(pool.get().map_err(IntoDatabaseError::into_database_error)?)
+            }
+            Pool::Sqlite(pool) => Box::new(SqliteConnection(
+                pool.get().map_err(IntoDatabaseError::into_database_error)?,
+            )),
+        };
+        Ok(conn)
+    }
+
+    /// Returns the embedded migrations which have not been run against the database yet.
+    ///
+    /// The server should not serve requests unless this is empty, since queries may otherwise
+    /// refer to tables or columns that do not exist.
+    pub fn pending_migrations(&self) -> database::Result<Vec<EmbeddedMigration>> {
+        let dialect = self.dialect();
+        match self {
+            Pool::Postgres(pool) => migrations::pending_migrations(&*checkout(pool)?, dialect),
+            Pool::Mysql(pool) => migrations::pending_migrations(&*checkout(pool)?, dialect),
+            Pool::Sqlite(pool) => migrations::pending_migrations(&*checkout(pool)?, dialect),
+        }
+    }
+
+    /// Runs the pending embedded migrations against the database, returning the migrations that
+    /// were run.
+    pub fn run_pending_migrations(&self) -> database::Result<Vec<EmbeddedMigration>> {
+        let dialect = self.dialect();
+        match self {
+            Pool::Postgres(pool) => migrations::run_pending_migrations(&*checkout(pool)?, dialect),
+            Pool::Mysql(pool) => migrations::run_pending_migrations(&*checkout(pool)?, dialect),
+            Pool::Sqlite(pool) => migrations::run_pending_migrations(&*checkout(pool)?, dialect),
+        }
+    }
+}
+
+/// Checks out a connection from `pool`.
+fn checkout<M: r2d2::ManageConnection>(
+    pool: &r2d2::Pool<M>,
+) -> database::Result<r2d2::PooledConnection<M>> {
+    pool.get().map_err(IntoDatabaseError::into_database_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::NewExerciseBuilder;
+
+    /// Test that all connections to an in-memory SQLite database share the same database.
+    #[test]
+    fn share_in_memory_sqlite_database() {
+        let pool = Pool::new(&Config::new("sqlite://:memory:")).unwrap();
+        assert_eq!(
+            pool.pending_migrations().unwrap(),
+            migrations::embedded_migrations(Dialect::Sqlite)
+        );
+        pool.run_pending_migrations().unwrap();
+        assert_eq!(pool.pending_migrations().unwrap(), vec![]);
+
+        let new_exercise = NewExerciseBuilder::new()
+            .title("Albatross")
+            .body("Albatross body")
+            .build()
+            .unwrap();
+        let exercise = pool.get().unwrap().create(&new_exercise).unwrap();
+        assert_eq!(
+            pool.get().unwrap().find_by_id(exercise.id.as_str()),
+            Ok(exercise)
+        );
+    }
+
+    /// Test that inconsistent pool settings are reported instead of panicking.
+    #[test]
+    fn reject_invalid_pool_settings() {
+        let mut config = Config::new(":memory:");
+        config.pool_min_idle = Some(config.pool_max_size + 1);
+        assert!(matches!(
+            Pool::new(&config),
+            Err(database::Error::ServerError(_))
+        ));
+        config.pool_min_idle = None;
+        config.pool_max_size = 0;
+        assert!(matches!(
+            Pool::new(&config),
+            Err(database::Error::ServerError(_))
+        ));
+    }
+}
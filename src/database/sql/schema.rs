@@ -1,3 +1,30 @@
+table! {
+    exercise_revisions (id) {
+        id -> Varchar,
+        exercise_id -> Varchar,
+        version -> Integer,
+        title -> Varchar,
+        body -> Text,
+        topic -> Nullable<Varchar>,
+        author_id -> Nullable<Varchar>,
+        created_on -> Timestamp,
+    }
+}
+
+table! {
+    exercise_tags (exercise_id, tag_id) {
+        exercise_id -> Varchar,
+        tag_id -> Varchar,
+    }
+}
+
+table! {
+    exercise_topics (exercise_id, topic_id) {
+        exercise_id -> Varchar,
+        topic_id -> Varchar,
+    }
+}
+
 table! {
     exercises (id) {
         id -> Varchar,
@@ -6,5 +33,77 @@ table! {
         topic -> Nullable<Varchar>,
         created_on -> Timestamp,
         modified_on -> Timestamp,
+        author_id -> Nullable<Varchar>,
+        version -> Integer,
+        series_id -> Nullable<Varchar>,
+        part_index -> Nullable<Integer>,
+        difficulty -> Nullable<Integer>,
+        deleted_on -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    tags (id) {
+        id -> Varchar,
+        name -> Varchar,
+        created_on -> Timestamp,
+    }
+}
+
+table! {
+    topics (id) {
+        id -> Varchar,
+        parent_id -> Nullable<Varchar>,
+        slug -> Varchar,
+        name -> Varchar,
+        description -> Nullable<Text>,
+        created_on -> Timestamp,
+    }
+}
+
+table! {
+    typing_attempts (id) {
+        id -> Varchar,
+        exercise_id -> Varchar,
+        started_on -> Timestamp,
+        finished_on -> Timestamp,
+        typed_text -> Text,
+        keystrokes -> Integer,
+        errors -> Integer,
+        gross_wpm -> Double,
+        accuracy -> Double,
+        net_wpm -> Double,
+        corrected_errors -> Integer,
+        error_positions -> Text,
+    }
+}
+
+table! {
+    users (id) {
+        id -> Varchar,
+        username -> Varchar,
+        password_hash -> Varchar,
+        role -> Varchar,
+        created_on -> Timestamp,
     }
 }
+
+joinable!(exercise_revisions -> exercises (exercise_id));
+joinable!(exercise_revisions -> users (author_id));
+joinable!(exercise_tags -> exercises (exercise_id));
+joinable!(exercise_tags -> tags (tag_id));
+joinable!(exercise_topics -> exercises (exercise_id));
+joinable!(exercise_topics -> topics (topic_id));
+joinable!(exercises -> users (author_id));
+joinable!(typing_attempts -> exercises (exercise_id));
+
+allow_tables_to_appear_in_same_query!(
+    exercise_revisions,
+    exercise_tags,
+    exercise_topics,
+    exercises,
+    tags,
+    topics,
+    typing_attempts,
+    users,
+);
@@ -0,0 +1,202 @@
+/// Largest number of cells of the table used to align the changed words of two texts.
+///
+/// Texts whose changed parts are too long to align (e.g. rewritten from scratch) are diffed as the
+/// deletion of the old changed words followed by the insertion of the new ones.
+const MAX_TABLE_SIZE: usize = 4_000_000;
+
+/// What a `Chunk` of a diff does to the old text.
+#[derive(juniper::GraphQLEnum, Clone, Copy, Debug, Eq, PartialEq)]
+#[graphql(name = "DiffOperation")]
+pub enum Operation {
+    /// The words are in both texts.
+    Equal,
+
+    /// The words are only in the old text.
+    Delete,
+
+    /// The words are only in the new text.
+    Insert,
+}
+
+/// A run of consecutive words of a diff.
+#[derive(juniper::GraphQLObject, Clone, Debug, Eq, PartialEq)]
+#[graphql(name = "DiffChunk")]
+pub struct Chunk {
+    /// What the chunk does to the old text.
+    pub operation: Operation,
+
+    /// The words of the chunk, separated by single spaces.
+    pub text: String,
+}
+
+/// Returns the word-level differences between `old` and `new`, as the chunks turning `old` into
+/// `new`.
+///
+/// Words are separated by whitespace, which is otherwise ignored. Adjacent words with the same
+/// operation are merged into one chunk, and within a change, deleted words come before inserted
+/// words.
+///
+/// # Examples
+///
+/// ```
+/// use wikitype_api::diff::{diff_words, Chunk, Operation};
+///
+/// let chunks = diff_words("Albatrosses are large seabirds.", "Albatrosses are big seabirds.");
+/// let chunk = |operation, text: &str| Chunk { operation, text: String::from(text) };
+/// assert_eq!(
+///     chunks,
+///     vec![
+///         chunk(Operation::Equal, "Albatrosses are"),
+///         chunk(Operation::Delete, "large"),
+///         chunk(Operation::Insert, "big"),
+///         chunk(Operation::Equal, "seabirds."),
+///     ]
+/// );
+/// ```
+pub fn diff_words(old: &str, new: &str) -> Vec<Chunk> {
+    let old: Vec<&str> = old.split_whitespace().collect();
+    let new: Vec<&str> = new.split_whitespace().collect();
+
+    // NOTE: Edits are usually local, so only the words between the common prefix and suffix are
+    // aligned.
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut chunks = Vec::new();
+    push_words(&mut chunks, Operation::Equal, &old[..prefix]);
+    for (operation, word) in align(
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    ) {
+        push_words(&mut chunks, operation, &[word]);
+    }
+    push_words(&mut chunks, Operation::Equal, &old[old.len() - suffix..]);
+    chunks
+}
+
+/// Aligns `old` and `new` along one of their longest common subsequences, returning every word
+/// along with its operation.
+fn align<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Operation, &'a str)> {
+    if old.len().saturating_mul(new.len()) > MAX_TABLE_SIZE {
+        let deleted = old.iter().map(|word| (Operation::Delete, *word));
+        let inserted = new.iter().map(|word| (Operation::Insert, *word));
+        return deleted.chain(inserted).collect();
+    }
+
+    // `lengths[i * width + j]` is the length of the longest common subsequence of `old[i..]` and
+    // `new[j..]`.
+    let width = new.len() + 1;
+    let mut lengths = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut words = Vec::with_capacity(old.len() + new.len());
+    let mut inserted = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            words.append(&mut inserted);
+            words.push((Operation::Equal, old[i]));
+            i += 1;
+            j += 1;
+        } else if j == new.len()
+            || (i < old.len() && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+        {
+            words.push((Operation::Delete, old[i]));
+            i += 1;
+        } else {
+            // NOTE: Inserted words are held back until the end of the change, so that deleted
+            // words come first.
+            inserted.push((Operation::Insert, new[j]));
+            j += 1;
+        }
+    }
+    words.append(&mut inserted);
+    words
+}
+
+/// Appends `words` to `chunks`, extending the last chunk if it has the same operation.
+fn push_words(chunks: &mut Vec<Chunk>, operation: Operation, words: &[&str]) {
+    if words.is_empty() {
+        return;
+    }
+    let text = words.join(" ");
+    match chunks.last_mut() {
+        Some(last) if last.operation == operation => {
+            last.text.push(' ');
+            last.text.push_str(&text);
+        }
+        _ => chunks.push(Chunk { operation, text }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(operation: Operation, text: &str) -> Chunk {
+        Chunk {
+            operation,
+            text: String::from(text),
+        }
+    }
+
+    /// Test diffing texts with changes at the start, in the middle and at the end.
+    #[test]
+    fn diff_changed_words() {
+        assert_eq!(diff_words("", ""), vec![]);
+        assert_eq!(
+            diff_words("a  b\nc", "a b c"),
+            vec![chunk(Operation::Equal, "a b c")]
+        );
+        assert_eq!(diff_words("", "a b"), vec![chunk(Operation::Insert, "a b")]);
+        assert_eq!(
+            diff_words("x a b c y", "a d c z"),
+            vec![
+                chunk(Operation::Delete, "x"),
+                chunk(Operation::Equal, "a"),
+                chunk(Operation::Delete, "b"),
+                chunk(Operation::Insert, "d"),
+                chunk(Operation::Equal, "c"),
+                chunk(Operation::Delete, "y"),
+                chunk(Operation::Insert, "z"),
+            ]
+        );
+        assert_eq!(
+            diff_words("a b a b", "b a b"),
+            vec![
+                chunk(Operation::Delete, "a"),
+                chunk(Operation::Equal, "b a b"),
+            ]
+        );
+    }
+
+    /// Test that texts too long to align are diffed as a deletion followed by an insertion.
+    #[test]
+    fn diff_long_texts() {
+        let old = vec!["old"; 2001].join(" ");
+        let new = vec!["new"; 2001].join(" ");
+        let chunks = diff_words(&format!("a {} b", old), &format!("a {} b", new));
+        assert_eq!(
+            chunks,
+            vec![
+                chunk(Operation::Equal, "a"),
+                chunk(Operation::Delete, &old),
+                chunk(Operation::Insert, &new),
+                chunk(Operation::Equal, "b"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,167 @@
+/// Lowest difficulty of a text.
+pub const MIN_DIFFICULTY: i32 = 0;
+
+/// Highest difficulty of a text.
+pub const MAX_DIFFICULTY: i32 = 100;
+
+/// The most common letter bigrams of English text, which account for most of the bigrams of a
+/// typical text. Typing other bigrams takes unusual finger movements.
+///
+/// See <https://en.wikipedia.org/wiki/Bigram#Bigram_frequency_in_the_English_language>.
+const COMMON_BIGRAMS: [&str; 100] = [
+    "th", "he", "in", "er", "an", "re", "on", "at", "en", "nd", "ti", "es", "or", "te", "of", "ed",
+    "is", "it", "al", "ar", "st", "to", "nt", "ng", "se", "ha", "as", "ou", "io", "le", "ve", "co",
+    "me", "de", "hi", "ri", "ro", "ic", "ne", "ea", "ra", "ce", "li", "ch", "ll", "be", "ma", "si",
+    "om", "ur", "ca", "el", "ta", "la", "ns", "di", "fo", "ho", "pe", "ec", "pr", "no", "ct", "us",
+    "ac", "ot", "il", "tr", "ly", "nc", "et", "ut", "ss", "so", "rs", "un", "lo", "wa", "ge", "ie",
+    "wh", "ee", "wi", "em", "ad", "ol", "rt", "po", "we", "na", "ul", "ni", "ts", "mo", "ow", "pa",
+    "im", "mi", "ai", "sh",
+];
+
+/// Measurements of a text from which its difficulty is derived.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Features {
+    /// Average number of characters per word.
+    pub average_word_length: f64,
+
+    /// Fraction of the characters that are punctuation (e.g. `.`, `,` or `"`).
+    pub punctuation_density: f64,
+
+    /// Fraction of the characters that are digits or symbols (e.g. `7`, `%` or `é`).
+    pub symbol_density: f64,
+
+    /// Fraction of the letter bigrams within words that are not among the most common bigrams of
+    /// English text.
+    pub uncommon_bigram_ratio: f64,
+}
+
+impl Features {
+    /// Measures `text`.
+    pub fn of(text: &str) -> Features {
+        let mut chars = 0;
+        let mut punctuation = 0;
+        let mut symbols = 0;
+        for c in text.chars().filter(|c| !c.is_whitespace()) {
+            chars += 1;
+            if is_punctuation(c) {
+                punctuation += 1;
+            } else if !c.is_ascii_alphabetic() {
+                symbols += 1;
+            }
+        }
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let mut bigrams = 0;
+        let mut uncommon_bigrams = 0;
+        for word in &words {
+            let letters: Vec<char> = word
+                .chars()
+                .filter(char::is_ascii_alphabetic)
+                .map(|c| c.to_ascii_lowercase())
+                .collect();
+            for pair in letters.windows(2) {
+                let bigram: String = pair.iter().collect();
+                bigrams += 1;
+                if !COMMON_BIGRAMS.contains(&bigram.as_str()) {
+                    uncommon_bigrams += 1;
+                }
+            }
+        }
+        Features {
+            average_word_length: ratio(chars, words.len()),
+            punctuation_density: ratio(punctuation, chars),
+            symbol_density: ratio(symbols, chars),
+            uncommon_bigram_ratio: ratio(uncommon_bigrams, bigrams),
+        }
+    }
+
+    /// Combines the features into a difficulty between `MIN_DIFFICULTY` and `MAX_DIFFICULTY`.
+    ///
+    /// Each feature is scaled from the value of plain prose (easy) to the value of dense,
+    /// technical text (hard), then weighted.
+    pub fn difficulty(&self) -> i32 {
+        let score = 0.30 * scale(self.average_word_length, 4.0, 8.0)
+            + 0.20 * scale(self.punctuation_density, 0.02, 0.10)
+            + 0.25 * scale(self.symbol_density, 0.0, 0.10)
+            + 0.25 * scale(self.uncommon_bigram_ratio, 0.25, 0.60);
+        (score * f64::from(MAX_DIFFICULTY)).round() as i32
+    }
+}
+
+/// Returns the difficulty of typing `text`, from `MIN_DIFFICULTY` (plain prose of short words) to
+/// `MAX_DIFFICULTY`.
+///
+/// The difficulty only depends on the text, so it is the same whenever it is computed.
+///
+/// # Examples
+///
+/// ```
+/// use wikitype_api::difficulty::difficulty;
+///
+/// let prose = difficulty("The cat sat on the mat and then it went to the park.");
+/// let technical = difficulty("Set CFLAGS=\"-O2 -march=x86-64\" (v3.1; see §4.2b) & rebuild.");
+///
+/// assert_eq!(prose, difficulty("The cat sat on the mat and then it went to the park."));
+/// assert!(prose < 20);
+/// assert!(technical > 60);
+/// assert_eq!(difficulty(""), 0);
+/// ```
+pub fn difficulty(text: &str) -> i32 {
+    Features::of(text).difficulty()
+}
+
+/// Returns whether `c` is a punctuation mark found in regular prose.
+fn is_punctuation(c: char) -> bool {
+    ".,;:!?'\"()-".contains(c)
+}
+
+/// Returns `numerator / denominator`, or 0 if `denominator` is 0.
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+/// Scales `value` linearly from `easy` (0) to `hard` (1), clamping values outside that range.
+fn scale(value: f64, easy: f64, hard: f64) -> f64 {
+    ((value - easy) / (hard - easy)).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test measuring the features of a text.
+    #[test]
+    fn measure_text_features() {
+        let features = Features::of("Call 911, now!");
+        assert_eq!(features.average_word_length, 12.0 / 3.0);
+        assert_eq!(features.punctuation_density, 2.0 / 12.0);
+        assert_eq!(features.symbol_density, 3.0 / 12.0);
+        // "ca", "al", "ll", "no" and "ow" are all common.
+        assert_eq!(features.uncommon_bigram_ratio, 0.0);
+
+        let features = Features::of("jazz quiz");
+        assert_eq!(features.uncommon_bigram_ratio, 1.0);
+        assert_eq!(Features::of(""), Features::default());
+    }
+
+    /// Test that each feature makes a text harder.
+    #[test]
+    fn rate_harder_texts_higher() {
+        let prose = difficulty("The man went to the shop and then he sat in the sun.");
+        let long_words = difficulty(
+            "International organizations established comprehensive \
+                                     administrative regulations.",
+        );
+        let punctuated = difficulty("The man, then; the shop (and) \"sun\" - then: he sat!");
+        let symbols = difficulty("The man went to shop 42 at 10:30 and paid $7.50 + 8% tax.");
+        let bigrams = difficulty("Fjords, quartz, zephyrs; jukebox vex lymph gyms.");
+        for harder in &[long_words, punctuated, symbols, bigrams] {
+            assert!(prose < *harder, "{} < {}", prose, harder);
+        }
+        assert!(prose >= MIN_DIFFICULTY);
+        assert!(difficulty(&"%$#@!".repeat(10)) <= MAX_DIFFICULTY);
+    }
+}
@@ -1,11 +1,28 @@
+use crate::auth;
+use crate::auth::{Caller, TokenKind, TokenPair, TokenSigner};
+use crate::config::Config;
 use crate::database;
-use crate::database::sql::PgConnection;
-use crate::database::ExerciseDao;
+use crate::database::query;
+use crate::database::sql::pool::{Dialect, Pool};
+use crate::database::{Dao, IntoDatabaseError};
+use crate::diff;
 use crate::models;
-use crate::models::{Exercise, NewExerciseBuilder, UpdatedExerciseBuilder};
+use crate::models::{
+    Exercise, ExerciseRevision, NewExerciseBuilder, NewTopicBuilder, NewTypingAttemptBuilder,
+    NewUserBuilder, Tag, Topic, TopicNode, TypingAttempt, UpdatedExerciseBuilder, User, Uuid,
+};
+use crate::policy;
+use crate::policy::ExerciseAction;
+use crate::scoring;
+use crate::scoring::Score;
+use crate::text;
+use crate::text::{passages, NormalizeOptions};
+use crate::validation::{ExerciseLimits, ExerciseValidator};
 
-use diesel::r2d2::{ConnectionManager, Pool};
-use std::env;
+use chrono::NaiveDateTime;
+use std::sync::Arc;
+use warp::filters::BoxedFilter;
+use warp::Filter;
 
 /// Error-handling for database errors returned from resolvers.
 ///
@@ -23,6 +40,36 @@ impl juniper::IntoFieldError for database::Error {
             | Error::SerializationError(e) => {
                 juniper::FieldError::new(e, graphql_value!({"client_error": "bad_request"}))
             }
+            Error::Unauthorized(e) => {
+                juniper::FieldError::new(e, graphql_value!({"client_error": "unauthorized"}))
+            }
+            Error::Forbidden(e) => {
+                juniper::FieldError::new(e, graphql_value!({"client_error": "forbidden"}))
+            }
+            Error::Conflict(e) => {
+                juniper::FieldError::new(e, graphql_value!({"client_error": "conflict"}))
+            }
+            Error::ValidationError(errors) => {
+                let message = errors
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<String>>()
+                    .join("; ");
+                let fields = errors
+                    .iter()
+                    .map(|error| {
+                        let mut field = juniper::Object::with_capacity(3);
+                        field.add_field("field", juniper::Value::scalar(error.field));
+                        field.add_field("code", juniper::Value::scalar(error.violation.as_str()));
+                        field.add_field("message", juniper::Value::scalar(error.message.as_str()));
+                        juniper::Value::object(field)
+                    })
+                    .collect();
+                let mut extensions = juniper::Object::with_capacity(2);
+                extensions.add_field("client_error", juniper::Value::scalar("validation_error"));
+                extensions.add_field("validation_error", juniper::Value::list(fields));
+                juniper::FieldError::new(message, juniper::Value::object(extensions))
+            }
             Error::ServerError(e) => {
                 if let Some(e) = e {
                     log::error!("{}", e);
@@ -36,12 +83,138 @@ impl juniper::IntoFieldError for database::Error {
     }
 }
 
+#[juniper::object(Context = Context, description = "A WikiType typing exercise.")]
+impl Exercise {
+    /// UUID string.
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Title of the exercise.
+    fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Content of the exercise.
+    fn body(&self) -> &str {
+        &self.body
+    }
+
+    /// Optional free-text topic describing the general exercise category.
+    ///
+    /// See `topics` for the topics of the topic tree the exercise belongs to.
+    fn topic(&self) -> Option<&str> {
+        self.topic.as_deref()
+    }
+
+    /// Topics the exercise belongs to, sorted by name.
+    fn topics(&self, context: &Context) -> Result<Vec<Topic>, database::Error> {
+        let conn = context.conn()?;
+        conn.linked(&self.id)
+    }
+
+    /// Tags of the exercise, sorted by name.
+    fn tags(&self, context: &Context) -> Result<Vec<Tag>, database::Error> {
+        let conn = context.conn()?;
+        conn.linked(&self.id)
+    }
+
+    /// Date and time of creation.
+    fn created_on(&self) -> NaiveDateTime {
+        self.created_on
+    }
+
+    /// Date and time of the last modification.
+    fn modified_on(&self) -> NaiveDateTime {
+        self.modified_on
+    }
+
+    /// Id of the user who created the exercise (if created by a user that still exists).
+    fn author_id(&self) -> Option<&str> {
+        self.author_id.as_deref()
+    }
+
+    /// Version of the exercise, starting at 1 and incremented by every update.
+    fn version(&self) -> i32 {
+        self.version
+    }
+
+    /// Snapshots of every version of the exercise, from newest to oldest.
+    ///
+    /// Versions made before revisions were introduced are missing, except for the version the
+    /// exercise was at.
+    fn revisions(&self, context: &Context) -> Result<Vec<ExerciseRevision>, database::Error> {
+        let conn = context.conn()?;
+        conn.revisions(&self.id)
+    }
+
+    /// Id of the series the exercise is a part of, if it was split from a longer text.
+    fn series_id(&self) -> Option<&str> {
+        self.series_id.as_deref()
+    }
+
+    /// Index of the exercise within its series, starting at 0.
+    fn part_index(&self) -> Option<i32> {
+        self.part_index
+    }
+
+    /// Difficulty of typing the body, from 0 (easiest) to 100, derived from its word lengths,
+    /// punctuation, digits and symbols, and uncommon letter pairs. `null` if the exercise has not
+    /// been rated yet.
+    fn difficulty(&self) -> Option<i32> {
+        self.difficulty
+    }
+
+    /// Date and time the exercise was moved to the trash, or `null` if it is not trashed.
+    fn deleted_on(&self) -> Option<NaiveDateTime> {
+        self.deleted_on
+    }
+
+    /// The part of the series following the exercise, if any.
+    fn next(&self, context: &Context) -> Result<Option<Exercise>, database::Error> {
+        find_series_part(context, self, query::SortDirection::Ascending)
+    }
+
+    /// The part of the series preceding the exercise, if any.
+    fn previous(&self, context: &Context) -> Result<Option<Exercise>, database::Error> {
+        find_series_part(context, self, query::SortDirection::Descending)
+    }
+}
+
+/// Finds the nearest part after (`Ascending`) or before (`Descending`) `exercise` within
+/// its series, if any.
+///
+/// Trashed parts are skipped rather than ending the series, so trashing a middle part
+/// does not cut the series in two.
+fn find_series_part(
+    context: &Context,
+    exercise: &Exercise,
+    direction: query::SortDirection,
+) -> database::Result<Option<Exercise>> {
+    let (series_id, part_index) = match (&exercise.series_id, exercise.part_index) {
+        (Some(series_id), Some(part_index)) => (series_id, part_index),
+        _ => return Ok(None),
+    };
+    let mut builder = query::ExerciseQueryBuilder::new();
+    builder.series_id(series_id);
+    match direction {
+        query::SortDirection::Ascending => builder.min_part_index(part_index + 1),
+        query::SortDirection::Descending => builder.max_part_index(part_index - 1),
+    };
+    let query = builder
+        .sort_by(query::ExerciseSortField::PartIndex, direction)
+        .build();
+    let conn = context.conn()?;
+    let mut parts = conn.list(&query, 0, 1)?;
+    Ok(parts.pop())
+}
+
 /// Simplified type for creating a new `Exercise` via the API.
 ///
 /// This is the client-facing type which is converted into a `models::NewExercise` for
 /// database-insertion.
-#[graphql(description = "A WikiType typing exercise.")]
 #[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "A WikiType typing exercise.")]
 pub struct NewExercise {
     /// Title of the exercise.
     pub title: String,
@@ -49,201 +222,1823 @@ pub struct NewExercise {
     /// Content of the exercise.
     pub body: String,
 
-    /// Optional topic describing the general exercise category.
+    /// Optional topic describing the general exercise category, which links the exercise to the
+    /// topic with the same slug (creating it if needed).
     ///
     /// See <https://en.wikipedia.org/wiki/Portal:Contents/Portals> for an idea.
     pub topic: Option<String>,
 }
 
 impl NewExercise {
-    /// Converts a `graphql::NewExercise` to a `models::NewExercise`.
-    pub fn to_new_exercise_model(&self) -> models::NewExercise {
-        NewExerciseBuilder::new()
+    /// Normalizes the body of the exercise (see `text::normalize`), returning what was changed.
+    pub fn normalize_body(&mut self, options: &NormalizeOptions) -> Vec<text::Change> {
+        let normalized = text::normalize(&self.body, options);
+        self.body = normalized.text;
+        normalized.changes
+    }
+
+    /// Validates the fields of the exercise (see `validation::ExerciseValidator`).
+    pub fn validate(&self, limits: &ExerciseLimits) -> database::Result<()> {
+        ExerciseValidator::new(limits)
+            .title(&self.title)
+            .body(&self.body)
+            .topic(self.topic.as_deref())
+            .finish()
+    }
+
+    /// Converts a `graphql::NewExercise` to a `models::NewExercise` authored by the given user.
+    pub fn to_new_exercise_model(
+        &self,
+        author_id: Option<&str>,
+    ) -> database::Result<models::NewExercise> {
+        let new_exercise = NewExerciseBuilder::new()
             .title(&self.title)
             .body(&self.body)
             .topic(self.topic.as_ref().map(|t| &**t))
-            .build()
+            .author_id(author_id)
+            .build()?;
+        Ok(new_exercise)
+    }
+}
+
+/// Number of characters of each part of a series when no `partLength` is given.
+const DEFAULT_SERIES_PART_LENGTH: i32 = 500;
+
+/// Minimum `partLength` of a series.
+const MIN_SERIES_PART_LENGTH: i32 = 50;
+
+/// Maximum `partLength` of a series.
+const MAX_SERIES_PART_LENGTH: i32 = 5000;
+
+/// Type for creating a series of `Exercise`s from a long text via the API.
+///
+/// This is the client-facing type which is split into `models::NewExercise`s for
+/// database-insertion.
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "A long text to split into a series of WikiType typing exercises.")]
+pub struct NewExerciseSeries {
+    /// Title of the series. Parts are titled "<title> (<part>/<parts>)".
+    pub title: String,
+
+    /// Text to split into exercises, with paragraphs separated by blank lines.
+    pub body: String,
+
+    /// Optional topic of every part.
+    pub topic: Option<String>,
+
+    /// Number of characters each part should have (500 by default). Parts are split between
+    /// paragraphs or sentences where possible.
+    pub part_length: Option<i32>,
+}
+
+impl NewExerciseSeries {
+    /// Splits a `graphql::NewExerciseSeries` into the `models::NewExercise`s of the series
+    /// `series_id` authored by the given user, normalizing and validating each part.
+    pub fn to_new_exercise_models(
+        &self,
+        series_id: &str,
+        author_id: Option<&str>,
+        normalization: &NormalizeOptions,
+        limits: &ExerciseLimits,
+    ) -> database::Result<Vec<models::NewExercise>> {
+        let part_length = self.part_length.unwrap_or(DEFAULT_SERIES_PART_LENGTH);
+        if !(MIN_SERIES_PART_LENGTH..=MAX_SERIES_PART_LENGTH).contains(&part_length) {
+            return Err(database::Error::QueryError(format!(
+                "partLength must be between {} and {}",
+                MIN_SERIES_PART_LENGTH, MAX_SERIES_PART_LENGTH
+            )));
+        }
+        let parts: Vec<String> = passages::segment(&self.body, part_length as usize)
+            .iter()
+            .map(|part| text::normalize(part, normalization).text)
+            .filter(|part| !part.is_empty())
+            .collect();
+        if parts.is_empty() {
+            return Err(database::Error::QueryError(String::from(
+                "The body of a series must not be empty",
+            )));
+        }
+        let count = parts.len();
+        let titles: Vec<String> = (0..count)
+            .map(|i| models::series_part_title(&self.title, i, count))
+            .collect();
+        let mut validator = ExerciseValidator::new(limits);
+        validator.title(&self.title).topic(self.topic.as_deref());
+        for (title, part) in titles.iter().zip(&parts) {
+            validator.title(title).body(part);
+        }
+        validator.finish()?;
+        let new_exercises = parts
+            .iter()
+            .enumerate()
+            .map(|(i, part)| {
+                NewExerciseBuilder::new()
+                    .title(&titles[i])
+                    .body(part)
+                    .topic(self.topic.as_deref())
+                    .author_id(author_id)
+                    .series(series_id, i as i32)
+                    .build()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(new_exercises)
     }
 }
 
 /// Simplified type for updating an `Exercise` via the API.
 ///
 /// This is the client-facing type which is converted into a `models::UpdatedExercise` for
-/// updating.
-#[graphql(description = "A WikiType typing exercise.")]
-#[derive(juniper::GraphQLInputObject)]
+/// updating. Fields which are absent leave the exercise unchanged.
+///
+/// NOTE: Implemented by hand (rather than derived) since derived input objects read absent fields
+/// as `null`, which would not let clients tell clearing a nullable column from leaving it as is.
 pub struct UpdatedExercise {
     /// UUID string.
     pub id: String,
 
-    /// Title of the exercise.
+    /// Title of the exercise (unchanged if absent or `null`).
     pub title: Option<String>,
 
-    /// Content of the exercise.
+    /// Content of the exercise (unchanged if absent or `null`).
     pub body: Option<String>,
 
-    /// Optional topic describing the general exercise category.
+    /// Optional topic describing the general exercise category: `None` if absent (unchanged),
+    /// `Some(None)` if `null` (cleared).
     ///
     /// See <https://en.wikipedia.org/wiki/Portal:Contents/Portals> for an idea.
-    pub topic: Option<String>,
+    pub topic: Option<Option<String>>,
+
+    /// Version of the exercise the update is based on. If set and the exercise has since been
+    /// updated, the update fails with a `conflict` error.
+    pub expected_version: Option<i32>,
+}
+
+impl<S> juniper::GraphQLType<S> for UpdatedExercise
+where
+    S: juniper::ScalarValue,
+    for<'b> &'b S: juniper::ScalarRefValue<'b>,
+{
+    type Context = ();
+    type TypeInfo = ();
+
+    fn name(_: &()) -> Option<&'static str> {
+        Some("UpdatedExercise")
+    }
+
+    fn meta<'r>(_: &(), registry: &mut juniper::Registry<'r, S>) -> juniper::meta::MetaType<'r, S>
+    where
+        S: 'r,
+    {
+        let fields = &[
+            registry
+                .arg::<String>("id", &())
+                .description("UUID string."),
+            registry
+                .arg::<Option<String>>("title", &())
+                .description("Title of the exercise. Unchanged if absent or null."),
+            registry
+                .arg::<Option<String>>("body", &())
+                .description("Content of the exercise. Unchanged if absent or null."),
+            registry.arg::<Option<String>>("topic", &()).description(
+                "Optional topic describing the general exercise category. Unchanged if absent, \
+                 cleared if null.",
+            ),
+            registry
+                .arg::<Option<i32>>("expectedVersion", &())
+                .description(
+                    "Version of the exercise the update is based on. If set and the exercise has \
+                     since been updated, the update fails with a `conflict` error.",
+                ),
+        ];
+        registry
+            .build_input_object_type::<UpdatedExercise>(&(), fields)
+            .description("A WikiType typing exercise.")
+            .into_meta()
+    }
+}
+
+impl<S> juniper::FromInputValue<S> for UpdatedExercise
+where
+    S: juniper::ScalarValue,
+{
+    fn from_input_value(value: &juniper::InputValue<S>) -> Option<UpdatedExercise>
+    where
+        for<'b> &'b S: juniper::ScalarRefValue<'b>,
+    {
+        let obj = value.to_object_value()?;
+        let field = |name: &str| obj.get(name).copied();
+        let null = juniper::InputValue::null();
+        Some(UpdatedExercise {
+            id: juniper::FromInputValue::from_input_value(field("id")?)?,
+            title: juniper::FromInputValue::from_input_value(field("title").unwrap_or(&null))?,
+            body: juniper::FromInputValue::from_input_value(field("body").unwrap_or(&null))?,
+            topic: match field("topic") {
+                Some(topic) => Some(juniper::FromInputValue::from_input_value(topic)?),
+                None => None,
+            },
+            expected_version: juniper::FromInputValue::from_input_value(
+                field("expectedVersion").unwrap_or(&null),
+            )?,
+        })
+    }
 }
 
 impl UpdatedExercise {
-    /// Converts a `graphql::UpdatedExercise` to a `models::UpdatedExercise`.
-    pub fn to_updated_exercise_model(&self) -> models::UpdatedExercise {
+    /// Normalizes the body of the exercise, if updated (see `text::normalize`), returning what was
+    /// changed.
+    pub fn normalize_body(&mut self, options: &NormalizeOptions) -> Vec<text::Change> {
+        match self.body.as_mut() {
+            Some(body) => {
+                let normalized = text::normalize(body, options);
+                *body = normalized.text;
+                normalized.changes
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Validates the fields of the exercise which are updated (see
+    /// `validation::ExerciseValidator`).
+    pub fn validate(&self, limits: &ExerciseLimits) -> database::Result<()> {
+        let mut validator = ExerciseValidator::new(limits);
+        if let Some(title) = &self.title {
+            validator.title(title);
+        }
+        if let Some(body) = &self.body {
+            validator.body(body);
+        }
+        if let Some(topic) = &self.topic {
+            validator.topic(topic.as_deref());
+        }
+        validator.finish()
+    }
+
+    /// Converts a `graphql::UpdatedExercise` to a `models::UpdatedExercise` made by the user with
+    /// id `editor_id`, failing if the update would not change any field.
+    pub fn to_updated_exercise_model<'a>(
+        &'a self,
+        editor_id: Option<&'a str>,
+    ) -> database::Result<models::UpdatedExercise<'a>> {
         let mut update = UpdatedExerciseBuilder::new(&self.id);
-        self.title.as_ref().map(|title| update.title(title));
-        self.body.as_ref().map(|body| update.body(body));
-        update.topic(self.topic.as_ref().map(|t| &**t));
-        update.build()
+        update.editor_id(editor_id);
+        if let Some(title) = &self.title {
+            update.title(title);
+        }
+        if let Some(body) = &self.body {
+            update.body(body);
+        }
+        if let Some(topic) = &self.topic {
+            update.topic(topic.as_deref());
+        }
+        if let Some(version) = self.expected_version {
+            update.expected_version(version);
+        }
+        let updated_exercise = update.build()?;
+        Ok(updated_exercise)
     }
 }
 
-/// Defines shared state for GraphQL resolvers (e.g. database connections).
-pub struct Context {
-    // Postgres connection pool.
-    //
-    // NOTE: The database should already contain the `exercises` table. Otherwise, run the
-    // migrations against the database.
-    //
-    // i.e.
-    //     $ diesel migration run
-    pool: Pool<ConnectionManager<PgConnection>>,
+/// Maximum number of characters in the name or slug of a topic.
+const MAX_TOPIC_NAME_LENGTH: usize = 255;
+
+/// Simplified type for creating a new `Topic` via the API.
+///
+/// This is the client-facing type which is converted into a `models::NewTopic` for
+/// database-insertion.
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "A topic in the tree of exercise categories.")]
+pub struct NewTopic {
+    /// Display name of the topic.
+    pub name: String,
+
+    /// Unique, URL-friendly name of the topic (derived from the name if not given).
+    pub slug: Option<String>,
+
+    /// Optional description of the exercises in the topic.
+    pub description: Option<String>,
+
+    /// Id of the topic to create the topic as a subtopic of.
+    pub parent_id: Option<String>,
 }
 
-impl Context {
-    pub fn new() -> Context {
-        let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-        let manager = ConnectionManager::new(database_url);
-        let pool = Pool::builder().max_size(20).build(manager).unwrap();
-        Context { pool }
+impl NewTopic {
+    /// Converts a `graphql::NewTopic` to a `models::NewTopic`.
+    ///
+    /// Names must be between 1 and 255 characters long. Slugs must consist of lowercase words
+    /// joined by hyphens (see `models::slugify`).
+    pub fn to_new_topic_model(&self) -> database::Result<models::NewTopic> {
+        let name = self.name.trim();
+        if name.is_empty() || name.chars().count() > MAX_TOPIC_NAME_LENGTH {
+            return Err(database::Error::QueryError(format!(
+                "Topic names must be between 1 and {} characters long",
+                MAX_TOPIC_NAME_LENGTH
+            )));
+        }
+        let slug = self.slug.clone().unwrap_or_else(|| models::slugify(name));
+        if slug.is_empty()
+            || slug.chars().count() > MAX_TOPIC_NAME_LENGTH
+            || models::slugify(&slug) != slug
+        {
+            return Err(database::Error::QueryError(format!(
+                "Invalid topic slug {:?}: slugs must consist of lowercase words joined by hyphens",
+                slug
+            )));
+        }
+        Ok(NewTopicBuilder::new()
+            .name(name)
+            .slug(&slug)
+            .description(self.description.as_deref())
+            .parent_id(self.parent_id.as_deref())
+            .build()?)
     }
 }
 
-impl juniper::Context for Context {}
+/// Maximum number of characters in the name of a tag.
+const MAX_TAG_LENGTH: usize = 64;
 
-/// Defines available non-side-effecting queries on a GraphQL endpoint.
-pub struct Query;
+/// Normalizes tag names as submitted via the API into the names tags are stored with, e.g.
+/// "Numbers heavy" into "numbers-heavy" (see `models::slugify`).
+fn normalize_tag(tag: &str) -> String {
+    models::slugify(tag)
+}
 
-#[juniper::object(Context = Context)]
-impl Query {
-    fn apiVersion() -> &'static str {
-        "1.0"
-    }
+/// Normalizes the tag names of a mutation, failing if a tag is empty or too long.
+fn normalize_tags(tags: &[String]) -> database::Result<Vec<String>> {
+    tags.iter()
+        .map(|tag| {
+            let normalized = normalize_tag(tag);
+            if normalized.is_empty() || normalized.chars().count() > MAX_TAG_LENGTH {
+                return Err(database::Error::QueryError(format!(
+                    "Invalid tag {:?}: tags must contain between 1 and {} letters or digits",
+                    tag, MAX_TAG_LENGTH
+                )));
+            }
+            Ok(normalized)
+        })
+        .collect()
+}
 
-    fn exercise(context: &Context, id: String) -> Result<Exercise, database::Error> {
-        let conn: &dyn ExerciseDao = &context.pool.get().unwrap();
-        let exercise = conn.find_by_id(&id)?;
-        Ok(exercise)
+/// A key pressed while typing an exercise, as submitted via the API.
+///
+/// This is the client-facing type which is converted into a `scoring::Keystroke`.
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "A key pressed while typing an exercise.")]
+pub struct Keystroke {
+    /// The typed character, or "Backspace".
+    pub key: String,
+
+    /// Number of milliseconds between the start of the attempt and the key press.
+    pub elapsed_ms: i32,
+}
+
+/// `key` of a backspace `Keystroke`.
+const BACKSPACE_KEY: &str = "Backspace";
+
+impl Keystroke {
+    /// Converts a `graphql::Keystroke` to a `scoring::Keystroke`.
+    pub fn to_keystroke_model(&self) -> database::Result<scoring::Keystroke> {
+        let mut chars = self.key.chars();
+        let key = match (chars.next(), chars.next()) {
+            _ if self.key == BACKSPACE_KEY => scoring::Key::Backspace,
+            (Some(c), None) => scoring::Key::Char(c),
+            _ => {
+                return Err(database::Error::QueryError(format!(
+                    "Invalid key {:?}: expected a single character or {:?}",
+                    self.key, BACKSPACE_KEY
+                )))
+            }
+        };
+        Ok(scoring::Keystroke {
+            key,
+            elapsed_ms: i64::from(self.elapsed_ms),
+        })
     }
 }
 
-/// Defines available side-effecting queries on a GraphQL endpoint.
-pub struct Mutation;
+/// Simplified type for submitting a new `TypingAttempt` via the API.
+///
+/// This is the client-facing type which is scored and converted into a
+/// `models::NewTypingAttempt` for database-insertion.
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "An attempt at typing a WikiType exercise.")]
+pub struct NewTypingAttempt {
+    /// Id of the exercise that was typed.
+    pub exercise_id: String,
 
-#[juniper::object(Context = Context)]
-impl Mutation {
-    fn createExercise(
-        context: &Context,
-        new_exercise: NewExercise,
-    ) -> Result<Exercise, database::Error> {
-        let conn: &dyn ExerciseDao = &executor.context().pool.get().unwrap();
-        let new_exercise = new_exercise.to_new_exercise_model();
-        let exercise = conn.create(&new_exercise)?;
-        Ok(exercise)
+    /// Date and time the typist started typing.
+    pub started_on: NaiveDateTime,
+
+    /// Every key the typist pressed, in order.
+    pub keystrokes: Vec<Keystroke>,
+}
+
+impl NewTypingAttempt {
+    /// Scores the keystroke log of the attempt against the body of `exercise`.
+    pub fn score(&self, exercise: &Exercise) -> database::Result<Score> {
+        let keystrokes = self
+            .keystrokes
+            .iter()
+            .map(Keystroke::to_keystroke_model)
+            .collect::<database::Result<Vec<_>>>()?;
+        scoring::score(&exercise.body, &keystrokes)
+            .map_err(|e| database::Error::QueryError(e.to_string()))
     }
 
-    fn updateExercise(
-        context: &Context,
-        updated_exercise: UpdatedExercise,
-    ) -> Result<Exercise, database::Error> {
-        let conn: &dyn ExerciseDao = &executor.context().pool.get().unwrap();
-        let updated_exercise = updated_exercise.to_updated_exercise_model();
-        let exercise = conn.update(&updated_exercise)?;
-        Ok(exercise)
+    /// Converts a scored `graphql::NewTypingAttempt` to a `models::NewTypingAttempt`.
+    pub fn to_new_typing_attempt_model(
+        &self,
+        score: &Score,
+    ) -> database::Result<models::NewTypingAttempt> {
+        let new_attempt = NewTypingAttemptBuilder::new()
+            .exercise_id(&self.exercise_id)
+            .started_on(self.started_on)
+            .score(score)
+            .build()?;
+        Ok(new_attempt)
     }
+}
 
-    fn deleteExerciseById(context: &Context, id: String) -> Result<Exercise, database::Error> {
-        let conn: &dyn ExerciseDao = &executor.context().pool.get().unwrap();
-        let exercise = conn.delete_by_id(&id)?;
-        Ok(exercise)
+/// Minimum number of characters in a password.
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Maximum number of characters in a username.
+const MAX_USERNAME_LENGTH: usize = 64;
+
+/// Username and password of a user, as submitted via the API.
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Username and password of a WikiType user.")]
+pub struct Credentials {
+    /// Unique name of the user.
+    pub username: String,
+
+    /// Password of the user.
+    pub password: String,
+}
+
+impl Credentials {
+    /// Converts `graphql::Credentials` to a `models::NewUser`, hashing the password.
+    ///
+    /// Usernames must be between 1 and 64 characters long and not start or end with whitespace.
+    /// Passwords must be at least 8 characters long.
+    pub fn to_new_user_model(&self) -> database::Result<models::NewUser> {
+        let length = self.username.chars().count();
+        if length == 0 || length > MAX_USERNAME_LENGTH || self.username.trim() != self.username {
+            return Err(database::Error::QueryError(format!(
+                "Usernames must be between 1 and {} characters long and not start or end with \
+                 whitespace",
+                MAX_USERNAME_LENGTH
+            )));
+        }
+        if self.password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(database::Error::QueryError(format!(
+                "Passwords must be at least {} characters long",
+                MIN_PASSWORD_LENGTH
+            )));
+        }
+        let password_hash =
+            auth::hash_password(&self.password).map_err(IntoDatabaseError::into_database_error)?;
+        Ok(NewUserBuilder::new()
+            .username(&self.username)
+            .password_hash(&password_hash)
+            .build()?)
     }
 }
 
-/// Type alias for `juniper::RootNode<...>` (needed when implementing a GraphQL endpoint).
-pub type Schema = juniper::RootNode<'static, Query, Mutation>;
+/// Tokens issued to a user on registration, login or refresh.
+#[derive(juniper::GraphQLObject)]
+pub struct AuthPayload {
+    /// The authenticated user.
+    pub user: User,
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use bytes::Bytes;
-    use dotenv::dotenv;
-    use http::response::Response;
-    use std::env;
-    use warp::{test, Filter, Reply};
+    /// Token to send with requests (as `Authorization: Bearer <token>`).
+    pub access_token: String,
 
-    #[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
-    struct Exercise {
-        pub id: Option<String>,
-        pub title: Option<String>,
-        pub body: Option<String>,
-        pub topic: Option<String>,
-        #[serde(rename = "createdOn")]
-        pub created_on: Option<f64>,
-        #[serde(rename = "modifiedOn")]
-        pub modified_on: Option<f64>,
+    /// Token to exchange for new tokens (via `refreshTokens`) once the access token expires.
+    pub refresh_token: String,
+
+    /// Number of seconds until the access token expires.
+    pub expires_in: i32,
+}
+
+impl AuthPayload {
+    /// Issues new tokens to `user` with the signer of `context`.
+    fn issue(context: &Context, user: User) -> database::Result<AuthPayload> {
+        let TokenPair {
+            access_token,
+            refresh_token,
+            expires_in,
+        } = context
+            .tokens
+            .issue(&user)
+            .map_err(IntoDatabaseError::into_database_error)?;
+        Ok(AuthPayload {
+            user,
+            access_token,
+            refresh_token,
+            expires_in: expires_in as i32,
+        })
     }
+}
 
-    /// Create a new `graphql::Schema`.
-    fn schema() -> Schema {
-        Schema::new(Query, Mutation)
+/// Maximum number of items in a single batch mutation.
+const MAX_BATCH_SIZE: usize = 1000;
+
+/// Error of an item of a batch mutation.
+#[derive(juniper::GraphQLObject, Debug, PartialEq)]
+pub struct BatchError {
+    /// Position of the failed item in the batch (starting at 0).
+    pub index: i32,
+
+    /// Description of the error.
+    pub message: String,
+
+    /// Code of the error, as in the `client_error` (or `server_error`) extension of errors.
+    pub code: String,
+}
+
+impl BatchError {
+    /// Converts the error of the item at `index` like an error returned from a resolver.
+    fn new(index: usize, error: database::Error) -> BatchError {
+        let error = juniper::IntoFieldError::into_field_error(error);
+        let code = error
+            .extensions()
+            .as_object_value()
+            .and_then(|extensions| {
+                extensions
+                    .get_field_value("client_error")
+                    .or_else(|| extensions.get_field_value("server_error"))
+            })
+            .and_then(|code| code.as_scalar_value::<String>())
+            .map(String::as_str)
+            .unwrap_or_default();
+        BatchError {
+            index: index as i32,
+            message: error.message().to_string(),
+            code: code.to_string(),
+        }
     }
+}
 
-    /// Create a new `warp::Filter` at `/graphql` for handling GraphQL requests.
-    fn create_graphql_filter() -> warp::filters::BoxedFilter<(impl Reply,)> {
-        let state = warp::any().map(move || Context::new());
-        let graphql_filter = juniper_warp::make_graphql_filter(schema(), state.boxed());
-        let graphql_filter = warp::path("graphql").and(graphql_filter);
-        graphql_filter.boxed()
+/// Results of a batch mutation on exercises.
+#[derive(juniper::GraphQLObject)]
+#[graphql(Context = Context, Scalar = juniper::DefaultScalarValue)]
+pub struct ExerciseBatch {
+    /// The resulting exercise of each item in the batch, in order (null if the item failed).
+    pub exercises: Vec<Option<Exercise>>,
+
+    /// Errors of the failed items.
+    pub errors: Vec<BatchError>,
+}
+
+impl ExerciseBatch {
+    /// Collects the results of the items of a batch.
+    fn new(results: Vec<database::Result<Exercise>>) -> ExerciseBatch {
+        let mut batch = ExerciseBatch {
+            exercises: Vec::with_capacity(results.len()),
+            errors: Vec::new(),
+        };
+        for (index, result) in results.into_iter().enumerate() {
+            match result {
+                Ok(exercise) => batch.exercises.push(Some(exercise)),
+                Err(e) => {
+                    batch.exercises.push(None);
+                    batch.errors.push(BatchError::new(index, e));
+                }
+            }
+        }
+        batch
     }
+}
 
-    /// Create a new `warp::test::RequestBuilder` for testing a `warp::Filter` that handles GraphQL
-    /// requests.
-    fn make_test_graphql_request(request: &str) -> test::RequestBuilder {
-        test::request()
-            .method("POST")
-            .path("/graphql")
-            .header("accept", "application/json")
-            .header("content-type", "application/json")
-            .body(request)
+/// Fails if a batch of `size` items is too large.
+fn check_batch_size(size: usize) -> database::Result<()> {
+    if size > MAX_BATCH_SIZE {
+        return Err(database::Error::QueryError(format!(
+            "Batches may contain at most {} items",
+            MAX_BATCH_SIZE
+        )));
     }
+    Ok(())
+}
 
-    /// Create the JSON-encoded body of a GraphQL POST request.
-    ///
-    /// Returns a `String`.
-    ///
-    /// # Usage
-    ///
-    /// ```
-    /// create_graphql_request!("<query>");
-    /// // or
-    /// create_graphql_request!("<query>", "<variables>");
-    /// // or
-    /// create_graphql_request!("<query>", "<operation-name>", "<variables>");
-    /// ```
-    ///
-    /// See [GraphQL POST request](https://graphql.org/learn/serving-over-http/#post-request).
-    macro_rules! create_graphql_request {
-        ($query:expr) => {
-            format!(
-                "{{ \
-                 \"query\": \"{}\" \
-                 }}",
-                $query.to_string().replace('"', "\\\"")
-            )
-        };
-        ($query:expr, $vars:expr) => {
-            format!(
+/// Merges the results of checking (e.g. authorizing, validating and building) each item of a batch
+/// with the results of writing the items that passed the checks.
+fn merge_batch_results(
+    checked: Vec<database::Result<()>>,
+    written: Vec<database::Result<Exercise>>,
+) -> Vec<database::Result<Exercise>> {
+    let mut written = written.into_iter();
+    checked
+        .into_iter()
+        .map(|checked| {
+            checked.and_then(|_| {
+                written.next().unwrap_or_else(|| {
+                    Err(database::Error::ServerError(Some(String::from(
+                        "Missing result of batch item",
+                    ))))
+                })
+            })
+        })
+        .collect()
+}
+
+/// Columns by which exercises can be sorted.
+#[derive(juniper::GraphQLEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ExerciseSortField {
+    Topic,
+    Title,
+    /// Number of characters in the body.
+    BodyLength,
+    CreatedOn,
+    ModifiedOn,
+    /// Index of the exercise within its series.
+    PartIndex,
+    Difficulty,
+}
+
+/// Direction in which exercises are sorted.
+#[derive(juniper::GraphQLEnum, Clone, Copy, Debug, PartialEq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+/// Which exercises are listed with regard to the trash.
+#[derive(juniper::GraphQLEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Trashed {
+    /// Only exercises that are not in the trash.
+    Exclude,
+    /// Exercises whether they are in the trash or not.
+    Include,
+    /// Only exercises that are in the trash.
+    Only,
+}
+
+/// Sort order for listing exercises via the API.
+///
+/// This is the client-facing type which is converted into a `query::ExerciseSort`.
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Sort order of a list of exercises.")]
+pub struct ExerciseSort {
+    /// Column to sort by.
+    pub field: ExerciseSortField,
+
+    /// Sort direction (ascending by default).
+    pub direction: Option<SortDirection>,
+}
+
+impl ExerciseSort {
+    /// Converts a `graphql::ExerciseSort` to a `query::ExerciseSort`.
+    pub fn to_exercise_sort_model(&self) -> query::ExerciseSort {
+        let field = match self.field {
+            ExerciseSortField::Topic => query::ExerciseSortField::Topic,
+            ExerciseSortField::Title => query::ExerciseSortField::Title,
+            ExerciseSortField::BodyLength => query::ExerciseSortField::BodyLength,
+            ExerciseSortField::CreatedOn => query::ExerciseSortField::CreatedOn,
+            ExerciseSortField::ModifiedOn => query::ExerciseSortField::ModifiedOn,
+            ExerciseSortField::PartIndex => query::ExerciseSortField::PartIndex,
+            ExerciseSortField::Difficulty => query::ExerciseSortField::Difficulty,
+        };
+        let direction = match self.direction {
+            Some(SortDirection::Asc) | None => query::SortDirection::Ascending,
+            Some(SortDirection::Desc) => query::SortDirection::Descending,
+        };
+        query::ExerciseSort { field, direction }
+    }
+}
+
+/// Filter for listing exercises via the API.
+///
+/// This is the client-facing type which is converted into a `query::ExerciseFilter`.
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Filter of a list of exercises. All given criteria must match.")]
+pub struct ExerciseFilter {
+    /// Free-text topic the exercise must have.
+    pub topic: Option<String>,
+
+    /// Id of a topic the exercise must be linked to.
+    pub topic_id: Option<String>,
+
+    /// Tags of which the exercise must have at least one (ignored if empty).
+    pub tags_any: Option<Vec<String>>,
+
+    /// Tags which the exercise must all have.
+    pub tags_all: Option<Vec<String>>,
+
+    /// Substring the title must contain (case-insensitively).
+    pub title_contains: Option<String>,
+
+    /// Minimum number of characters in the body.
+    pub min_body_length: Option<i32>,
+
+    /// Maximum number of characters in the body.
+    pub max_body_length: Option<i32>,
+
+    /// Earliest date and time of creation.
+    pub created_after: Option<NaiveDateTime>,
+
+    /// Latest date and time of creation.
+    pub created_before: Option<NaiveDateTime>,
+
+    /// Earliest date and time of the last modification.
+    pub modified_after: Option<NaiveDateTime>,
+
+    /// Latest date and time of the last modification.
+    pub modified_before: Option<NaiveDateTime>,
+
+    /// Id of the series the exercise must be a part of.
+    pub series_id: Option<String>,
+
+    /// Minimum difficulty, from 0 to 100.
+    pub min_difficulty: Option<i32>,
+
+    /// Maximum difficulty, from 0 to 100.
+    pub max_difficulty: Option<i32>,
+
+    /// Whether trashed exercises are listed (`EXCLUDE` by default). Only moderators may list
+    /// trashed exercises.
+    pub trashed: Option<Trashed>,
+}
+
+impl ExerciseFilter {
+    /// Converts a `graphql::ExerciseFilter` to a `query::ExerciseFilter`.
+    pub fn to_exercise_filter_model(&self) -> query::ExerciseFilter {
+        query::ExerciseFilter {
+            topic: self.topic.clone(),
+            topic_id: self.topic_id.clone(),
+            tags_any: self
+                .tags_any
+                .as_ref()
+                .map(|tags| tags.iter().map(|tag| normalize_tag(tag)).collect()),
+            tags_all: self
+                .tags_all
+                .as_ref()
+                .map(|tags| tags.iter().map(|tag| normalize_tag(tag)).collect()),
+            title_contains: self.title_contains.clone(),
+            min_body_length: self.min_body_length,
+            max_body_length: self.max_body_length,
+            created_after: self.created_after,
+            created_before: self.created_before,
+            modified_after: self.modified_after,
+            modified_before: self.modified_before,
+            series_id: self.series_id.clone(),
+            part_index: None,
+            min_part_index: None,
+            max_part_index: None,
+            min_difficulty: self.min_difficulty,
+            max_difficulty: self.max_difficulty,
+            trashed: match self.trashed {
+                Some(Trashed::Exclude) | None => query::Trashed::Exclude,
+                Some(Trashed::Include) => query::Trashed::Include,
+                Some(Trashed::Only) => query::Trashed::Only,
+            },
+        }
+    }
+}
+
+/// Number of items returned by a connection when neither `first` nor `last` is given.
+const DEFAULT_PAGE_SIZE: i32 = 20;
+
+/// Maximum number of items returned by a single page of a connection.
+const MAX_PAGE_SIZE: i32 = 100;
+
+/// Prefix of the (base64-encoded) cursors of connections.
+const CURSOR_PREFIX: &str = "exercise:";
+
+/// Information about the current page of a connection.
+///
+/// See <https://facebook.github.io/relay/graphql/connections.htm>.
+#[derive(juniper::GraphQLObject, Debug, PartialEq)]
+pub struct PageInfo {
+    /// Whether more items exist after the current page (when paginating forwards).
+    pub has_next_page: bool,
+
+    /// Whether more items exist before the current page (when paginating backwards).
+    pub has_previous_page: bool,
+
+    /// Cursor of the first item in the current page.
+    pub start_cursor: Option<String>,
+
+    /// Cursor of the last item in the current page.
+    pub end_cursor: Option<String>,
+}
+
+/// An exercise in a connection, along with its cursor.
+#[derive(juniper::GraphQLObject)]
+#[graphql(Context = Context, Scalar = juniper::DefaultScalarValue)]
+pub struct ExerciseEdge {
+    /// Opaque cursor identifying the position of the exercise in the connection.
+    pub cursor: String,
+
+    /// The exercise.
+    pub node: Exercise,
+}
+
+/// A page of exercises.
+#[derive(juniper::GraphQLObject)]
+#[graphql(Context = Context, Scalar = juniper::DefaultScalarValue)]
+pub struct ExerciseConnection {
+    /// Exercises in the current page.
+    pub edges: Vec<ExerciseEdge>,
+
+    /// Information about the current page.
+    pub page_info: PageInfo,
+
+    /// Total number of exercises in the connection.
+    pub total_count: i32,
+}
+
+/// A typing attempt in a connection, along with its cursor.
+#[derive(juniper::GraphQLObject)]
+pub struct TypingAttemptEdge {
+    /// Opaque cursor identifying the position of the attempt in the connection.
+    pub cursor: String,
+
+    /// The typing attempt.
+    pub node: TypingAttempt,
+}
+
+/// A page of typing attempts.
+#[derive(juniper::GraphQLObject)]
+pub struct TypingAttemptConnection {
+    /// Typing attempts in the current page.
+    pub edges: Vec<TypingAttemptEdge>,
+
+    /// Information about the current page.
+    pub page_info: PageInfo,
+
+    /// Total number of typing attempts in the connection.
+    pub total_count: i32,
+}
+
+/// An exercise matching a search, along with its relevance.
+#[derive(juniper::GraphQLObject)]
+#[graphql(Context = Context, Scalar = juniper::DefaultScalarValue)]
+pub struct ExerciseSearchResult {
+    /// The matching exercise.
+    pub exercise: Exercise,
+
+    /// Relevance of the exercise (higher is more relevant).
+    pub rank: f64,
+
+    /// Excerpt of the body with the matching terms highlighted between `<b>` and `</b>`.
+    pub snippet: String,
+}
+
+/// The word-level differences between two revisions of exercises.
+#[derive(juniper::GraphQLObject)]
+pub struct RevisionDiff {
+    /// The old revision.
+    pub from: ExerciseRevision,
+
+    /// The new revision.
+    pub to: ExerciseRevision,
+
+    /// Chunks turning the title of the old revision into that of the new revision.
+    pub title: Vec<diff::Chunk>,
+
+    /// Chunks turning the body of the old revision into that of the new revision.
+    pub body: Vec<diff::Chunk>,
+}
+
+impl RevisionDiff {
+    /// Diffs the revisions `from` and `to`.
+    fn new(from: ExerciseRevision, to: ExerciseRevision) -> RevisionDiff {
+        let title = diff::diff_words(&from.title, &to.title);
+        let body = diff::diff_words(&from.body, &to.body);
+        RevisionDiff {
+            from,
+            to,
+            title,
+            body,
+        }
+    }
+}
+
+/// Relay-style pagination arguments, as passed to a connection field.
+#[derive(Debug, Default, PartialEq)]
+struct PageArgs {
+    first: Option<i32>,
+    after: Option<String>,
+    last: Option<i32>,
+    before: Option<String>,
+}
+
+/// A window `[start, end)` into a collection of `count` items.
+#[derive(Debug, PartialEq)]
+struct PageWindow {
+    start: i64,
+    end: i64,
+    has_previous_page: bool,
+    has_next_page: bool,
+}
+
+impl PageArgs {
+    /// Resolves the pagination arguments against a collection of `count` items.
+    ///
+    /// Follows the algorithm of the [Relay cursor connections specification], except that a page
+    /// never contains more than `MAX_PAGE_SIZE` items.
+    ///
+    /// [Relay cursor connections specification]: https://facebook.github.io/relay/graphql/connections.htm#sec-Pagination-algorithm
+    fn window(&self, count: i64) -> database::Result<PageWindow> {
+        let after = self.after.as_ref().map(|c| decode_cursor(c)).transpose()?;
+        let before = self.before.as_ref().map(|c| decode_cursor(c)).transpose()?;
+        let first = check_page_size("first", self.first)?;
+        let last = check_page_size("last", self.last)?;
+        let first = match (first, last) {
+            (None, None) => Some(i64::from(DEFAULT_PAGE_SIZE)),
+            (first, _) => first,
+        };
+
+        // NOTE: Cursors are decoded from client input, so offsets may be as large as `i64::MAX`.
+        let lower = after.map_or(0, |after| after.saturating_add(1).min(count));
+        let upper = before.map_or(count, |before| before.min(count)).max(lower);
+
+        let mut start = lower;
+        let mut end = upper;
+        if let Some(first) = first {
+            end = end.min(start.saturating_add(first));
+        }
+        if let Some(last) = last {
+            start = start.max(end - last);
+        }
+
+        Ok(PageWindow {
+            start,
+            end,
+            has_previous_page: last.is_some() && start > lower,
+            has_next_page: first.is_some() && end < upper,
+        })
+    }
+}
+
+impl PageWindow {
+    /// Returns the `PageInfo` of the page of `len` items starting at the window's start.
+    fn page_info(&self, len: usize) -> PageInfo {
+        let cursors = (self.start..).take(len).map(encode_cursor);
+        PageInfo {
+            has_next_page: self.has_next_page,
+            has_previous_page: self.has_previous_page,
+            start_cursor: cursors.clone().next(),
+            end_cursor: cursors.last(),
+        }
+    }
+}
+
+/// Validates a `first`/`last` pagination argument.
+fn check_page_size(name: &str, size: Option<i32>) -> database::Result<Option<i64>> {
+    match size {
+        Some(size) if !(0..=MAX_PAGE_SIZE).contains(&size) => Err(database::Error::QueryError(
+            format!("`{}` must be between 0 and {}", name, MAX_PAGE_SIZE),
+        )),
+        size => Ok(size.map(i64::from)),
+    }
+}
+
+/// Encodes the offset of an item into an opaque connection cursor.
+fn encode_cursor(offset: i64) -> String {
+    base64::encode(&format!("{}{}", CURSOR_PREFIX, offset))
+}
+
+/// Decodes a connection cursor created by `encode_cursor` back into an offset.
+fn decode_cursor(cursor: &str) -> database::Result<i64> {
+    base64::decode(cursor)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .and_then(|s| {
+            s.get(CURSOR_PREFIX.len()..)
+                .filter(|_| s.starts_with(CURSOR_PREFIX))
+                .and_then(|offset| offset.parse().ok())
+        })
+        .filter(|offset| *offset >= 0)
+        .ok_or_else(|| database::Error::QueryError(format!("Invalid cursor: {:?}", cursor)))
+}
+
+/// Defines shared state for GraphQL resolvers (e.g. database connections).
+///
+/// A `Context` is created once at startup and cloned for every request; clones share the same
+/// connection pool and configuration. Each request's clone is authenticated as the caller of the
+/// request (see `context_filter`).
+#[derive(Clone)]
+pub struct Context {
+    // Connection pool to a Postgres, MySQL or SQLite database, depending on the database URL.
+    //
+    // NOTE: The database schema should be up to date, i.e. `pool.pending_migrations()` should be
+    // empty. Otherwise, run the migrations with `pool.run_pending_migrations()`.
+    pool: Pool,
+
+    // Configuration the context was created with.
+    config: Arc<Config>,
+
+    // Signer of access and refresh tokens.
+    tokens: Arc<TokenSigner>,
+
+    // Caller of the current request, if authenticated, or the error authenticating it.
+    caller: Result<Option<Caller>, auth::Error>,
+}
+
+impl Context {
+    /// Creates a new `Context`, opening a connection pool to the configured database.
+    ///
+    /// The context is not authenticated as any caller.
+    pub fn new(config: Config) -> database::Result<Context> {
+        let pool = Pool::new(&config)?;
+        let secret = match &config.token_secret {
+            Some(secret) => secret.clone(),
+            None => {
+                log::warn!("AUTH_TOKEN_SECRET is not set; tokens are invalidated on restart");
+                format!("{}{}", uuid::Uuid::new_v4(), uuid::Uuid::new_v4())
+            }
+        };
+        let tokens = TokenSigner::new(
+            secret.as_bytes(),
+            config.access_token_lifetime,
+            config.refresh_token_lifetime,
+        );
+        Ok(Context {
+            pool,
+            config: Arc::new(config),
+            tokens: Arc::new(tokens),
+            caller: Ok(None),
+        })
+    }
+
+    /// Returns a clone of the context authenticated by the value of an `Authorization` header.
+    ///
+    /// Requests without a header are anonymous. Requests with an invalid or expired token are
+    /// not rejected here, but every resolver asking for the caller fails.
+    pub fn authenticate(&self, authorization: Option<&str>) -> Context {
+        let caller = authorization
+            .map(|authorization| {
+                auth::bearer_token(authorization).and_then(|token| self.tokens.authenticate(token))
+            })
+            .transpose();
+        Context {
+            caller,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the authenticated caller of the request, or `None` if the request is anonymous.
+    ///
+    /// Fails with `Error::Unauthorized` if the request carried an invalid or expired token.
+    pub fn caller(&self) -> database::Result<Option<&Caller>> {
+        self.caller
+            .as_ref()
+            .map(Option::as_ref)
+            .map_err(|e| e.clone().into_database_error())
+    }
+
+    /// Returns the authenticated caller of the request.
+    ///
+    /// Fails with `Error::Unauthorized` if the request is anonymous or carried an invalid or
+    /// expired token.
+    pub fn require_caller(&self) -> database::Result<&Caller> {
+        self.caller()?
+            .ok_or_else(|| database::Error::Unauthorized(String::from("Authentication required")))
+    }
+
+    /// Returns the configuration the context was created with.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Returns the connection pool to the database.
+    pub fn pool(&self) -> &Pool {
+        &self.pool
+    }
+
+    /// Returns the SQL dialect of the database.
+    pub fn dialect(&self) -> Dialect {
+        self.pool.dialect()
+    }
+
+    /// Checks out a connection from the connection pool.
+    pub fn conn(&self) -> database::Result<Box<dyn Dao>> {
+        self.pool.get()
+    }
+
+    /// Purges the exercises that have been in the trash for longer than `trash_retention`,
+    /// returning the number of purged exercises.
+    pub fn purge_trash(&self) -> database::Result<usize> {
+        let retention = match self.config.trash_retention {
+            Some(retention) => retention,
+            None => return Ok(0),
+        };
+        let retention = chrono::Duration::from_std(retention)
+            .map_err(|e| database::Error::ServerError(Some(e.to_string())))?;
+        let deleted_before = chrono::Utc::now().naive_utc() - retention;
+        let conn = self.conn()?;
+        conn.purge_trashed(deleted_before)
+    }
+
+    /// Normalizes the body of an exercise to be created as configured by `text_normalization`.
+    pub fn normalize_new_exercise(&self, new_exercise: &mut NewExercise) {
+        let changes = new_exercise.normalize_body(&self.config.text_normalization);
+        log_normalization(&new_exercise.title, &changes);
+    }
+
+    /// Normalizes the body of an exercise update as configured by `text_normalization`.
+    pub fn normalize_updated_exercise(&self, updated_exercise: &mut UpdatedExercise) {
+        let changes = updated_exercise.normalize_body(&self.config.text_normalization);
+        log_normalization(&updated_exercise.id, &changes);
+    }
+}
+
+/// Logs the changes made by normalizing the body of `exercise` (a title or id).
+fn log_normalization(exercise: &str, changes: &[text::Change]) {
+    for change in changes {
+        log::debug!(
+            "Normalized {} ({}) in body of exercise {:?}",
+            change.step,
+            change.count,
+            exercise
+        );
+    }
+}
+
+impl juniper::Context for Context {}
+
+/// Creates a `warp::Filter` extracting a clone of `context` authenticated as the caller of the
+/// request, i.e. by its `Authorization: Bearer <access token>` header.
+pub fn context_filter(context: Context) -> BoxedFilter<(Context,)> {
+    warp::header::optional::<String>("authorization")
+        .map(move |authorization: Option<String>| context.authenticate(authorization.as_deref()))
+        .boxed()
+}
+
+/// Defines available non-side-effecting queries on a GraphQL endpoint.
+pub struct Query;
+
+#[juniper::object(Context = Context)]
+impl Query {
+    fn apiVersion() -> &'static str {
+        "1.0"
+    }
+
+    /// Normalizes `text` as the bodies of created and updated exercises are normalized, reporting
+    /// what would be changed.
+    fn normalizeText(context: &Context, text: String) -> text::Normalized {
+        text::normalize(&text, &context.config().text_normalization)
+    }
+
+    /// Returns the user the request is authenticated as.
+    fn me(context: &Context) -> Result<User, database::Error> {
+        let caller = context.require_caller()?;
+        let conn = context.conn()?;
+        let user = conn.find_by_id(caller.user_id.as_str())?;
+        Ok(user)
+    }
+
+    fn exercise(context: &Context, id: String) -> Result<Exercise, database::Error> {
+        let conn = context.conn()?;
+        let exercise = conn.find_by_id(id.as_str())?;
+        Ok(exercise)
+    }
+
+    /// Returns the word-level differences between two exercise revisions, from the revision with
+    /// id `fromId` to the revision with id `toId`.
+    fn exerciseRevisionDiff(
+        context: &Context,
+        from_id: String,
+        to_id: String,
+    ) -> Result<RevisionDiff, database::Error> {
+        let conn = context.conn()?;
+        let from = conn.find_by_id(from_id.as_str())?;
+        let to = conn.find_by_id(to_id.as_str())?;
+        Ok(RevisionDiff::new(from, to))
+    }
+
+    /// Returns the topic with the given slug.
+    fn topic(context: &Context, slug: String) -> Result<Topic, database::Error> {
+        let conn = context.conn()?;
+        let topic = conn.find_by_name(slug.as_str())?;
+        Ok(topic)
+    }
+
+    /// Returns the tree of topics, starting at the top-level topics, along with the number of
+    /// exercises in each topic. Topics are sorted by name.
+    fn topicTree(context: &Context) -> Result<Vec<TopicNode>, database::Error> {
+        let conn = context.conn()?;
+        let topics = conn.count_exercises()?;
+        Ok(models::topic_tree(topics))
+    }
+
+    /// Lists exercises as a Relay-style connection.
+    ///
+    /// Exercises are sorted from oldest to newest unless `sort` is given. Returns the first 20
+    /// exercises if neither `first` nor `last` is given. Cursors are only valid for the `filter`
+    /// and `sort` they were returned for.
+    fn exercises(
+        context: &Context,
+        filter: Option<ExerciseFilter>,
+        sort: Option<ExerciseSort>,
+        first: Option<i32>,
+        after: Option<String>,
+        last: Option<i32>,
+        before: Option<String>,
+    ) -> Result<ExerciseConnection, database::Error> {
+        let args = PageArgs {
+            first,
+            after,
+            last,
+            before,
+        };
+        let query = query::ExerciseQuery {
+            filter: filter
+                .map(|filter| filter.to_exercise_filter_model())
+                .unwrap_or_default(),
+            sort: sort
+                .map(|sort| sort.to_exercise_sort_model())
+                .unwrap_or_default(),
+        };
+        if query.filter.trashed != query::Trashed::Exclude {
+            policy::authorize_trash(context.caller()?)?;
+        }
+        let conn = context.conn()?;
+        let count = conn.count(&query)?;
+        let window = args.window(count)?;
+        let exercises = if window.start < window.end {
+            conn.list(&query, window.start, window.end - window.start)?
+        } else {
+            Vec::new()
+        };
+
+        let page_info = window.page_info(exercises.len());
+        let edges: Vec<ExerciseEdge> = exercises
+            .into_iter()
+            .zip(window.start..)
+            .map(|(node, offset)| ExerciseEdge {
+                cursor: encode_cursor(offset),
+                node,
+            })
+            .collect();
+        Ok(ExerciseConnection {
+            edges,
+            page_info,
+            total_count: count as i32,
+        })
+    }
+
+    fn attempt(context: &Context, id: String) -> Result<TypingAttempt, database::Error> {
+        let conn = context.conn()?;
+        let attempt = conn.find_by_id(id.as_str())?;
+        Ok(attempt)
+    }
+
+    /// Lists the typing attempts at an exercise, from newest to oldest, as a Relay-style
+    /// connection.
+    ///
+    /// Returns the first 20 attempts if neither `first` nor `last` is given.
+    fn exerciseAttempts(
+        context: &Context,
+        exercise_id: String,
+        first: Option<i32>,
+        after: Option<String>,
+        last: Option<i32>,
+        before: Option<String>,
+    ) -> Result<TypingAttemptConnection, database::Error> {
+        let args = PageArgs {
+            first,
+            after,
+            last,
+            before,
+        };
+        let query = query::AttemptQuery::for_exercise(&exercise_id);
+        let conn = context.conn()?;
+        let count = conn.count(&query)?;
+        let window = args.window(count)?;
+        let attempts = if window.start < window.end {
+            conn.list(&query, window.start, window.end - window.start)?
+        } else {
+            Vec::new()
+        };
+
+        let page_info = window.page_info(attempts.len());
+        let edges: Vec<TypingAttemptEdge> = attempts
+            .into_iter()
+            .zip(window.start..)
+            .map(|(node, offset)| TypingAttemptEdge {
+                cursor: encode_cursor(offset),
+                node,
+            })
+            .collect();
+        Ok(TypingAttemptConnection {
+            edges,
+            page_info,
+            total_count: count as i32,
+        })
+    }
+
+    /// Searches exercise titles and bodies, returning the `first` (20 by default) most relevant
+    /// exercises.
+    fn searchExercises(
+        context: &Context,
+        query: String,
+        first: Option<i32>,
+    ) -> Result<Vec<ExerciseSearchResult>, database::Error> {
+        let first = check_page_size("first", first)?.unwrap_or(i64::from(DEFAULT_PAGE_SIZE));
+        let conn = context.conn()?;
+        let hits = conn.search(&query, first)?;
+        Ok(hits
+            .into_iter()
+            .map(|hit| ExerciseSearchResult {
+                exercise: hit.item,
+                rank: hit.rank,
+                snippet: hit.snippet,
+            })
+            .collect())
+    }
+}
+
+/// Defines available side-effecting queries on a GraphQL endpoint.
+pub struct Mutation;
+
+#[juniper::object(Context = Context)]
+impl Mutation {
+    /// Registers a new user, returning tokens authenticating as the user.
+    ///
+    /// Fails with a `conflict` error if the username is already taken.
+    fn register(
+        context: &Context,
+        credentials: Credentials,
+    ) -> Result<AuthPayload, database::Error> {
+        let conn = context.conn()?;
+        let new_user = credentials.to_new_user_model()?;
+        let user = conn.create(&new_user)?;
+        AuthPayload::issue(context, user)
+    }
+
+    /// Logs a user in, returning tokens authenticating as the user.
+    fn login(context: &Context, credentials: Credentials) -> Result<AuthPayload, database::Error> {
+        let conn = context.conn()?;
+        let found: database::Result<User> = conn.find_by_name(credentials.username.as_str());
+        let user = match found {
+            // NOTE: Unknown usernames take as long to reject as wrong passwords, so that login
+            // attempts do not reveal which usernames exist.
+            Err(database::Error::NotFound) => {
+                auth::verify_dummy_password(&credentials.password);
+                Err(auth::Error::InvalidCredentials)
+            }
+            Err(e) => return Err(e),
+            Ok(user) => {
+                if auth::verify_password(&user.password_hash, &credentials.password) {
+                    Ok(user)
+                } else {
+                    Err(auth::Error::InvalidCredentials)
+                }
+            }
+        }
+        .map_err(IntoDatabaseError::into_database_error)?;
+        AuthPayload::issue(context, user)
+    }
+
+    /// Exchanges a refresh token for new tokens.
+    fn refreshTokens(
+        context: &Context,
+        refresh_token: String,
+    ) -> Result<AuthPayload, database::Error> {
+        let claims = context
+            .tokens
+            .verify(&refresh_token, TokenKind::Refresh)
+            .map_err(IntoDatabaseError::into_database_error)?;
+        let conn = context.conn()?;
+        let user: User = match conn.find_by_id(claims.sub.as_str()) {
+            Err(database::Error::NotFound) => {
+                return Err(auth::Error::InvalidToken.into_database_error())
+            }
+            user => user?,
+        };
+        AuthPayload::issue(context, user)
+    }
+
+    /// Creates an exercise authored by the caller.
+    ///
+    /// Fails with a `validation_error` listing every invalid field if the title, (normalized) body
+    /// or topic is invalid.
+    fn createExercise(
+        context: &Context,
+        new_exercise: NewExercise,
+    ) -> Result<Exercise, database::Error> {
+        let caller = context.caller()?;
+        policy::authorize_exercise(caller, ExerciseAction::Create)?;
+        let conn = context.conn()?;
+        let author_id = caller.map(|caller| caller.user_id.as_str());
+        let mut new_exercise = new_exercise;
+        context.normalize_new_exercise(&mut new_exercise);
+        new_exercise.validate(&context.config().exercise_limits)?;
+        let new_exercise = new_exercise.to_new_exercise_model(author_id)?;
+        let exercise = conn.create(&new_exercise)?;
+        Ok(exercise)
+    }
+
+    /// Splits a long text into a series of exercises authored by the caller, in a single
+    /// transaction.
+    ///
+    /// The parts are returned in order and linked via `next` and `previous`.
+    fn createExerciseSeries(
+        context: &Context,
+        input: NewExerciseSeries,
+    ) -> Result<Vec<Exercise>, database::Error> {
+        let caller = context.caller()?;
+        policy::authorize_exercise(caller, ExerciseAction::Create)?;
+        let author_id = caller.map(|caller| caller.user_id.as_str());
+        let series_id = Uuid::new().to_string();
+        let new_exercises = input.to_new_exercise_models(
+            &series_id,
+            author_id,
+            &context.config().text_normalization,
+            &context.config().exercise_limits,
+        )?;
+        check_batch_size(new_exercises.len())?;
+        let conn = context.conn()?;
+        let exercises = conn.create_all(&new_exercises)?;
+        Ok(exercises)
+    }
+
+    /// Updates an exercise (authored by the caller, unless the caller is a moderator).
+    ///
+    /// Fails with a `validation_error` listing every invalid field if an updated field is invalid.
+    fn updateExercise(
+        context: &Context,
+        updated_exercise: UpdatedExercise,
+    ) -> Result<Exercise, database::Error> {
+        let caller = context.caller()?;
+        let conn = context.conn()?;
+        let exercise: Exercise = conn.find_by_id(updated_exercise.id.as_str())?;
+        policy::authorize_exercise(caller, ExerciseAction::Update(&exercise))?;
+        let mut updated_exercise = updated_exercise;
+        context.normalize_updated_exercise(&mut updated_exercise);
+        updated_exercise.validate(&context.config().exercise_limits)?;
+        let editor_id = caller.map(|caller| caller.user_id.as_str());
+        let updated_exercise = updated_exercise.to_updated_exercise_model(editor_id)?;
+        let exercise = conn.update(&updated_exercise)?;
+        Ok(exercise)
+    }
+
+    /// Restores the content (title, body and topic) of an exercise (authored by the caller,
+    /// unless the caller is a moderator) to that of one of its revisions, as a new version.
+    ///
+    /// If `expectedVersion` is set and the exercise has since been updated, the restore fails
+    /// with a `conflict` error.
+    fn restoreExerciseRevision(
+        context: &Context,
+        id: String,
+        expected_version: Option<i32>,
+    ) -> Result<Exercise, database::Error> {
+        let caller = context.caller()?;
+        let conn = context.conn()?;
+        let revision: ExerciseRevision = conn.find_by_id(id.as_str())?;
+        let exercise: Exercise = conn.find_by_id(revision.exercise_id.as_str())?;
+        policy::authorize_exercise(caller, ExerciseAction::Update(&exercise))?;
+        let mut update = UpdatedExerciseBuilder::new(&exercise.id);
+        update
+            .title(&revision.title)
+            .body(&revision.body)
+            .topic(revision.topic.as_deref())
+            .editor_id(caller.map(|caller| caller.user_id.as_str()));
+        if let Some(version) = expected_version {
+            update.expected_version(version);
+        }
+        let exercise = conn.update(&update.build()?)?;
+        Ok(exercise)
+    }
+
+    /// Moves an exercise (authored by the caller, unless the caller is a moderator) to the trash.
+    ///
+    /// Trashed exercises are hidden until they are restored with `restoreExercise`, and purged
+    /// after a while.
+    fn deleteExerciseById(context: &Context, id: String) -> Result<Exercise, database::Error> {
+        let caller = context.caller()?;
+        let conn = context.conn()?;
+        let exercise: Exercise = conn.find_by_id(id.as_str())?;
+        policy::authorize_exercise(caller, ExerciseAction::Delete(&exercise))?;
+        let exercise = conn.delete_by_id(&id)?;
+        Ok(exercise)
+    }
+
+    /// Moves an exercise (authored by the caller, unless the caller is a moderator) out of the
+    /// trash.
+    fn restoreExercise(context: &Context, id: String) -> Result<Exercise, database::Error> {
+        let caller = context.caller()?;
+        let conn = context.conn()?;
+        let exercise: Exercise = conn.find_trashed(id.as_str())?;
+        policy::authorize_exercise(caller, ExerciseAction::Delete(&exercise))?;
+        let exercise = conn.restore(&id)?;
+        Ok(exercise)
+    }
+
+    /// Permanently deletes an exercise, whether it is in the trash or not, along with its typing
+    /// attempts and revisions (if the caller is a moderator).
+    fn purgeExercise(context: &Context, id: String) -> Result<Exercise, database::Error> {
+        let caller = context.caller()?;
+        let conn = context.conn()?;
+        let exercise: Exercise = match conn.find_by_id(id.as_str()) {
+            Err(database::Error::NotFound) => conn.find_trashed(id.as_str())?,
+            exercise => exercise?,
+        };
+        policy::authorize_exercise(caller, ExerciseAction::Purge(&exercise))?;
+        let exercise = conn.purge(&id)?;
+        Ok(exercise)
+    }
+
+    /// Creates a topic (if the caller is a moderator).
+    ///
+    /// Fails with a `conflict` error if the slug is already taken.
+    fn createTopic(context: &Context, new_topic: NewTopic) -> Result<Topic, database::Error> {
+        policy::authorize_topic(context.caller()?)?;
+        let conn = context.conn()?;
+        let new_topic = new_topic.to_new_topic_model()?;
+        let topic = conn.create(&new_topic)?;
+        Ok(topic)
+    }
+
+    /// Adds an exercise (authored by the caller, unless the caller is a moderator) to topics,
+    /// returning every topic of the exercise.
+    fn addExerciseTopics(
+        context: &Context,
+        id: String,
+        topic_ids: Vec<String>,
+    ) -> Result<Vec<Topic>, database::Error> {
+        let caller = context.caller()?;
+        let conn = context.conn()?;
+        let exercise: Exercise = conn.find_by_id(id.as_str())?;
+        policy::authorize_exercise(caller, ExerciseAction::Update(&exercise))?;
+        let topic_ids: Vec<&str> = topic_ids.iter().map(String::as_str).collect();
+        conn.link(&id, &topic_ids)
+    }
+
+    /// Removes an exercise (authored by the caller, unless the caller is a moderator) from
+    /// topics, returning every remaining topic of the exercise.
+    fn removeExerciseTopics(
+        context: &Context,
+        id: String,
+        topic_ids: Vec<String>,
+    ) -> Result<Vec<Topic>, database::Error> {
+        let caller = context.caller()?;
+        let conn = context.conn()?;
+        let exercise: Exercise = conn.find_by_id(id.as_str())?;
+        policy::authorize_exercise(caller, ExerciseAction::Update(&exercise))?;
+        let topic_ids: Vec<&str> = topic_ids.iter().map(String::as_str).collect();
+        conn.unlink(&id, &topic_ids)
+    }
+
+    /// Tags an exercise (authored by the caller, unless the caller is a moderator), returning
+    /// every tag of the exercise.
+    ///
+    /// Tags are normalized into lowercase words joined by hyphens (e.g. "Numbers heavy" into
+    /// "numbers-heavy"), and created when first used.
+    fn addTags(
+        context: &Context,
+        id: String,
+        tags: Vec<String>,
+    ) -> Result<Vec<Tag>, database::Error> {
+        let caller = context.caller()?;
+        let tags = normalize_tags(&tags)?;
+        let conn = context.conn()?;
+        let exercise: Exercise = conn.find_by_id(id.as_str())?;
+        policy::authorize_exercise(caller, ExerciseAction::Update(&exercise))?;
+        let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+        conn.link(&id, &tags)
+    }
+
+    /// Removes tags from an exercise (authored by the caller, unless the caller is a moderator),
+    /// returning every remaining tag of the exercise.
+    fn removeTags(
+        context: &Context,
+        id: String,
+        tags: Vec<String>,
+    ) -> Result<Vec<Tag>, database::Error> {
+        let caller = context.caller()?;
+        let conn = context.conn()?;
+        let exercise: Exercise = conn.find_by_id(id.as_str())?;
+        policy::authorize_exercise(caller, ExerciseAction::Update(&exercise))?;
+        let tags: Vec<String> = tags.iter().map(|tag| normalize_tag(tag)).collect();
+        let tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+        conn.unlink(&id, &tags)
+    }
+
+    /// Creates exercises authored by the caller, in a single transaction.
+    ///
+    /// Items that fail are reported in `errors` and do not affect the other items.
+    fn createExercises(
+        context: &Context,
+        input: Vec<NewExercise>,
+    ) -> Result<ExerciseBatch, database::Error> {
+        check_batch_size(input.len())?;
+        let caller = context.require_caller()?;
+        policy::authorize_exercise(Some(caller), ExerciseAction::Create)?;
+        let conn = context.conn()?;
+        let author_id = Some(caller.user_id.as_str());
+        let mut input = input;
+        for new_exercise in &mut input {
+            context.normalize_new_exercise(new_exercise);
+        }
+        let limits = &context.config().exercise_limits;
+        let mut new_exercises: Vec<models::NewExercise> = Vec::new();
+        let checked: Vec<database::Result<()>> = input
+            .iter()
+            .map(|new_exercise| {
+                new_exercise.validate(limits)?;
+                new_exercises.push(new_exercise.to_new_exercise_model(author_id)?);
+                Ok(())
+            })
+            .collect();
+        let results = conn.create_batch(&new_exercises)?;
+        Ok(ExerciseBatch::new(merge_batch_results(checked, results)))
+    }
+
+    /// Updates exercises (authored by the caller, unless the caller is a moderator), in a single
+    /// transaction.
+    ///
+    /// Items that fail are reported in `errors` and do not affect the other items.
+    fn updateExercises(
+        context: &Context,
+        input: Vec<UpdatedExercise>,
+    ) -> Result<ExerciseBatch, database::Error> {
+        check_batch_size(input.len())?;
+        let caller = context.require_caller()?;
+        let conn = context.conn()?;
+        let authorized: Vec<database::Result<()>> = input
+            .iter()
+            .map(|updated_exercise| {
+                let exercise: Exercise = conn.find_by_id(updated_exercise.id.as_str())?;
+                policy::authorize_exercise(Some(caller), ExerciseAction::Update(&exercise))
+            })
+            .collect();
+        let mut input = input;
+        for updated_exercise in &mut input {
+            context.normalize_updated_exercise(updated_exercise);
+        }
+        let limits = &context.config().exercise_limits;
+        let mut updated_exercises: Vec<models::UpdatedExercise> = Vec::new();
+        let checked: Vec<database::Result<()>> = input
+            .iter()
+            .zip(authorized)
+            .map(|(updated_exercise, authorized)| {
+                authorized?;
+                updated_exercise.validate(limits)?;
+                updated_exercises
+                    .push(updated_exercise.to_updated_exercise_model(Some(&caller.user_id))?);
+                Ok(())
+            })
+            .collect();
+        let results = conn.update_batch(&updated_exercises)?;
+        Ok(ExerciseBatch::new(merge_batch_results(checked, results)))
+    }
+
+    /// Deletes exercises (authored by the caller, unless the caller is a moderator), in a single
+    /// transaction.
+    ///
+    /// Items that fail are reported in `errors` and do not affect the other items.
+    fn deleteExercisesByIds(
+        context: &Context,
+        ids: Vec<String>,
+    ) -> Result<ExerciseBatch, database::Error> {
+        check_batch_size(ids.len())?;
+        let caller = context.require_caller()?;
+        let conn = context.conn()?;
+        let authorized: Vec<database::Result<()>> = ids
+            .iter()
+            .map(|id| {
+                let exercise: Exercise = conn.find_by_id(id.as_str())?;
+                policy::authorize_exercise(Some(caller), ExerciseAction::Delete(&exercise))
+            })
+            .collect();
+        let ids: Vec<&str> = ids
+            .iter()
+            .zip(&authorized)
+            .filter(|(_, authorized)| authorized.is_ok())
+            .map(|(id, _)| id.as_str())
+            .collect();
+        let results = conn.delete_by_ids(&ids)?;
+        Ok(ExerciseBatch::new(merge_batch_results(authorized, results)))
+    }
+
+    /// Records an attempt at typing an exercise, scoring its keystroke log against the exercise.
+    fn submitAttempt(
+        context: &Context,
+        attempt: NewTypingAttempt,
+    ) -> Result<TypingAttempt, database::Error> {
+        let conn = context.conn()?;
+        let exercise: Exercise = conn.find_by_id(attempt.exercise_id.as_str())?;
+        let score = attempt.score(&exercise)?;
+        let new_attempt = attempt.to_new_typing_attempt_model(&score)?;
+        let attempt = conn.create(&new_attempt)?;
+        Ok(attempt)
+    }
+}
+
+/// Type alias for `juniper::RootNode<...>` (needed when implementing a GraphQL endpoint).
+pub type Schema = juniper::RootNode<'static, Query, Mutation>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use dotenv::dotenv;
+    use http::response::Response;
+    use std::env;
+    use warp::{test, Filter, Reply};
+
+    #[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+    struct Exercise {
+        pub id: Option<String>,
+        pub title: Option<String>,
+        pub body: Option<String>,
+        pub topic: Option<String>,
+        #[serde(rename = "createdOn")]
+        pub created_on: Option<f64>,
+        #[serde(rename = "modifiedOn")]
+        pub modified_on: Option<f64>,
+    }
+
+    /// Create a new `graphql::Schema`.
+    fn schema() -> Schema {
+        Schema::new(Query, Mutation)
+    }
+
+    /// Create a new `Context` for the database at `DATABASE_URL`, running any pending migrations.
+    fn create_test_context() -> Context {
+        let config = Config::from_env().unwrap();
+        let context = Context::new(config).unwrap();
+        context.pool().run_pending_migrations().unwrap();
+        context
+    }
+
+    /// Create a new `Context` for the database at `TEST_DATABASE_URL` and a GraphQL endpoint
+    /// serving it.
+    fn test_endpoint() -> (Context, warp::filters::BoxedFilter<(impl Reply,)>) {
+        dotenv().ok();
+        let test_database_url =
+            env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+        env::set_var("DATABASE_URL", test_database_url);
+
+        let context = create_test_context();
+        let graphql_filter = create_graphql_filter(&context);
+        (context, graphql_filter)
+    }
+
+    /// Register a new user with the given role directly in the database, returning the user and
+    /// an access token authenticating as the user.
+    fn create_test_user(context: &Context, role: models::Role) -> (User, String) {
+        let username = format!("{}-{}", role, uuid::Uuid::new_v4());
+        let new_user = NewUserBuilder::new()
+            .username(&username)
+            .password_hash("not really a hash")
+            .role(role)
+            .build()
+            .unwrap();
+        let user: User = context.conn().unwrap().create(&new_user).unwrap();
+        let tokens = context.tokens.issue(&user).unwrap();
+        (user, tokens.access_token)
+    }
+
+    /// Create a new `warp::Filter` at `/graphql` for handling GraphQL requests with `context`.
+    fn create_graphql_filter(context: &Context) -> warp::filters::BoxedFilter<(impl Reply,)> {
+        let state = context_filter(context.clone());
+        let graphql_filter = juniper_warp::make_graphql_filter(schema(), state);
+        let graphql_filter = warp::path("graphql").and(graphql_filter);
+        graphql_filter.boxed()
+    }
+
+    /// Create a new `warp::test::RequestBuilder` for testing a `warp::Filter` that handles GraphQL
+    /// requests.
+    fn make_test_graphql_request(request: &str) -> test::RequestBuilder {
+        test::request()
+            .method("POST")
+            .path("/graphql")
+            .header("accept", "application/json")
+            .header("content-type", "application/json")
+            .body(request)
+    }
+
+    /// Create the JSON-encoded body of a GraphQL POST request.
+    ///
+    /// Returns a `String`.
+    ///
+    /// # Usage
+    ///
+    /// ```
+    /// create_graphql_request!("<query>");
+    /// // or
+    /// create_graphql_request!("<query>", "<variables>");
+    /// // or
+    /// create_graphql_request!("<query>", "<operation-name>", "<variables>");
+    /// ```
+    ///
+    /// See [GraphQL POST request](https://graphql.org/learn/serving-over-http/#post-request).
+    macro_rules! create_graphql_request {
+        ($query:expr) => {
+            format!(
+                "{{ \
+                 \"query\": \"{}\" \
+                 }}",
+                $query.to_string().replace('"', "\\\"")
+            )
+        };
+        ($query:expr, $vars:expr) => {
+            format!(
                 "{{ \
                  \"query\": \"{}\", \
                  \"variables\": {} \
@@ -378,11 +2173,14 @@ mod tests {
     /// Make a HTTP POST request request to the GraphQL endpoint to create a new exercise.
     fn create_new_exercise(
         graphql_filter: &warp::filters::BoxedFilter<(impl Reply + 'static,)>,
+        access_token: &str,
         title: &str,
         body: &str,
     ) -> Exercise {
         let request = create_new_exercise_graphql_request(title, body);
-        let response = make_test_graphql_request(&request).reply(graphql_filter);
+        let response = make_test_graphql_request(&request)
+            .header("authorization", format!("Bearer {}", access_token))
+            .reply(graphql_filter);
         let new_exercise = deserialize_exercise_from_response(response, "createExercise");
 
         assert_eq!(new_exercise.title, Some(String::from(title)));
@@ -408,33 +2206,149 @@ mod tests {
     /// Make a HTTP POST request request to the GraphQL endpoint to updated an exercise.
     fn update_exercise(
         graphql_filter: &warp::filters::BoxedFilter<(impl Reply + 'static,)>,
+        access_token: &str,
         updated_exercise: Exercise,
     ) -> Exercise {
         let updated_title = updated_exercise.title.clone();
         let request = create_update_exercise_graphql_request(updated_exercise);
+        let response = make_test_graphql_request(&request)
+            .header("authorization", format!("Bearer {}", access_token))
+            .reply(graphql_filter);
+        let updated_exercise = deserialize_exercise_from_response(response, "updateExercise");
+
+        let expected_exercise =
+            find_exercise_by_id(&graphql_filter, updated_exercise.id.as_ref().unwrap());
+
+        assert_eq!(expected_exercise.id, updated_exercise.id);
+        assert_eq!(updated_title, updated_exercise.title);
+        assert_eq!(expected_exercise.body, updated_exercise.body);
+        assert_eq!(expected_exercise.topic, updated_exercise.topic);
+        assert_eq!(expected_exercise.created_on, updated_exercise.created_on);
+        assert!(expected_exercise.modified_on <= updated_exercise.modified_on);
+
+        updated_exercise
+    }
+
+    /// Make a HTTP POST request request to the GraphQL endpoint to list the ids of the exercises
+    /// whose title contains `title`, sorted from the longest to the shortest body.
+    fn list_exercise_ids_by_title(
+        graphql_filter: &warp::filters::BoxedFilter<(impl Reply + 'static,)>,
+        title: &str,
+    ) -> Vec<String> {
+        let request = create_graphql_request!(
+            "query ListExercises($filter: ExerciseFilter!) {
+                exercises(filter: $filter, sort: { field: BODY_LENGTH, direction: DESC }) {
+                    edges { node { id } }
+                }
+            }"
+            .replace("\n", " "),
+            format!("{{ \"filter\": {{ \"titleContains\": \"{}\" }} }}", title)
+        );
+        let response = make_test_graphql_request(&request).reply(graphql_filter);
+        let response: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        response["data"]["exercises"]["edges"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|edge| edge["node"]["id"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    /// Make a HTTP POST request request to the GraphQL endpoint to search exercises, returning the
+    /// ids and snippets of the results.
+    fn search_exercises(
+        graphql_filter: &warp::filters::BoxedFilter<(impl Reply + 'static,)>,
+        query: &str,
+    ) -> Vec<(String, String)> {
+        let request = create_graphql_request!(
+            "query SearchExercises($query: String!) {
+                searchExercises(query: $query) { exercise { id } snippet }
+            }"
+            .replace("\n", " "),
+            format!("{{ \"query\": \"{}\" }}", query)
+        );
+        let response = make_test_graphql_request(&request).reply(graphql_filter);
+        let response: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        response["data"]["searchExercises"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| {
+                (
+                    result["exercise"]["id"].as_str().unwrap().to_string(),
+                    result["snippet"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect()
+    }
+
+    /// Make a HTTP POST request request to the GraphQL endpoint to submit an attempt at typing an
+    /// exercise in 60 seconds, returning the recorded attempt.
+    fn submit_attempt(
+        graphql_filter: &warp::filters::BoxedFilter<(impl Reply + 'static,)>,
+        exercise_id: &str,
+        keys: &[&str],
+    ) -> serde_json::Value {
+        // Press the keys evenly spread over a minute.
+        let keystrokes: Vec<serde_json::Value> = keys
+            .iter()
+            .zip(1..)
+            .map(|(key, i)| serde_json::json!({ "key": key, "elapsedMs": i * 60_000 / keys.len() }))
+            .collect();
+        let request = create_graphql_request!(
+            "mutation SubmitAttempt($attempt: NewTypingAttempt!) {
+                submitAttempt(attempt: $attempt) {
+                    id exerciseId typedText grossWpm netWpm accuracy errorPositions
+                }
+            }"
+            .replace("\n", " "),
+            serde_json::json!({
+                "attempt": {
+                    "exerciseId": exercise_id,
+                    "startedOn": 0.0,
+                    "keystrokes": keystrokes,
+                }
+            })
+            .to_string()
+        );
         let response = make_test_graphql_request(&request).reply(graphql_filter);
-        let updated_exercise = deserialize_exercise_from_response(response, "updateExercise");
-
-        let expected_exercise =
-            find_exercise_by_id(&graphql_filter, updated_exercise.id.as_ref().unwrap());
-
-        assert_eq!(expected_exercise.id, updated_exercise.id);
-        assert_eq!(updated_title, updated_exercise.title);
-        assert_eq!(expected_exercise.body, updated_exercise.body);
-        assert_eq!(expected_exercise.topic, updated_exercise.topic);
-        assert_eq!(expected_exercise.created_on, updated_exercise.created_on);
-        assert!(expected_exercise.modified_on <= updated_exercise.modified_on);
+        let response: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        response["data"]["submitAttempt"].clone()
+    }
 
-        updated_exercise
+    /// Make a HTTP POST request request to the GraphQL endpoint to list the ids of the attempts at
+    /// an exercise.
+    fn list_attempt_ids(
+        graphql_filter: &warp::filters::BoxedFilter<(impl Reply + 'static,)>,
+        exercise_id: &str,
+    ) -> Vec<String> {
+        let request = create_graphql_request!(
+            "query ListAttempts($exerciseId: String!) {
+                exerciseAttempts(exerciseId: $exerciseId) { edges { node { id } } }
+            }"
+            .replace("\n", " "),
+            format!("{{ \"exerciseId\": \"{}\" }}", exercise_id)
+        );
+        let response = make_test_graphql_request(&request).reply(graphql_filter);
+        let response: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        response["data"]["exerciseAttempts"]["edges"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|edge| edge["node"]["id"].as_str().unwrap().to_string())
+            .collect()
     }
 
     /// Make a HTTP POST request request to the GraphQL endpoint to delete an exercise by its id.
     fn delete_exercise_by_id(
         graphql_filter: &warp::filters::BoxedFilter<(impl Reply + 'static,)>,
+        access_token: &str,
         id: &str,
     ) -> Exercise {
         let request = create_delete_exercise_by_id_graphql_request(id);
-        let response = make_test_graphql_request(&request).reply(graphql_filter);
+        let response = make_test_graphql_request(&request)
+            .header("authorization", format!("Bearer {}", access_token))
+            .reply(graphql_filter);
         let deleted_exercise = deserialize_exercise_from_response(response, "deleteExerciseById");
         deleted_exercise
     }
@@ -467,33 +2381,1141 @@ mod tests {
     /// Test creating, reading, updating, and deleting exercises via a GraphQL endpoint.
     #[test]
     fn graphql_crud_integration() {
-        dotenv().ok();
-        let test_database_url =
-            env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
-        env::set_var("DATABASE_URL", test_database_url);
-
-        let graphql_filter = create_graphql_filter();
+        let (context, graphql_filter) = test_endpoint();
+        let (_, access_token) = create_test_user(&context, models::Role::User);
 
         // Create a new exercise.
         let title = "Albatross";
         let body = "Albatrosses, of the biological family Diomedeidae, are large seabirds related \
                     to the procellariids, storm petrels, and diving petrels in the order \
                     Procellariiformes (the tubenoses).";
-        let new_exercise = create_new_exercise(&graphql_filter, title, body);
+        let new_exercise = create_new_exercise(&graphql_filter, &access_token, title, body);
+
+        // Create an exercise whose body is normalized.
+        let request = create_new_exercise_graphql_request(
+            "Albatross (typographic)",
+            "Albatrosses[1] are  “large” seabirds…",
+        );
+        let response = make_test_graphql_request(&request)
+            .header("authorization", format!("Bearer {}", access_token))
+            .reply(&graphql_filter);
+        let normalized_exercise = deserialize_exercise_from_response(response, "createExercise");
+        assert_eq!(
+            normalized_exercise.body.as_deref(),
+            Some("Albatrosses are \"large\" seabirds...")
+        );
 
         // Read the new exercise.
         let id = new_exercise.id.as_ref().unwrap();
         let found_exercise = find_exercise_by_id(&graphql_filter, id);
         assert_eq!(found_exercise, new_exercise);
 
+        // List the new exercise.
+        let ids = list_exercise_ids_by_title(&graphql_filter, "ALBATROSS");
+        assert!(ids.contains(id));
+
+        // Search for the new exercise.
+        let results = search_exercises(&graphql_filter, "seabird tubenose");
+        let (_, snippet) = results.iter().find(|(result, _)| result == id).unwrap();
+        assert!(snippet.contains("<b>seabirds</b>"));
+
         // Update the new exercise.
         let mut updated_exercise = found_exercise.clone();
         updated_exercise.title = Some(String::from("The Amazing Albatross"));
-        let updated_exercise = update_exercise(&graphql_filter, updated_exercise);
+        let updated_exercise = update_exercise(&graphql_filter, &access_token, updated_exercise);
+
+        // Record attempts at typing the exercise.
+        let keys = [
+            "A",
+            "l",
+            "x",
+            "Backspace",
+            "b",
+            "a",
+            "t",
+            "r",
+            "o",
+            "s",
+            "s",
+        ];
+        let first = submit_attempt(&graphql_filter, id, &keys);
+        assert_eq!(first["exerciseId"], id.as_str());
+        assert_eq!(first["typedText"], "Albatross");
+        assert_eq!(first["errorPositions"], serde_json::json!([2]));
+        assert_eq!(first["grossWpm"], 2.0);
+        assert_eq!(first["netWpm"], 2.0);
+        assert_eq!(first["accuracy"], 0.9);
+        let second = submit_attempt(&graphql_filter, id, &["A", "l", "b"]);
+        assert!(submit_attempt(&graphql_filter, id, &["Al"]).is_null());
+        let ids = list_attempt_ids(&graphql_filter, id);
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&first["id"].as_str().unwrap().to_string()));
+        assert!(ids.contains(&second["id"].as_str().unwrap().to_string()));
 
         // Delete the new exercise.
-        let deleted_exercise = delete_exercise_by_id(&graphql_filter, id);
+        let deleted_exercise = delete_exercise_by_id(&graphql_filter, &access_token, id);
         assert_eq!(updated_exercise, deleted_exercise);
         assert_exercise_not_found_by_id(&graphql_filter, id);
     }
+
+    /// Make a HTTP POST request to the GraphQL endpoint, optionally authenticated with an access
+    /// token, returning the JSON response.
+    fn request(
+        graphql_filter: &warp::filters::BoxedFilter<(impl Reply + 'static,)>,
+        query: &str,
+        variables: serde_json::Value,
+        access_token: Option<&str>,
+    ) -> serde_json::Value {
+        let request = create_graphql_request!(query, variables.to_string());
+        let mut builder = make_test_graphql_request(&request);
+        if let Some(access_token) = access_token {
+            builder = builder.header("authorization", format!("Bearer {}", access_token));
+        }
+        let response = builder.reply(graphql_filter);
+        serde_json::from_slice(response.body()).unwrap()
+    }
+
+    /// Returns the `client_error` of the first error of a GraphQL response.
+    fn client_error(response: &serde_json::Value) -> Option<&str> {
+        response["errors"][0]["extensions"]["client_error"].as_str()
+    }
+
+    /// Test registering, logging in, refreshing tokens and authenticating requests via a GraphQL
+    /// endpoint.
+    #[test]
+    fn graphql_auth_integration() {
+        let (_, graphql_filter) = test_endpoint();
+        let auth_fields = "user { id username role } accessToken refreshToken expiresIn";
+        let register = format!(
+            "mutation Register($credentials: Credentials!) {{ \
+             register(credentials: $credentials) {{ {} }} }}",
+            auth_fields
+        );
+        let login = format!(
+            "mutation Login($credentials: Credentials!) {{ \
+             login(credentials: $credentials) {{ {} }} }}",
+            auth_fields
+        );
+        let refresh = format!(
+            "mutation Refresh($token: String!) {{ refreshTokens(refreshToken: $token) {{ {} }} }}",
+            auth_fields
+        );
+        let me = "query { me { id username } }";
+
+        // Register a new user.
+        let username = format!("albatross-{}", uuid::Uuid::new_v4());
+        let credentials = serde_json::json!({
+            "credentials": { "username": username, "password": "correct horse" }
+        });
+        let registered = request(&graphql_filter, &register, credentials.clone(), None);
+        let registered = &registered["data"]["register"];
+        assert_eq!(registered["user"]["username"], username.as_str());
+        assert_eq!(registered["user"]["role"], "USER");
+        assert_eq!(registered["expiresIn"], 900);
+
+        // Usernames are unique and passwords must not be too short.
+        let response = request(&graphql_filter, &register, credentials.clone(), None);
+        assert_eq!(client_error(&response), Some("conflict"));
+        let short_password = serde_json::json!({
+            "credentials": { "username": "petrel", "password": "short" }
+        });
+        let response = request(&graphql_filter, &register, short_password, None);
+        assert_eq!(client_error(&response), Some("bad_request"));
+
+        // Log in with the wrong and the right password.
+        let wrong_password = serde_json::json!({
+            "credentials": { "username": username, "password": "incorrect horse" }
+        });
+        let response = request(&graphql_filter, &login, wrong_password, None);
+        assert_eq!(client_error(&response), Some("unauthorized"));
+        let logged_in = request(&graphql_filter, &login, credentials, None);
+        let logged_in = &logged_in["data"]["login"];
+        assert_eq!(logged_in["user"], registered["user"]);
+
+        // Authenticate as the user.
+        let access_token = logged_in["accessToken"].as_str().unwrap();
+        let response = request(
+            &graphql_filter,
+            me,
+            serde_json::json!({}),
+            Some(access_token),
+        );
+        assert_eq!(response["data"]["me"]["username"], username.as_str());
+        let response = request(&graphql_filter, me, serde_json::json!({}), None);
+        assert_eq!(client_error(&response), Some("unauthorized"));
+        let response = request(&graphql_filter, me, serde_json::json!({}), Some("garbage"));
+        assert_eq!(client_error(&response), Some("unauthorized"));
+
+        // Exchange the refresh token (but not the access token) for new tokens.
+        let refresh_token = logged_in["refreshToken"].as_str().unwrap();
+        let response = request(
+            &graphql_filter,
+            &refresh,
+            serde_json::json!({ "token": access_token }),
+            None,
+        );
+        assert_eq!(client_error(&response), Some("unauthorized"));
+        let response = request(
+            &graphql_filter,
+            &refresh,
+            serde_json::json!({ "token": refresh_token }),
+            None,
+        );
+        let refreshed = &response["data"]["refreshTokens"];
+        assert_eq!(refreshed["user"], registered["user"]);
+        let access_token = refreshed["accessToken"].as_str().unwrap();
+        let response = request(
+            &graphql_filter,
+            me,
+            serde_json::json!({}),
+            Some(access_token),
+        );
+        assert_eq!(response["data"]["me"]["id"], registered["user"]["id"]);
+    }
+
+    /// Test that only authors and moderators may update and delete exercises via a GraphQL
+    /// endpoint.
+    #[test]
+    fn graphql_authorization_integration() {
+        let (context, graphql_filter) = test_endpoint();
+        let (author, author_token) = create_test_user(&context, models::Role::User);
+        let (_, other_token) = create_test_user(&context, models::Role::User);
+        let (_, moderator_token) = create_test_user(&context, models::Role::Moderator);
+
+        let create = "mutation Create($exercise: NewExercise!) { \
+                      createExercise(newExercise: $exercise) { id authorId } }";
+        let update = "mutation Update($exercise: UpdatedExercise!) { \
+                      updateExercise(updatedExercise: $exercise) { id title } }";
+        let delete = "mutation Delete($id: String!) { deleteExerciseById(id: $id) { id } }";
+        let new_exercise = serde_json::json!({
+            "exercise": { "title": "Albatross", "body": "Albatross body" }
+        });
+
+        // Anonymous callers may not create exercises.
+        let response = request(&graphql_filter, create, new_exercise.clone(), None);
+        assert_eq!(client_error(&response), Some("unauthorized"));
+
+        // Exercises are created by their author.
+        let response = request(&graphql_filter, create, new_exercise, Some(&author_token));
+        let exercise = &response["data"]["createExercise"];
+        assert_eq!(exercise["authorId"], author.id.as_str());
+        let id = exercise["id"].as_str().unwrap();
+
+        // Only the author and moderators may update the exercise.
+        let updated_exercise =
+            |title: &str| serde_json::json!({ "exercise": { "id": id, "title": title } });
+        let response = request(&graphql_filter, update, updated_exercise("Petrel"), None);
+        assert_eq!(client_error(&response), Some("unauthorized"));
+        let response = request(
+            &graphql_filter,
+            update,
+            updated_exercise("Petrel"),
+            Some(&other_token),
+        );
+        assert_eq!(client_error(&response), Some("forbidden"));
+        let response = request(
+            &graphql_filter,
+            update,
+            updated_exercise("Albatross 2"),
+            Some(&author_token),
+        );
+        assert_eq!(response["data"]["updateExercise"]["title"], "Albatross 2");
+        let response = request(
+            &graphql_filter,
+            update,
+            updated_exercise("Albatross 3"),
+            Some(&moderator_token),
+        );
+        assert_eq!(response["data"]["updateExercise"]["title"], "Albatross 3");
+
+        // Updates based on a stale version conflict.
+        let versioned_exercise = |title: &str, version: i32| {
+            serde_json::json!({
+                "exercise": { "id": id, "title": title, "expectedVersion": version }
+            })
+        };
+        let response = request(
+            &graphql_filter,
+            update,
+            versioned_exercise("Albatross 4", 2),
+            Some(&author_token),
+        );
+        assert_eq!(client_error(&response), Some("conflict"));
+        let response = request(
+            &graphql_filter,
+            update,
+            versioned_exercise("Albatross 4", 3),
+            Some(&author_token),
+        );
+        assert_eq!(response["data"]["updateExercise"]["title"], "Albatross 4");
+
+        // Only the author and moderators may delete the exercise.
+        let response = request(
+            &graphql_filter,
+            delete,
+            serde_json::json!({ "id": id }),
+            Some(&other_token),
+        );
+        assert_eq!(client_error(&response), Some("forbidden"));
+        let response = request(
+            &graphql_filter,
+            delete,
+            serde_json::json!({ "id": id }),
+            Some(&moderator_token),
+        );
+        assert_eq!(response["data"]["deleteExerciseById"]["id"], id);
+    }
+
+    /// Test creating, updating and deleting batches of exercises via a GraphQL endpoint, with
+    /// errors reported per item.
+    #[test]
+    fn graphql_batch_integration() {
+        let (context, graphql_filter) = test_endpoint();
+        let (_, author_token) = create_test_user(&context, models::Role::User);
+        let (_, other_token) = create_test_user(&context, models::Role::User);
+
+        let batch_fields = "exercises { id title version } errors { index code }";
+        let create = format!(
+            "mutation Create($input: [NewExercise!]!) {{ createExercises(input: $input) {{ {} }} }}",
+            batch_fields
+        );
+        let update = format!(
+            "mutation Update($input: [UpdatedExercise!]!) {{ \
+             updateExercises(input: $input) {{ {} }} }}",
+            batch_fields
+        );
+        let delete = format!(
+            "mutation Delete($ids: [String!]!) {{ deleteExercisesByIds(ids: $ids) {{ {} }} }}",
+            batch_fields
+        );
+        let codes = |batch: &serde_json::Value| -> Vec<(i64, String)> {
+            batch["errors"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|e| {
+                    (
+                        e["index"].as_i64().unwrap(),
+                        e["code"].as_str().unwrap().into(),
+                    )
+                })
+                .collect()
+        };
+
+        let response = request(
+            &graphql_filter,
+            &create,
+            serde_json::json!({ "input": [
+                { "title": "Albatross", "body": "Albatross body" },
+                { "title": "Penguin", "body": "Penguin body" },
+            ] }),
+            Some(&author_token),
+        );
+        let batch = &response["data"]["createExercises"];
+        assert_eq!(codes(batch), vec![]);
+        let albatross = batch["exercises"][0]["id"].as_str().unwrap();
+        let penguin = batch["exercises"][1]["id"].as_str().unwrap();
+        assert_eq!(batch["exercises"][1]["title"], "Penguin");
+
+        let response = request(
+            &graphql_filter,
+            &create,
+            serde_json::json!({ "input": [{ "title": "Petrel", "body": "Petrel body" }] }),
+            Some(&other_token),
+        );
+        let petrel = response["data"]["createExercises"]["exercises"][0]["id"]
+            .as_str()
+            .unwrap();
+
+        // Anonymous callers may not create exercises.
+        let input = serde_json::json!({ "input": [{ "title": "Skua", "body": "Skua body" }] });
+        let response = request(&graphql_filter, &create, input, None);
+        assert_eq!(client_error(&response), Some("unauthorized"));
+
+        // Anonymous callers may not update exercises.
+        let input = serde_json::json!({ "input": [{ "id": albatross, "title": "Albatross 2" }] });
+        let response = request(&graphql_filter, &update, input, None);
+        assert_eq!(client_error(&response), Some("unauthorized"));
+
+        // Failing items do not affect the other items.
+        let response = request(
+            &graphql_filter,
+            &update,
+            serde_json::json!({ "input": [
+                { "id": albatross, "title": "Albatross 2" },
+                { "id": petrel, "title": "Petrel 2" },
+                { "id": "unknown", "title": "Unknown" },
+                { "id": penguin, "title": "Penguin 2", "expectedVersion": 2 },
+            ] }),
+            Some(&author_token),
+        );
+        let batch = &response["data"]["updateExercises"];
+        assert_eq!(batch["exercises"][0]["title"], "Albatross 2");
+        assert_eq!(batch["exercises"][0]["version"], 2);
+        assert!(batch["exercises"][1].is_null());
+        assert!(batch["exercises"][2].is_null());
+        assert!(batch["exercises"][3].is_null());
+        assert_eq!(
+            codes(batch),
+            vec![
+                (1, String::from("forbidden")),
+                (2, String::from("not_found")),
+                (3, String::from("conflict")),
+            ]
+        );
+
+        let response = request(
+            &graphql_filter,
+            &delete,
+            serde_json::json!({ "ids": [albatross, petrel, "unknown", penguin] }),
+            Some(&author_token),
+        );
+        let batch = &response["data"]["deleteExercisesByIds"];
+        assert_eq!(batch["exercises"][0]["title"], "Albatross 2");
+        assert_eq!(batch["exercises"][3]["title"], "Penguin");
+        assert_eq!(
+            codes(batch),
+            vec![
+                (1, String::from("forbidden")),
+                (2, String::from("not_found"))
+            ]
+        );
+        assert_exercise_not_found_by_id(&graphql_filter, albatross);
+        assert_exercise_not_found_by_id(&graphql_filter, penguin);
+    }
+
+    /// Test splitting a long text into a series of exercises linked via `next` and `previous`.
+    #[test]
+    fn graphql_series_integration() {
+        let (context, graphql_filter) = test_endpoint();
+        let (_, access_token) = create_test_user(&context, models::Role::User);
+
+        let create = "mutation Create($input: NewExerciseSeries!) { \
+                      createExerciseSeries(input: $input) { id title body seriesId partIndex } }";
+        let sentence = "Albatrosses are large seabirds of the Southern Ocean.";
+        let body = format!("{} {}\n\n{} {}", sentence, sentence, sentence, sentence);
+        let input = serde_json::json!({ "input": {
+            "title": "Albatross",
+            "body": body,
+            "partLength": 110,
+        } });
+        let response = request(&graphql_filter, create, input, Some(&access_token));
+        let parts = response["data"]["createExerciseSeries"].as_array().unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0]["title"], "Albatross (1/2)");
+        assert_eq!(parts[1]["body"], format!("{} {}", sentence, sentence));
+        assert_eq!(parts[1]["partIndex"], 1);
+        assert_eq!(parts[0]["seriesId"], parts[1]["seriesId"]);
+
+        let find = "query Find($id: String!) { \
+                    exercise(id: $id) { next { id } previous { id } } }";
+        let response = request(
+            &graphql_filter,
+            find,
+            serde_json::json!({ "id": parts[0]["id"] }),
+            None,
+        );
+        let exercise = &response["data"]["exercise"];
+        assert_eq!(exercise["next"]["id"], parts[1]["id"]);
+        assert!(exercise["previous"].is_null());
+        let response = request(
+            &graphql_filter,
+            find,
+            serde_json::json!({ "id": parts[1]["id"] }),
+            None,
+        );
+        let exercise = &response["data"]["exercise"];
+        assert!(exercise["next"].is_null());
+        assert_eq!(exercise["previous"]["id"], parts[0]["id"]);
+
+        let body = format!("{}\n\n{}\n\n{}", sentence, sentence, sentence);
+        let input = serde_json::json!({ "input": {
+            "title": "Albatross",
+            "body": body,
+            "partLength": 60,
+        } });
+        let response = request(&graphql_filter, create, input, Some(&access_token));
+        let parts = response["data"]["createExerciseSeries"].as_array().unwrap();
+        assert_eq!(parts.len(), 3);
+        let delete = "mutation Delete($id: String!) { deleteExerciseById(id: $id) { id } }";
+        let response = request(
+            &graphql_filter,
+            delete,
+            serde_json::json!({ "id": parts[1]["id"] }),
+            Some(&access_token),
+        );
+        assert_eq!(response["data"]["deleteExerciseById"]["id"], parts[1]["id"]);
+        let response = request(
+            &graphql_filter,
+            find,
+            serde_json::json!({ "id": parts[0]["id"] }),
+            None,
+        );
+        assert_eq!(response["data"]["exercise"]["next"]["id"], parts[2]["id"]);
+        let response = request(
+            &graphql_filter,
+            find,
+            serde_json::json!({ "id": parts[2]["id"] }),
+            None,
+        );
+        assert_eq!(
+            response["data"]["exercise"]["previous"]["id"],
+            parts[0]["id"]
+        );
+
+        let input = serde_json::json!({ "input": { "title": "Albatross", "body": "  \n\n " } });
+        let response = request(&graphql_filter, create, input, Some(&access_token));
+        assert_eq!(client_error(&response), Some("bad_request"));
+        let input = serde_json::json!({ "input": {
+            "title": "Albatross",
+            "body": body,
+            "partLength": 1,
+        } });
+        let response = request(&graphql_filter, create, input, Some(&access_token));
+        assert_eq!(client_error(&response), Some("bad_request"));
+    }
+
+    /// Test creating topics, adding exercises to them and browsing the topic tree via GraphQL.
+    #[test]
+    fn graphql_topics_integration() {
+        let (context, graphql_filter) = test_endpoint();
+        let (_, moderator_token) = create_test_user(&context, models::Role::Moderator);
+        let (_, author_token) = create_test_user(&context, models::Role::User);
+        let (_, other_token) = create_test_user(&context, models::Role::User);
+
+        let create_topic = "mutation Create($newTopic: NewTopic!) { \
+                            createTopic(newTopic: $newTopic) { id slug name parentId } }";
+        let name = format!("Science {}", Uuid::new());
+        let input = serde_json::json!({ "newTopic": { "name": name } });
+        let response = request(
+            &graphql_filter,
+            create_topic,
+            input.clone(),
+            Some(&author_token),
+        );
+        assert_eq!(client_error(&response), Some("forbidden"));
+        let response = request(&graphql_filter, create_topic, input, Some(&moderator_token));
+        let science = response["data"]["createTopic"].clone();
+        assert_eq!(science["slug"], models::slugify(&name));
+        let input = serde_json::json!({ "newTopic": {
+            "name": "Birds",
+            "slug": format!("birds-{}", Uuid::new()),
+            "parentId": science["id"],
+        } });
+        let response = request(&graphql_filter, create_topic, input, Some(&moderator_token));
+        let birds = response["data"]["createTopic"].clone();
+        assert_eq!(birds["parentId"], science["id"]);
+        let input = serde_json::json!({ "newTopic": { "name": "Birds", "slug": "Not a slug" } });
+        let response = request(&graphql_filter, create_topic, input, Some(&moderator_token));
+        assert_eq!(client_error(&response), Some("bad_request"));
+        let input = serde_json::json!({ "newTopic": { "name": name } });
+        let response = request(&graphql_filter, create_topic, input, Some(&moderator_token));
+        assert_eq!(client_error(&response), Some("conflict"));
+
+        let exercise = create_new_exercise(
+            &graphql_filter,
+            &author_token,
+            "Albatross",
+            "Albatross body",
+        );
+        let add = "mutation Add($id: String!, $topicIds: [String!]!) { \
+                   addExerciseTopics(id: $id, topicIds: $topicIds) { id } }";
+        let variables = serde_json::json!({ "id": exercise.id, "topicIds": [birds["id"]] });
+        let response = request(&graphql_filter, add, variables.clone(), Some(&other_token));
+        assert_eq!(client_error(&response), Some("forbidden"));
+        let response = request(&graphql_filter, add, variables, Some(&author_token));
+        assert_eq!(
+            response["data"]["addExerciseTopics"],
+            serde_json::json!([{ "id": birds["id"] }])
+        );
+
+        let find = "query Find($id: String!) { exercise(id: $id) { topics { slug } } }";
+        let response = request(
+            &graphql_filter,
+            find,
+            serde_json::json!({ "id": exercise.id }),
+            None,
+        );
+        assert_eq!(
+            response["data"]["exercise"]["topics"],
+            serde_json::json!([{ "slug": birds["slug"] }])
+        );
+        let list = "query List($topicId: String!) { \
+                    exercises(filter: { topicId: $topicId }) { totalCount } }";
+        let response = request(
+            &graphql_filter,
+            list,
+            serde_json::json!({ "topicId": birds["id"] }),
+            None,
+        );
+        assert_eq!(response["data"]["exercises"]["totalCount"], 1);
+
+        let tree = "{ topicTree { topic { id } exerciseCount \
+                    children { topic { id } exerciseCount children { topic { id } } } } }";
+        let response = request(&graphql_filter, tree, serde_json::json!({}), None);
+        let roots = response["data"]["topicTree"].as_array().unwrap();
+        let root = roots
+            .iter()
+            .find(|node| node["topic"]["id"] == science["id"])
+            .unwrap();
+        assert_eq!(root["exerciseCount"], 0);
+        assert_eq!(
+            root["children"],
+            serde_json::json!([{
+                "topic": { "id": birds["id"] },
+                "exerciseCount": 1,
+                "children": [],
+            }])
+        );
+
+        let remove = "mutation Remove($id: String!, $topicIds: [String!]!) { \
+                      removeExerciseTopics(id: $id, topicIds: $topicIds) { id } }";
+        let variables = serde_json::json!({ "id": exercise.id, "topicIds": [birds["id"]] });
+        let response = request(&graphql_filter, remove, variables, Some(&author_token));
+        assert_eq!(
+            response["data"]["removeExerciseTopics"],
+            serde_json::json!([])
+        );
+
+        // The free-text topic links the exercise to the topic with the same slug.
+        let topic = format!("Seabirds {}", Uuid::new());
+        let create = "mutation Create($newExercise: NewExercise!) { \
+                      createExercise(newExercise: $newExercise) { id topics { slug } } }";
+        let variables = serde_json::json!({ "newExercise": {
+            "title": "Albatross",
+            "body": "Albatross body",
+            "topic": topic,
+        } });
+        let response = request(&graphql_filter, create, variables, Some(&author_token));
+        let exercise = response["data"]["createExercise"].clone();
+        assert_eq!(
+            exercise["topics"],
+            serde_json::json!([{ "slug": models::slugify(&topic) }])
+        );
+        let roots = "{ topicTree { topic { slug } exerciseCount } }";
+        let count = |slug: &str| {
+            let response = request(&graphql_filter, roots, serde_json::json!({}), None);
+            response["data"]["topicTree"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|node| node["topic"]["slug"] == slug)
+                .map(|node| node["exerciseCount"].clone())
+        };
+        assert_eq!(count(&models::slugify(&topic)), Some(serde_json::json!(1)));
+
+        let other_topic = format!("Penguins {}", Uuid::new());
+        let update = "mutation Update($updatedExercise: UpdatedExercise!) { \
+                      updateExercise(updatedExercise: $updatedExercise) { topics { slug } } }";
+        let variables = serde_json::json!({ "updatedExercise": {
+            "id": exercise["id"],
+            "topic": other_topic,
+        } });
+        let response = request(&graphql_filter, update, variables, Some(&author_token));
+        assert_eq!(
+            response["data"]["updateExercise"]["topics"],
+            serde_json::json!([{ "slug": models::slugify(&other_topic) }])
+        );
+        assert_eq!(count(&models::slugify(&topic)), Some(serde_json::json!(0)));
+        assert_eq!(
+            count(&models::slugify(&other_topic)),
+            Some(serde_json::json!(1))
+        );
+    }
+
+    /// Test tagging an exercise and listing exercises by their tags via GraphQL.
+    #[test]
+    fn graphql_tags_integration() {
+        let (context, graphql_filter) = test_endpoint();
+        let (_, author_token) = create_test_user(&context, models::Role::User);
+        let (_, other_token) = create_test_user(&context, models::Role::User);
+        let exercise = create_new_exercise(&graphql_filter, &author_token, "Pi", "3.14159");
+        // NOTE: Unique tags keep the test independent of exercises tagged by other test runs.
+        let short = format!("short-{}", Uuid::new());
+        let digits = format!("digits-{}", Uuid::new());
+
+        let add = "mutation Add($id: String!, $tags: [String!]!) { \
+                   addTags(id: $id, tags: $tags) { name } }";
+        let variables = serde_json::json!({ "id": exercise.id, "tags": [short] });
+        let response = request(&graphql_filter, add, variables, Some(&other_token));
+        assert_eq!(client_error(&response), Some("forbidden"));
+        let variables = serde_json::json!({ "id": exercise.id, "tags": [" "] });
+        let response = request(&graphql_filter, add, variables, Some(&author_token));
+        assert_eq!(client_error(&response), Some("bad_request"));
+        let variables = serde_json::json!({
+            "id": exercise.id,
+            "tags": [short.to_uppercase(), digits],
+        });
+        let response = request(&graphql_filter, add, variables, Some(&author_token));
+        let mut expected = [short.clone(), digits.clone()];
+        expected.sort();
+        assert_eq!(
+            response["data"]["addTags"],
+            serde_json::json!([{ "name": expected[0] }, { "name": expected[1] }])
+        );
+
+        let list = "query List($filter: ExerciseFilter!) { \
+                    exercises(filter: $filter) { edges { node { id tags { name } } } } }";
+        let filter = serde_json::json!({ "filter": { "tagsAll": [short, digits] } });
+        let response = request(&graphql_filter, list, filter, None);
+        let edges = response["data"]["exercises"]["edges"].as_array().unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0]["node"]["id"], serde_json::json!(exercise.id));
+        let filter = serde_json::json!({ "filter": { "tagsAny": [short, "unknown"] } });
+        let response = request(&graphql_filter, list, filter, None);
+        assert_eq!(
+            response["data"]["exercises"]["edges"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let remove = "mutation Remove($id: String!, $tags: [String!]!) { \
+                      removeTags(id: $id, tags: $tags) { name } }";
+        let variables = serde_json::json!({ "id": exercise.id, "tags": [short] });
+        let response = request(&graphql_filter, remove, variables, Some(&author_token));
+        assert_eq!(
+            response["data"]["removeTags"],
+            serde_json::json!([{ "name": digits }])
+        );
+        let filter = serde_json::json!({ "filter": { "tagsAll": [short, digits] } });
+        let response = request(&graphql_filter, list, filter, None);
+        assert_eq!(
+            response["data"]["exercises"]["edges"],
+            serde_json::json!([])
+        );
+    }
+
+    /// Test listing, diffing and restoring the revisions of an exercise via GraphQL.
+    #[test]
+    fn graphql_revisions_integration() {
+        let (context, graphql_filter) = test_endpoint();
+        let (author, author_token) = create_test_user(&context, models::Role::User);
+        let (_, other_token) = create_test_user(&context, models::Role::User);
+        let exercise = create_new_exercise(
+            &graphql_filter,
+            &author_token,
+            "Albatross",
+            "Albatrosses are large seabirds.",
+        );
+
+        let update = "mutation Update($updatedExercise: UpdatedExercise!) { \
+                      updateExercise(updatedExercise: $updatedExercise) { version } }";
+        let variables = serde_json::json!({ "updatedExercise": {
+            "id": exercise.id,
+            "body": "Albatrosses are big seabirds.",
+        } });
+        let response = request(&graphql_filter, update, variables, Some(&author_token));
+        assert_eq!(response["data"]["updateExercise"]["version"], 2);
+
+        let revisions = "query Revisions($id: String!) { \
+                         exercise(id: $id) { revisions { id version body authorId } } }";
+        let variables = serde_json::json!({ "id": exercise.id });
+        let response = request(&graphql_filter, revisions, variables.clone(), None);
+        let listed = response["data"]["exercise"]["revisions"].clone();
+        assert_eq!(listed.as_array().unwrap().len(), 2);
+        assert_eq!(listed[0]["version"], 2);
+        assert_eq!(listed[0]["authorId"], serde_json::json!(author.id));
+        assert_eq!(listed[1]["body"], "Albatrosses are large seabirds.");
+
+        let diff = "query Diff($fromId: String!, $toId: String!) { \
+                    exerciseRevisionDiff(fromId: $fromId, toId: $toId) { \
+                    from { version } to { version } body { operation text } } }";
+        let ids = serde_json::json!({ "fromId": listed[1]["id"], "toId": listed[0]["id"] });
+        let response = request(&graphql_filter, diff, ids, None);
+        assert_eq!(
+            response["data"]["exerciseRevisionDiff"],
+            serde_json::json!({
+                "from": { "version": 1 },
+                "to": { "version": 2 },
+                "body": [
+                    { "operation": "EQUAL", "text": "Albatrosses are" },
+                    { "operation": "DELETE", "text": "large" },
+                    { "operation": "INSERT", "text": "big" },
+                    { "operation": "EQUAL", "text": "seabirds." },
+                ],
+            })
+        );
+
+        let restore = "mutation Restore($id: String!, $expectedVersion: Int) { \
+                       restoreExerciseRevision(id: $id, expectedVersion: $expectedVersion) { \
+                       body version } }";
+        let response = request(
+            &graphql_filter,
+            restore,
+            serde_json::json!({ "id": listed[1]["id"] }),
+            None,
+        );
+        assert_eq!(client_error(&response), Some("unauthorized"));
+        let variables = serde_json::json!({ "id": listed[1]["id"] });
+        let response = request(&graphql_filter, restore, variables, Some(&other_token));
+        assert_eq!(client_error(&response), Some("forbidden"));
+        let variables = serde_json::json!({ "id": listed[1]["id"], "expectedVersion": 1 });
+        let response = request(&graphql_filter, restore, variables, Some(&author_token));
+        assert_eq!(client_error(&response), Some("conflict"));
+        let variables = serde_json::json!({ "id": listed[1]["id"], "expectedVersion": 2 });
+        let response = request(&graphql_filter, restore, variables, Some(&author_token));
+        assert_eq!(
+            response["data"]["restoreExerciseRevision"],
+            serde_json::json!({ "body": "Albatrosses are large seabirds.", "version": 3 })
+        );
+
+        let response = request(
+            &graphql_filter,
+            revisions,
+            serde_json::json!({ "id": exercise.id }),
+            None,
+        );
+        let listed = response["data"]["exercise"]["revisions"].clone();
+        assert_eq!(listed.as_array().unwrap().len(), 3);
+        assert_eq!(listed[0]["body"], "Albatrosses are large seabirds.");
+        assert_eq!(listed[0]["authorId"], serde_json::json!(author.id));
+    }
+
+    /// Test trashing, listing, restoring and purging an exercise via GraphQL.
+    #[test]
+    fn graphql_trash_integration() {
+        let (context, graphql_filter) = test_endpoint();
+        let (_, author_token) = create_test_user(&context, models::Role::User);
+        let (_, moderator_token) = create_test_user(&context, models::Role::Moderator);
+        let title = format!("Albatross {}", Uuid::new());
+        let exercise = create_new_exercise(
+            &graphql_filter,
+            &author_token,
+            &title,
+            "Albatrosses are large seabirds.",
+        );
+        let id = exercise.id.unwrap();
+        let variables = serde_json::json!({ "id": id });
+
+        let delete = "mutation Delete($id: String!) { deleteExerciseById(id: $id) { deletedOn } }";
+        let response = request(
+            &graphql_filter,
+            delete,
+            variables.clone(),
+            Some(&author_token),
+        );
+        assert!(!response["data"]["deleteExerciseById"]["deletedOn"].is_null());
+        assert_exercise_not_found_by_id(&graphql_filter, &id);
+
+        let list = "query List($filter: ExerciseFilter) { \
+                    exercises(filter: $filter) { edges { node { id } } } }";
+        let list_ids = |trashed: &str| {
+            let filter = serde_json::json!({ "titleContains": title, "trashed": trashed });
+            let response = request(
+                &graphql_filter,
+                list,
+                serde_json::json!({ "filter": filter }),
+                Some(&moderator_token),
+            );
+            response["data"]["exercises"]["edges"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|edge| edge["node"]["id"].as_str().unwrap().to_owned())
+                .collect::<Vec<String>>()
+        };
+        assert!(list_ids("EXCLUDE").is_empty());
+        assert_eq!(list_ids("ONLY"), vec![id.clone()]);
+        let filter = serde_json::json!({ "filter": { "titleContains": title, "trashed": "ONLY" } });
+        let response = request(&graphql_filter, list, filter.clone(), None);
+        assert_eq!(client_error(&response), Some("unauthorized"));
+        let response = request(&graphql_filter, list, filter, Some(&author_token));
+        assert_eq!(client_error(&response), Some("forbidden"));
+
+        let restore = "mutation Restore($id: String!) { restoreExercise(id: $id) { deletedOn } }";
+        let response = request(
+            &graphql_filter,
+            restore,
+            variables.clone(),
+            Some(&author_token),
+        );
+        assert_eq!(
+            response["data"]["restoreExercise"],
+            serde_json::json!({ "deletedOn": null })
+        );
+        let response = request(
+            &graphql_filter,
+            restore,
+            variables.clone(),
+            Some(&author_token),
+        );
+        assert_eq!(client_error(&response), Some("not_found"));
+        assert_eq!(list_ids("EXCLUDE"), vec![id.clone()]);
+
+        let purge = "mutation Purge($id: String!) { purgeExercise(id: $id) { id } }";
+        let response = request(
+            &graphql_filter,
+            purge,
+            variables.clone(),
+            Some(&author_token),
+        );
+        assert_eq!(client_error(&response), Some("forbidden"));
+        let response = request(
+            &graphql_filter,
+            purge,
+            variables.clone(),
+            Some(&moderator_token),
+        );
+        assert_eq!(
+            response["data"]["purgeExercise"],
+            serde_json::json!({ "id": id })
+        );
+        assert!(list_ids("INCLUDE").is_empty());
+    }
+
+    /// Test that updates leave the topic unchanged if absent, and clear it if `null`.
+    #[test]
+    fn graphql_update_topic_integration() {
+        let (context, graphql_filter) = test_endpoint();
+        let (_, author_token) = create_test_user(&context, models::Role::User);
+        let exercise = create_new_exercise(
+            &graphql_filter,
+            &author_token,
+            "Albatross",
+            "Albatrosses are large seabirds.",
+        );
+
+        let update = "mutation Update($updatedExercise: UpdatedExercise!) { \
+                      updateExercise(updatedExercise: $updatedExercise) { title topic } }";
+        let update_with = |fields: serde_json::Value| {
+            let mut updated_exercise = serde_json::json!({ "id": exercise.id });
+            for (key, value) in fields.as_object().unwrap() {
+                updated_exercise[key] = value.clone();
+            }
+            let variables = serde_json::json!({ "updatedExercise": updated_exercise });
+            request(&graphql_filter, update, variables, Some(&author_token))["data"]
+                ["updateExercise"]
+                .clone()
+        };
+        assert_eq!(
+            update_with(serde_json::json!({ "topic": "Birds" })),
+            serde_json::json!({ "title": "Albatross", "topic": "Birds" })
+        );
+        assert_eq!(
+            update_with(serde_json::json!({ "title": "Albatross new" })),
+            serde_json::json!({ "title": "Albatross new", "topic": "Birds" })
+        );
+        assert_eq!(
+            update_with(serde_json::json!({ "title": null, "topic": null })),
+            serde_json::json!({ "title": "Albatross new", "topic": null })
+        );
+
+        // Updates which change nothing are rejected.
+        let variables =
+            serde_json::json!({ "updatedExercise": { "id": exercise.id, "title": null } });
+        let response = request(&graphql_filter, update, variables, Some(&author_token));
+        assert_eq!(client_error(&response), Some("bad_request"));
+
+        // Literal arguments are read like variables.
+        let update = format!(
+            "mutation {{ updateExercise(updatedExercise: {{ id: \"{}\", topic: \"Birds\" }}) \
+             {{ topic }} }}",
+            exercise.id.as_ref().unwrap()
+        );
+        let response = request(
+            &graphql_filter,
+            &update,
+            serde_json::json!({}),
+            Some(&author_token),
+        );
+        assert_eq!(response["data"]["updateExercise"]["topic"], "Birds");
+    }
+
+    /// Test that absent fields of an `UpdatedExercise` are told apart from `null` fields.
+    #[test]
+    fn read_updated_exercise_fields() {
+        use juniper::{FromInputValue, InputValue};
+
+        let read = |value: serde_json::Value| -> Option<UpdatedExercise> {
+            let value: InputValue = serde_json::from_value(value).unwrap();
+            UpdatedExercise::from_input_value(&value)
+        };
+        let updated_exercise =
+            read(serde_json::json!({ "id": "1", "title": "Albatross" })).unwrap();
+        assert_eq!(updated_exercise.title.as_deref(), Some("Albatross"));
+        assert_eq!(updated_exercise.body, None);
+        assert_eq!(updated_exercise.topic, None);
+        assert_eq!(
+            updated_exercise
+                .to_updated_exercise_model(None)
+                .unwrap()
+                .topic,
+            None
+        );
+
+        let updated_exercise = read(serde_json::json!({ "id": "1", "topic": null })).unwrap();
+        assert_eq!(updated_exercise.topic, Some(None));
+        assert_eq!(
+            updated_exercise
+                .to_updated_exercise_model(None)
+                .unwrap()
+                .topic,
+            Some(None)
+        );
+
+        let updated_exercise = read(serde_json::json!({
+            "id": "1",
+            "topic": "Birds",
+            "expectedVersion": 2,
+        }))
+        .unwrap();
+        assert_eq!(updated_exercise.topic, Some(Some(String::from("Birds"))));
+        assert_eq!(updated_exercise.expected_version, Some(2));
+        assert_eq!(
+            updated_exercise
+                .to_updated_exercise_model(None)
+                .unwrap()
+                .topic,
+            Some(Some("Birds"))
+        );
+
+        let updated_exercise =
+            read(serde_json::json!({ "id": "1", "expectedVersion": 2 })).unwrap();
+        assert!(matches!(
+            updated_exercise.to_updated_exercise_model(None),
+            Err(database::Error::QueryError(_))
+        ));
+
+        assert!(read(serde_json::json!({ "topic": "Birds" })).is_none());
+        assert!(read(serde_json::json!({ "id": "1", "topic": 1 })).is_none());
+    }
+
+    /// Test that invalid exercises are rejected with a `validation_error` listing every invalid
+    /// field, and that invalid items of a batch are reported without affecting the other items.
+    #[test]
+    fn graphql_validation_integration() {
+        let (context, graphql_filter) = test_endpoint();
+        let (_, author_token) = create_test_user(&context, models::Role::User);
+
+        let create = "mutation Create($newExercise: NewExercise!) { \
+                      createExercise(newExercise: $newExercise) { id } }";
+        let variables = serde_json::json!({ "newExercise": {
+            "title": " ",
+            "body": "Albatrosses \u{1f426} are large seabirds.",
+            "topic": "Birds",
+        } });
+        let response = request(&graphql_filter, create, variables, Some(&author_token));
+        assert_eq!(client_error(&response), Some("validation_error"));
+        assert_eq!(
+            response["errors"][0]["extensions"]["validation_error"],
+            serde_json::json!([
+                {
+                    "field": "title",
+                    "code": "empty",
+                    "message": "title must not be empty",
+                },
+                {
+                    "field": "body",
+                    "code": "untypeable_character",
+                    "message": "body contains the untypeable character '\u{1f426}' (U+1F426) at \
+                                position 12",
+                },
+            ])
+        );
+
+        let exercise = create_new_exercise(
+            &graphql_filter,
+            &author_token,
+            "Albatross",
+            "Albatrosses are large seabirds.",
+        );
+        let update = "mutation Update($updatedExercise: UpdatedExercise!) { \
+                      updateExercise(updatedExercise: $updatedExercise) { id } }";
+        let variables = serde_json::json!({ "updatedExercise": {
+            "id": exercise.id,
+            "topic": "Bird\u{7}s",
+        } });
+        let response = request(&graphql_filter, update, variables, Some(&author_token));
+        assert_eq!(
+            response["errors"][0]["extensions"]["validation_error"][0]["code"],
+            "control_character"
+        );
+
+        let create_batch = "mutation CreateBatch($input: [NewExercise!]!) { \
+                            createExercises(input: $input) { \
+                            exercises { title } errors { index code } } }";
+        let variables = serde_json::json!({ "input": [
+            { "title": "Penguin", "body": "" },
+            { "title": "Penguin", "body": "Penguins are flightless birds." },
+        ] });
+        let response = request(
+            &graphql_filter,
+            create_batch,
+            variables,
+            Some(&author_token),
+        );
+        assert_eq!(
+            response["data"]["createExercises"],
+            serde_json::json!({
+                "exercises": [null, { "title": "Penguin" }],
+                "errors": [{ "index": 0, "code": "validation_error" }],
+            })
+        );
+    }
+
+    /// Test that cursors survive a round trip and that malformed cursors are rejected.
+    #[test]
+    fn connection_cursors() {
+        assert_eq!(decode_cursor(&encode_cursor(0)), Ok(0));
+        assert_eq!(decode_cursor(&encode_cursor(42)), Ok(42));
+        assert!(decode_cursor("not a cursor").is_err());
+        assert!(decode_cursor(&base64::encode("exercise:-1")).is_err());
+        assert!(decode_cursor(&base64::encode("topic:1")).is_err());
+    }
+
+    /// Test resolving Relay pagination arguments against a collection of 10 items.
+    #[test]
+    fn connection_page_window() {
+        let window = |first, after: Option<i64>, last, before: Option<i64>| {
+            PageArgs {
+                first,
+                after: after.map(encode_cursor),
+                last,
+                before: before.map(encode_cursor),
+            }
+            .window(10)
+            .map(|w| (w.start, w.end, w.has_previous_page, w.has_next_page))
+        };
+
+        // Forward pagination.
+        assert_eq!(window(Some(3), None, None, None), Ok((0, 3, false, true)));
+        assert_eq!(
+            window(Some(3), Some(2), None, None),
+            Ok((3, 6, false, true))
+        );
+        assert_eq!(
+            window(Some(3), Some(6), None, None),
+            Ok((7, 10, false, false))
+        );
+        assert_eq!(
+            window(Some(3), Some(9), None, None),
+            Ok((10, 10, false, false))
+        );
+
+        // Backward pagination.
+        assert_eq!(window(None, None, Some(3), None), Ok((7, 10, true, false)));
+        assert_eq!(
+            window(None, None, Some(3), Some(7)),
+            Ok((4, 7, true, false))
+        );
+        assert_eq!(
+            window(None, None, Some(3), Some(2)),
+            Ok((0, 2, false, false))
+        );
+
+        // Defaults and bounds.
+        assert_eq!(window(None, None, None, None), Ok((0, 10, false, false)));
+        assert_eq!(
+            window(Some(5), Some(1), None, Some(4)),
+            Ok((2, 4, false, false))
+        );
+        assert!(window(Some(-1), None, None, None).is_err());
+        assert_eq!(
+            window(Some(3), Some(i64::MAX), None, None),
+            Ok((10, 10, false, false))
+        );
+        assert!(window(Some(MAX_PAGE_SIZE + 1), None, None, None).is_err());
+    }
 }
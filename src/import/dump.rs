@@ -0,0 +1,207 @@
+use super::Error;
+
+use bzip2::read::MultiBzDecoder;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// A page of a MediaWiki XML dump (with the text of its latest revision).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Page {
+    /// Id of the page, unique within the wiki (pages appear in order of their ids).
+    pub id: u64,
+
+    /// [Namespace] of the page (0 for articles).
+    ///
+    /// [Namespace]: https://en.wikipedia.org/wiki/Wikipedia:Namespace
+    pub namespace: i64,
+
+    /// Title of the page.
+    pub title: String,
+
+    /// Whether the page redirects to another page.
+    pub redirect: bool,
+
+    /// Wikitext of the page.
+    pub text: String,
+}
+
+/// Streams the pages of a MediaWiki XML dump (e.g. `enwiki-latest-pages-articles.xml`), one page
+/// at a time.
+///
+/// See <https://meta.wikimedia.org/wiki/Data_dumps/Dump_format>.
+pub struct DumpReader<R: BufRead> {
+    reader: Reader<R>,
+    buf: Vec<u8>,
+}
+
+/// Opens the dump at `path`, decompressing it if its name ends in ".bz2".
+pub fn open(path: &Path) -> Result<DumpReader<Box<dyn BufRead>>, Error> {
+    let file = File::open(path)?;
+    let reader: Box<dyn BufRead> = match path.extension() {
+        // NOTE: Wikimedia publishes "multistream" dumps, which consist of many bzip2 streams.
+        Some(extension) if extension == "bz2" => {
+            Box::new(BufReader::new(MultiBzDecoder::new(file)))
+        }
+        _ => Box::new(BufReader::new(file)),
+    };
+    Ok(DumpReader::new(reader))
+}
+
+impl<R: BufRead> DumpReader<R> {
+    /// Creates a reader of the dump read from `reader`.
+    pub fn new(reader: R) -> DumpReader<R> {
+        DumpReader {
+            reader: Reader::from_reader(reader),
+            buf: Vec::new(),
+        }
+    }
+
+    /// Reads the next page, or `None` at the end of the dump.
+    fn next_page(&mut self) -> Result<Option<Page>, Error> {
+        let mut page: Option<Page> = None;
+        let mut namespace = String::new();
+        let mut id = String::new();
+        // Names of the elements enclosing the current event.
+        let mut path: Vec<Vec<u8>> = Vec::new();
+        loop {
+            match self.reader.read_event(&mut self.buf)? {
+                Event::Start(e) => {
+                    if e.name() == b"page" {
+                        page = Some(Page::default());
+                        namespace.clear();
+                        id.clear();
+                    }
+                    path.push(e.name().to_vec());
+                }
+                Event::Empty(e) => {
+                    if let (b"redirect", Some(page)) = (e.name(), page.as_mut()) {
+                        page.redirect = true;
+                    }
+                }
+                Event::Text(e) | Event::CData(e) => {
+                    if let Some(page) = page.as_mut() {
+                        let text = e.unescape_and_decode(&self.reader)?;
+                        if in_element(&path, b"page", b"title") {
+                            page.title.push_str(&text);
+                        } else if in_element(&path, b"page", b"ns") {
+                            namespace.push_str(&text);
+                        } else if in_element(&path, b"page", b"id") {
+                            id.push_str(&text);
+                        } else if in_element(&path, b"revision", b"text") {
+                            page.text.push_str(&text);
+                        }
+                    }
+                }
+                Event::End(e) => {
+                    path.pop();
+                    if e.name() == b"page" {
+                        if let Some(mut page) = page.take() {
+                            page.namespace = parse_number(&namespace, "namespace", &page.title)?;
+                            page.id = parse_number(&id, "id", &page.title)?;
+                            return Ok(Some(page));
+                        }
+                    }
+                }
+                Event::Eof => {
+                    return match page {
+                        Some(page) => Err(Error::Xml(format!(
+                            "Unexpected end of dump in page {:?}",
+                            page.title
+                        ))),
+                        None => Ok(None),
+                    };
+                }
+                _ => {}
+            }
+            self.buf.clear();
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for DumpReader<R> {
+    type Item = Result<Page, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_page().transpose()
+    }
+}
+
+/// Returns whether the innermost elements of `path` are `parent` and `child`.
+fn in_element(path: &[Vec<u8>], parent: &[u8], child: &[u8]) -> bool {
+    match path {
+        [.., p, c] => p.as_slice() == parent && c.as_slice() == child,
+        _ => false,
+    }
+}
+
+/// Parses the `field` of the page titled `title`.
+fn parse_number<T: std::str::FromStr>(s: &str, field: &str, title: &str) -> Result<T, Error> {
+    s.trim()
+        .parse()
+        .map_err(|_| Error::Xml(format!("Invalid {} {:?} of page {:?}", field, s, title)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test reading the pages of a (trimmed) dump.
+    #[test]
+    fn read_dump_pages() {
+        let dump = r#"<mediawiki xmlns="http://www.mediawiki.org/xml/export-0.10/" xml:lang="en">
+  <siteinfo>
+    <sitename>Wikipedia</sitename>
+    <namespaces><namespace key="0" case="first-letter" /></namespaces>
+  </siteinfo>
+  <page>
+    <title>Albatross</title>
+    <ns>0</ns>
+    <id>12</id>
+    <revision>
+      <id>1234</id>
+      <text bytes="42" xml:space="preserve">'''Albatrosses''' are &lt;b&gt;large&lt;/b&gt; seabirds.</text>
+    </revision>
+  </page>
+  <page>
+    <title>Albatros</title>
+    <ns>0</ns>
+    <id>13</id>
+    <redirect title="Albatross" />
+    <revision>
+      <id>1235</id>
+      <text xml:space="preserve">#REDIRECT [[Albatross]]</text>
+    </revision>
+  </page>
+</mediawiki>"#;
+        let pages: Vec<Page> = DumpReader::new(dump.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            pages,
+            vec![
+                Page {
+                    id: 12,
+                    namespace: 0,
+                    title: String::from("Albatross"),
+                    redirect: false,
+                    text: String::from("'''Albatrosses''' are <b>large</b> seabirds."),
+                },
+                Page {
+                    id: 13,
+                    namespace: 0,
+                    title: String::from("Albatros"),
+                    redirect: true,
+                    text: String::from("#REDIRECT [[Albatross]]"),
+                },
+            ]
+        );
+
+        let truncated = &dump[..dump.find("<id>13</id>").unwrap()];
+        let mut pages = DumpReader::new(truncated.as_bytes());
+        assert!(pages.next().unwrap().is_ok());
+        assert!(pages.next().unwrap().is_err());
+    }
+}
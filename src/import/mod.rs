@@ -0,0 +1,600 @@
+use crate::database;
+use crate::database::ExerciseDao;
+use crate::models::{series_part_title, NewExercise, NewExerciseBuilder, Uuid};
+use crate::text::{self, passages, NormalizeOptions};
+use crate::validation::{ExerciseLimits, ExerciseValidator};
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Streaming reader of MediaWiki XML dumps.
+pub mod dump;
+
+/// Conversion of wikitext into plain text.
+pub mod wikitext;
+
+pub use dump::{DumpReader, Page};
+
+/// Error type returned when an import fails.
+#[derive(Debug)]
+pub enum Error {
+    /// The dump or the progress file could not be read or written.
+    Io(io::Error),
+
+    /// The dump is not a valid MediaWiki XML dump.
+    Xml(String),
+
+    /// The exercises could not be inserted.
+    Database(database::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Xml(e) => write!(f, "Invalid dump: {}", e),
+            Error::Database(e) => write!(f, "Database error: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<quick_xml::Error> for Error {
+    fn from(e: quick_xml::Error) -> Error {
+        match e {
+            quick_xml::Error::Io(e) => Error::Io(e),
+            e => Error::Xml(e.to_string()),
+        }
+    }
+}
+
+impl From<database::Error> for Error {
+    fn from(e: database::Error) -> Error {
+        Error::Database(e)
+    }
+}
+
+/// Options of an import.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportOptions {
+    /// Minimum number of characters in a passage.
+    pub min_length: usize,
+
+    /// Maximum number of characters in a passage.
+    pub max_length: usize,
+
+    /// Maximum number of passages imported from a single article (from its beginning).
+    pub max_passages_per_page: usize,
+
+    /// Maximum number of articles to import, if any.
+    pub max_pages: Option<usize>,
+
+    /// Normalization applied to the paragraphs of an article before splitting them into passages.
+    pub normalization: NormalizeOptions,
+
+    /// Limits on the fields of the imported exercises. Exercises exceeding them (e.g. the parts of
+    /// an article with a very long title) are skipped.
+    pub limits: ExerciseLimits,
+}
+
+impl Default for ImportOptions {
+    fn default() -> ImportOptions {
+        ImportOptions {
+            min_length: 200,
+            max_length: 800,
+            max_passages_per_page: 5,
+            max_pages: None,
+            normalization: NormalizeOptions::default(),
+            limits: ExerciseLimits::default(),
+        }
+    }
+}
+
+/// Counts of what an import did.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ImportSummary {
+    /// Number of pages read from the dump.
+    pub pages_read: usize,
+
+    /// Number of articles exercises were created from.
+    pub pages_imported: usize,
+
+    /// Number of exercises created.
+    pub exercises_created: usize,
+
+    /// Number of exercises that could not be created.
+    pub exercises_failed: usize,
+}
+
+/// Progress of an import, persisted to a file so that an interrupted import can resume.
+///
+/// The file holds the id of the last imported page. Dumps list pages in order of their ids, so
+/// resuming skips every page up to that id.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Progress {
+    path: PathBuf,
+    last_page_id: Option<u64>,
+}
+
+impl Progress {
+    /// Loads the progress recorded in the file at `path` (none if the file does not exist).
+    pub fn load(path: &Path) -> Result<Progress, Error> {
+        let last_page_id = match fs::read_to_string(path) {
+            Ok(contents) => Some(contents.trim().parse().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid progress file {}", path.display()),
+                )
+            })?),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Progress {
+            path: path.to_path_buf(),
+            last_page_id,
+        })
+    }
+
+    /// Returns the id of the last imported page, if any.
+    pub fn last_page_id(&self) -> Option<u64> {
+        self.last_page_id
+    }
+
+    /// Returns whether the page with the given id was imported (or skipped) before.
+    fn is_done(&self, page_id: u64) -> bool {
+        self.last_page_id >= Some(page_id)
+    }
+
+    /// Records that the page with the given id was imported.
+    fn record(&mut self, page_id: u64) -> Result<(), Error> {
+        // NOTE: Write a temporary file and rename it, so an interruption never leaves behind a
+        // truncated progress file.
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, page_id.to_string())?;
+        fs::rename(&tmp_path, &self.path)?;
+        self.last_page_id = Some(page_id);
+        Ok(())
+    }
+}
+
+/// Topics (see <https://en.wikipedia.org/wiki/Portal:Contents/Portals>), along with the keywords
+/// of the categories implying them (see `matches_keyword`).
+const TOPIC_KEYWORDS: &[(&str, &[&str])] = &[
+    (
+        "Culture and the arts",
+        &[
+            "album",
+            "architect",
+            "art",
+            "dance",
+            "film",
+            "literature",
+            "music",
+            "novel",
+            "painter",
+            "poet",
+            "song",
+            "television",
+            "theatre",
+        ],
+    ),
+    (
+        "Geography and places",
+        &[
+            "cities",
+            "city",
+            "countries",
+            "country",
+            "geography",
+            "island",
+            "lake",
+            "mountain",
+            "populated places",
+            "region",
+            "river",
+            "village",
+        ],
+    ),
+    (
+        "Health and fitness",
+        &[
+            "disease",
+            "disorder",
+            "fitness",
+            "health",
+            "medical",
+            "medicine",
+            "nutrition",
+        ],
+    ),
+    (
+        "History and events",
+        &[
+            "battle",
+            "century",
+            "dynasty",
+            "empire",
+            "event",
+            "histor",
+            "revolution",
+            "war",
+        ],
+    ),
+    (
+        "Mathematics and logic",
+        &[
+            "algebra",
+            "geometry",
+            "logic",
+            "mathemati",
+            "number",
+            "statistic",
+            "theorem",
+        ],
+    ),
+    (
+        "Natural and physical sciences",
+        &[
+            "animal", "astronom", "biolog", "bird", "chemi", "fish", "geolog", "insect", "mammal",
+            "physic", "plant", "species", "taxa",
+        ],
+    ),
+    (
+        "People and self",
+        &["births", "deaths", "living people", "people"],
+    ),
+    (
+        "Philosophy and thinking",
+        &["epistemolog", "ethic", "philosoph"],
+    ),
+    (
+        "Religion and belief systems",
+        &[
+            "buddhis",
+            "christian",
+            "church",
+            "hindu",
+            "islam",
+            "judaism",
+            "mytholog",
+            "religio",
+            "temple",
+        ],
+    ),
+    (
+        "Society and social sciences",
+        &[
+            "compan",
+            "econom",
+            "education",
+            "language",
+            "law",
+            "organi",
+            "politic",
+            "sociolog",
+        ],
+    ),
+    (
+        "Technology and applied sciences",
+        &[
+            "aircraft",
+            "comput",
+            "electronic",
+            "engineering",
+            "internet",
+            "programming",
+            "software",
+            "technolog",
+            "vehicle",
+        ],
+    ),
+];
+
+/// Returns whether the (lowercase) words of a category match a keyword.
+///
+/// Keywords of several words match the same words in sequence. Keywords of a single word match
+/// the same word or its plural, and keywords of at least five letters also match the words they
+/// begin (so that "histor" matches "history" and "historians").
+fn matches_keyword(words: &[String], keyword: &str) -> bool {
+    if keyword.contains(' ') {
+        return format!(" {} ", words.join(" ")).contains(&format!(" {} ", keyword));
+    }
+    words.iter().any(|word| {
+        word == keyword
+            || (word.starts_with(keyword) && ["s", "es"].contains(&&word[keyword.len()..]))
+            || (keyword.len() >= 5 && word.starts_with(keyword))
+    })
+}
+
+/// Infers the topic of an article from its categories, returning the topic implied by the most
+/// categories (if any).
+pub fn infer_topic(categories: &[String]) -> Option<&'static str> {
+    let categories: Vec<Vec<String>> = categories
+        .iter()
+        .map(|category| {
+            category
+                .to_lowercase()
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .collect();
+    let mut best = None;
+    let mut best_score = 0;
+    for (topic, keywords) in TOPIC_KEYWORDS {
+        let score = categories
+            .iter()
+            .filter(|words| {
+                keywords
+                    .iter()
+                    .any(|keyword| matches_keyword(words, keyword))
+            })
+            .count();
+        if score > best_score {
+            best = Some(*topic);
+            best_score = score;
+        }
+    }
+    best
+}
+
+/// Converts an article into new exercises: one per passage, titled after the article (and
+/// numbered if there are several).
+pub fn page_exercises(page: &Page, options: &ImportOptions) -> Result<Vec<NewExercise>, Error> {
+    if page.namespace != 0 || page.redirect || wikitext::is_disambiguation(&page.text) {
+        return Ok(Vec::new());
+    }
+    let paragraphs: Vec<String> = wikitext::prose_paragraphs(&page.text)
+        .iter()
+        .map(|paragraph| text::normalize(paragraph, &options.normalization).text)
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect();
+    let mut passages =
+        passages::split_passages(&paragraphs, options.min_length, options.max_length);
+    passages.truncate(options.max_passages_per_page);
+    let topic = infer_topic(&wikitext::categories(&page.text));
+    let count = passages.len();
+    let series_id = Uuid::new().to_string();
+    passages
+        .iter()
+        .enumerate()
+        .map(|(i, passage)| {
+            let title = series_part_title(&page.title, i, count);
+            let mut builder = NewExerciseBuilder::new();
+            builder.title(&title).body(passage).topic(topic);
+            if count > 1 {
+                builder.series(&series_id, i as i32);
+            }
+            Ok(builder.build().map_err(database::Error::from)?)
+        })
+        .collect()
+}
+
+/// Validates the fields of an exercise built by `page_exercises` (see
+/// `validation::ExerciseValidator`).
+fn validate_exercise(new_exercise: &NewExercise, limits: &ExerciseLimits) -> database::Result<()> {
+    ExerciseValidator::new(limits)
+        .title(&new_exercise.title)
+        .body(&new_exercise.body)
+        .topic(new_exercise.topic.as_deref())
+        .finish()
+}
+
+/// Imports the articles of a dump as exercises, skipping the pages imported before according to
+/// `progress` and recording the progress after each article.
+///
+/// Exercises which fail validation against `options.limits` are skipped (and counted as failed)
+/// rather than inserted.
+///
+/// The exercises of each article are created in one transaction, so an interrupted import at
+/// worst imports the article in progress twice (if interrupted between creating its exercises
+/// and recording the progress).
+///
+/// # Examples
+///
+/// ```
+/// use diesel::prelude::*;
+/// use wikitype_api::database;
+/// use wikitype_api::database::sql::pool::Dialect;
+/// use wikitype_api::import::{self, DumpReader, ImportOptions, Progress};
+///
+/// let dump = r#"<mediawiki><page>
+///   <title>Albatross</title><ns>0</ns><id>12</id>
+///   <revision><id>1</id><text>'''Albatrosses''' are large [[seabird]]s.
+///
+/// [[Category:Seabirds]]</text></revision>
+/// </page></mediawiki>"#;
+///
+/// let dao = database::sql::SqliteConnection(SqliteConnection::establish(":memory:").unwrap());
+/// database::sql::migrations::run_pending_migrations(&dao.0, Dialect::Sqlite).unwrap();
+///
+/// let progress_path = std::env::temp_dir().join(format!("{}.progress", uuid::Uuid::new_v4()));
+/// let mut progress = Progress::load(&progress_path).unwrap();
+/// let options = ImportOptions { min_length: 10, ..ImportOptions::default() };
+///
+/// let pages = DumpReader::new(dump.as_bytes());
+/// let summary = import::import(&dao, pages, &options, &mut progress).unwrap();
+/// assert_eq!(summary.exercises_created, 1);
+/// assert_eq!(progress.last_page_id(), Some(12));
+///
+/// // Importing the dump again resumes after the last imported page.
+/// let pages = DumpReader::new(dump.as_bytes());
+/// let summary = import::import(&dao, pages, &options, &mut progress).unwrap();
+/// assert_eq!(summary.exercises_created, 0);
+/// # std::fs::remove_file(&progress_path).unwrap();
+/// ```
+pub fn import<I>(
+    dao: &dyn ExerciseDao,
+    pages: I,
+    options: &ImportOptions,
+    progress: &mut Progress,
+) -> Result<ImportSummary, Error>
+where
+    I: IntoIterator<Item = Result<Page, Error>>,
+{
+    let mut summary = ImportSummary::default();
+    for page in pages {
+        if options.max_pages == Some(summary.pages_imported) {
+            break;
+        }
+        let page = page?;
+        summary.pages_read += 1;
+        if progress.is_done(page.id) {
+            continue;
+        }
+        let new_exercises = page_exercises(&page, options)?;
+        if new_exercises.is_empty() {
+            continue;
+        }
+        let mut valid_exercises = Vec::with_capacity(new_exercises.len());
+        for new_exercise in new_exercises {
+            match validate_exercise(&new_exercise, &options.limits) {
+                Ok(()) => valid_exercises.push(new_exercise),
+                Err(database::Error::ValidationError(errors)) => {
+                    let reasons: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                    log::warn!("Skipping {:?}: {}", new_exercise.title, reasons.join(", "));
+                    summary.exercises_failed += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+        for result in dao.create_batch(&valid_exercises)? {
+            match result {
+                Ok(_) => summary.exercises_created += 1,
+                Err(e) => {
+                    log::warn!("Error importing a passage of {:?}: {}", page.title, e);
+                    summary.exercises_failed += 1;
+                }
+            }
+        }
+        summary.pages_imported += 1;
+        progress.record(page.id)?;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(id: u64, title: &str, text: &str) -> Page {
+        Page {
+            id,
+            namespace: 0,
+            title: String::from(title),
+            redirect: false,
+            text: String::from(text),
+        }
+    }
+
+    /// Test inferring topics from categories.
+    #[test]
+    fn infer_topics() {
+        let categories = |categories: &[&str]| -> Vec<String> {
+            categories.iter().map(|c| c.to_string()).collect()
+        };
+        assert_eq!(
+            infer_topic(&categories(&[
+                "Seabirds",
+                "Birds of Antarctica",
+                "Albatrosses"
+            ])),
+            Some("Natural and physical sciences")
+        );
+        assert_eq!(
+            infer_topic(&categories(&[
+                "1879 births",
+                "1955 deaths",
+                "German physicists"
+            ])),
+            Some("People and self")
+        );
+        assert_eq!(
+            infer_topic(&categories(&["Articles with short description", "Warsaw"])),
+            None
+        );
+        assert_eq!(infer_topic(&[]), None);
+    }
+
+    /// Test converting articles into exercises, and skipping pages that are not articles.
+    #[test]
+    fn convert_pages_to_exercises() {
+        let options = ImportOptions {
+            min_length: 10,
+            max_length: 40,
+            max_passages_per_page: 2,
+            max_pages: None,
+            normalization: NormalizeOptions::default(),
+            limits: ExerciseLimits::default(),
+        };
+        let albatross = page(
+            12,
+            "Albatross",
+            "Albatrosses are “large” seabirds.\n\nThey range widely in the Southern Ocean.\n\n\
+             They are efficient in the air.\n\n[[Category:Birds of the Southern Ocean]]",
+        );
+        let exercises = page_exercises(&albatross, &options).unwrap();
+        assert_eq!(exercises.len(), 2);
+        assert_eq!(exercises[0].title, "Albatross (1/2)");
+        assert_eq!(exercises[0].body, "Albatrosses are \"large\" seabirds.");
+        assert_eq!(exercises[1].title, "Albatross (2/2)");
+        assert!(exercises[0].series_id.is_some());
+        assert_eq!(exercises[1].series_id, exercises[0].series_id);
+        assert_eq!(exercises[1].part_index, Some(1));
+        assert_eq!(
+            exercises[1].body,
+            "They range widely in the Southern Ocean."
+        );
+        assert_eq!(
+            exercises[0].topic.as_deref(),
+            Some("Natural and physical sciences")
+        );
+
+        let redirect = Page {
+            redirect: true,
+            ..page(13, "Albatros", "#REDIRECT [[Albatross]]")
+        };
+        assert!(page_exercises(&redirect, &options).unwrap().is_empty());
+        let talk = Page {
+            namespace: 1,
+            ..albatross.clone()
+        };
+        assert!(page_exercises(&talk, &options).unwrap().is_empty());
+    }
+
+    /// Test that exercises exceeding the limits are skipped rather than inserted.
+    #[test]
+    fn skip_invalid_exercises() {
+        use crate::database::sql::{migrations, pool::Dialect, SqliteConnection};
+        use diesel::Connection;
+
+        let dao = SqliteConnection(diesel::SqliteConnection::establish(":memory:").unwrap());
+        migrations::run_pending_migrations(&dao.0, Dialect::Sqlite).unwrap();
+        let progress_path = std::env::temp_dir().join(format!("{}.progress", uuid::Uuid::new_v4()));
+        let mut progress = Progress::load(&progress_path).unwrap();
+        let options = ImportOptions {
+            min_length: 10,
+            max_length: 40,
+            ..ImportOptions::default()
+        };
+        let text = "Albatrosses are large seabirds.\n\nThey range widely in the Southern Ocean.";
+        let pages = vec![
+            Ok(page(12, "Albatross", text)),
+            Ok(page(13, &"Albatross ".repeat(30), text)),
+        ];
+
+        let summary = import(&dao, pages, &options, &mut progress).unwrap();
+        assert_eq!(summary.exercises_created, 2);
+        assert_eq!(summary.exercises_failed, 2);
+        assert_eq!(progress.last_page_id(), Some(13));
+        std::fs::remove_file(&progress_path).unwrap();
+    }
+}
@@ -0,0 +1,468 @@
+/// Titles of sections which hold no prose. The prose of an article ends at the first of them.
+const NON_PROSE_SECTIONS: &[&str] = &[
+    "see also",
+    "notes",
+    "references",
+    "citations",
+    "footnotes",
+    "sources",
+    "bibliography",
+    "works cited",
+    "further reading",
+    "external links",
+    "gallery",
+];
+
+/// Tags whose content is not prose (e.g. references and formulas).
+const NON_PROSE_TAGS: &[&str] = &[
+    "ref",
+    "math",
+    "chem",
+    "ce",
+    "code",
+    "pre",
+    "gallery",
+    "graph",
+    "hiero",
+    "imagemap",
+    "score",
+    "source",
+    "syntaxhighlight",
+    "table",
+    "timeline",
+];
+
+/// Namespaces of links which are not rendered as text.
+const HIDDEN_LINK_NAMESPACES: &[&str] = &["category", "file", "image", "media"];
+
+/// Names (or prefixes of names) of the templates marking disambiguation pages.
+const DISAMBIGUATION_TEMPLATES: &[&str] = &["disambig", "dab", "geodis", "hndis", "set index"];
+
+/// Returns the categories of a page (from its `[[Category:...]]` links).
+pub fn categories(wikitext: &str) -> Vec<String> {
+    let mut categories = Vec::new();
+    let mut rest = wikitext;
+    while let Some(start) = rest.find("[[") {
+        rest = &rest[start + 2..];
+        let end = match rest.find("]]") {
+            Some(end) => end,
+            None => break,
+        };
+        let link = &rest[..end];
+        if let Some((namespace, target)) = split_namespace(link) {
+            if namespace.eq_ignore_ascii_case("category") {
+                let name = target.split('|').next().unwrap_or_default().trim();
+                if !name.is_empty() {
+                    categories.push(name.to_string());
+                }
+            }
+        }
+        rest = &rest[end + 2..];
+    }
+    categories
+}
+
+/// Returns whether a page is a disambiguation page (which lists other pages rather than holding
+/// prose).
+pub fn is_disambiguation(wikitext: &str) -> bool {
+    wikitext.match_indices("{{").any(|(i, _)| {
+        let name = wikitext[i + 2..].trim_start().to_lowercase();
+        DISAMBIGUATION_TEMPLATES
+            .iter()
+            .any(|template| name.starts_with(template))
+    })
+}
+
+/// Strips the markup of a page, returning the paragraphs of its prose as plain text.
+///
+/// Templates, tables, references, lists, images and categories are dropped, links are replaced
+/// by their labels, and the sections after the prose (e.g. "References") are dropped.
+pub fn prose_paragraphs(wikitext: &str) -> Vec<String> {
+    let text = remove_between(wikitext, "<!--", "-->");
+    let text = remove_nested(&text, "{{", "}}");
+    let text = remove_nested(&text, "{|", "|}");
+    let text = strip_tags(&text);
+    let text = strip_links(&text);
+    let text = strip_external_links(&text);
+    let text = strip_emphasis(&text);
+    let text = decode_entities(&text);
+
+    let mut paragraphs = Vec::new();
+    let mut paragraph = String::new();
+    for line in text.lines() {
+        let line = line.trim();
+        let is_heading = line.len() > 1 && line.starts_with('=') && line.ends_with('=');
+        if line.is_empty() || is_heading || is_non_prose_line(line) {
+            push_paragraph(&mut paragraphs, &mut paragraph);
+        }
+        if is_heading {
+            let title = line.trim_matches('=').trim().to_lowercase();
+            if NON_PROSE_SECTIONS.contains(&title.as_str()) {
+                break;
+            }
+        } else if !line.is_empty() && !is_non_prose_line(line) {
+            if !paragraph.is_empty() {
+                paragraph.push(' ');
+            }
+            paragraph.push_str(line);
+        }
+    }
+    push_paragraph(&mut paragraphs, &mut paragraph);
+    paragraphs
+}
+
+/// Returns whether a line (after stripping markup) is not part of a paragraph, e.g. a list item
+/// or a magic word.
+fn is_non_prose_line(line: &str) -> bool {
+    line.starts_with(|c| "*#:;|!{}".contains(c)) || (line.starts_with("__") && line.ends_with("__"))
+}
+
+/// Tidies `paragraph` and appends it to `paragraphs` (if not empty), leaving it empty.
+fn push_paragraph(paragraphs: &mut Vec<String>, paragraph: &mut String) {
+    let tidied = tidy_whitespace(paragraph);
+    if !tidied.is_empty() {
+        paragraphs.push(tidied);
+    }
+    paragraph.clear();
+}
+
+/// Collapses runs of whitespace, and removes the whitespace and empty brackets left behind by
+/// removed markup (e.g. "seabirds ." for "seabirds<ref>...</ref>.").
+fn tidy_whitespace(s: &str) -> String {
+    let mut s = s.split_whitespace().collect::<Vec<_>>().join(" ");
+    for (from, to) in &[
+        ("( ", "("),
+        (" )", ")"),
+        ("()", ""),
+        ("(, ", "("),
+        ("(; ", "("),
+        (" ,", ","),
+        (" .", "."),
+        (" ;", ";"),
+        (" :", ":"),
+        ("  ", " "),
+    ] {
+        while s.contains(from) {
+            s = s.replace(from, to);
+        }
+    }
+    s.trim().to_string()
+}
+
+/// Splits the namespace (e.g. "Category") off the target of a link, ignoring a leading colon.
+fn split_namespace(link: &str) -> Option<(&str, &str)> {
+    let link = link.trim().trim_start_matches(':');
+    let colon = link.find(':')?;
+    Some((link[..colon].trim(), &link[colon + 1..]))
+}
+
+/// Removes everything from each `open` up to and including the next `close`.
+fn remove_between(s: &str, open: &str, close: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find(open) {
+        result.push_str(&rest[..start]);
+        rest = match rest[start..].find(close) {
+            Some(end) => &rest[start + end + close.len()..],
+            None => "",
+        };
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Removes (possibly nested) blocks delimited by `open` and `close`, such as templates.
+fn remove_nested(s: &str, open: &str, close: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut depth = 0;
+    let mut i = 0;
+    while i < s.len() {
+        if s[i..].starts_with(open) {
+            depth += 1;
+            i += open.len();
+        } else if depth > 0 && s[i..].starts_with(close) {
+            depth -= 1;
+            i += close.len();
+        } else {
+            let c = s[i..].chars().next().unwrap();
+            if depth == 0 {
+                result.push(c);
+            }
+            i += c.len_utf8();
+        }
+    }
+    result
+}
+
+/// Removes HTML-like tags, along with the content of `NON_PROSE_TAGS`.
+fn strip_tags(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('<') {
+        result.push_str(&rest[..start]);
+        let tag = &rest[start + 1..];
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+        let end = match (name.is_empty(), tag.find('>')) {
+            (false, Some(end)) => end,
+            _ => {
+                // Not a tag (e.g. "a < b").
+                result.push('<');
+                rest = tag;
+                continue;
+            }
+        };
+        let is_opening = !tag.starts_with('/') && !tag[..end].ends_with('/');
+        rest = &tag[end + 1..];
+        if name == "br" {
+            result.push(' ');
+        } else if is_opening && NON_PROSE_TAGS.contains(&name.as_str()) {
+            let closing = format!("</{}", name);
+            rest = match find_ignore_case(rest, &closing) {
+                Some(close) => match rest[close..].find('>') {
+                    Some(end) => &rest[close + end + 1..],
+                    None => "",
+                },
+                None => "",
+            };
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Returns the index of the first occurrence of the (ASCII) `needle` in `haystack`, ignoring
+/// case.
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack.char_indices().map(|(i, _)| i).find(|&i| {
+        haystack
+            .get(i..i + needle.len())
+            .is_some_and(|s| s.eq_ignore_ascii_case(needle))
+    })
+}
+
+/// Replaces internal links by their labels, removing images, categories and interlanguage links.
+fn strip_links(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find("[[") {
+        result.push_str(&rest[..start]);
+        rest = &rest[start + 2..];
+        // Find the matching "]]" (image captions may contain links).
+        let mut depth = 1;
+        let mut end = None;
+        let mut i = 0;
+        while i < rest.len() {
+            if rest[i..].starts_with("[[") {
+                depth += 1;
+                i += 2;
+            } else if rest[i..].starts_with("]]") {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(i);
+                    break;
+                }
+                i += 2;
+            } else {
+                i += rest[i..].chars().next().unwrap().len_utf8();
+            }
+        }
+        let end = match end {
+            Some(end) => end,
+            None => {
+                rest = "";
+                break;
+            }
+        };
+        let link = &rest[..end];
+        rest = &rest[end + 2..];
+        if let Some((namespace, _)) = split_namespace(link) {
+            let namespace = namespace.to_lowercase();
+            let is_interlanguage = !link.trim().starts_with(':')
+                && (2..=3).contains(&namespace.len())
+                && namespace
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c == '-');
+            if HIDDEN_LINK_NAMESPACES.contains(&namespace.as_str()) || is_interlanguage {
+                continue;
+            }
+        }
+        let label = match link.find('|') {
+            Some(bar) => &link[bar + 1..],
+            None => link.trim_start_matches(':'),
+        };
+        result.push_str(&strip_links(label));
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Replaces external links (`[https://example.org label]`) by their labels.
+fn strip_external_links(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('[') {
+        result.push_str(&rest[..start]);
+        let link = &rest[start + 1..];
+        let is_external = ["http://", "https://", "//"]
+            .iter()
+            .any(|scheme| link.starts_with(scheme));
+        match (is_external, link.find(']')) {
+            (true, Some(end)) => {
+                if let Some(space) = link[..end].find(' ') {
+                    result.push_str(link[space + 1..end].trim());
+                }
+                rest = &link[end + 1..];
+            }
+            _ => {
+                result.push('[');
+                rest = link;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Removes bold and italic markup (runs of two or more apostrophes).
+fn strip_emphasis(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\'' && chars.peek() == Some(&'\'') {
+            while chars.peek() == Some(&'\'') {
+                chars.next();
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Decodes the HTML entities common in wikitext.
+fn decode_entities(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        let entity = &rest[start + 1..];
+        let decoded = entity
+            .find(';')
+            .filter(|&end| end <= 8)
+            .and_then(|end| decode_entity(&entity[..end]).map(|c| (c, end)));
+        match decoded {
+            Some((c, end)) => {
+                result.push(c);
+                rest = &entity[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = entity;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Decodes the name of an HTML entity (without `&` and `;`).
+fn decode_entity(name: &str) -> Option<char> {
+    let c = match name {
+        "nbsp" | "thinsp" | "ensp" | "emsp" => ' ',
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "ndash" => '–',
+        "mdash" => '—',
+        "minus" => '−',
+        "times" => '×',
+        "hellip" => '…',
+        _ => {
+            let code =
+                if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                    u32::from_str_radix(hex, 16).ok()?
+                } else if let Some(decimal) = name.strip_prefix('#') {
+                    decimal.parse().ok()?
+                } else {
+                    return None;
+                };
+            std::char::from_u32(code)?
+        }
+    };
+    Some(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALBATROSS: &str = "{{Short description|Family of seabirds}}
+{{Automatic taxobox
+| image = Albatross.jpg
+| taxon = Diomedeidae
+}}
+'''Albatrosses''', of the [[biological family]] '''Diomedeidae''', are large [[seabird]]s \
+related to the [[Procellariidae|procellariids]].<ref name=\"BWP\">{{cite book |title=Birds}}</ref> \
+They range widely in the [[Southern Ocean]] and the North Pacific.<!-- citation needed -->
+
+[[File:Albatross.jpg|thumb|A [[wandering albatross]] in flight]]
+== Biology ==
+Albatrosses are highly efficient in the air, using [[dynamic soaring]] and slope soaring&nbsp;to \
+cover great distances.
+
+{| class=\"wikitable\"
+| Wingspan || 3.5 m
+|}
+* A list item
+* [https://example.org Another] list item
+
+The name comes from the Arabic ''al-qādūs'' (see [https://example.org etymology]).
+
+== References ==
+{{Reflist}}
+The [[Albatross]] is not prose.
+
+[[Category:Seabirds]]
+[[Category:Extant Eocene first appearances|Albatross]]
+[[fr:Albatros]]
+";
+
+    /// Test stripping the markup of an article down to the paragraphs of its prose.
+    #[test]
+    fn strip_article_markup() {
+        assert_eq!(
+            prose_paragraphs(ALBATROSS),
+            vec![
+                "Albatrosses, of the biological family Diomedeidae, are large seabirds related to \
+                 the procellariids. They range widely in the Southern Ocean and the North Pacific.",
+                "Albatrosses are highly efficient in the air, using dynamic soaring and slope \
+                 soaring to cover great distances.",
+                "The name comes from the Arabic al-qādūs (see etymology).",
+            ]
+        );
+        assert_eq!(
+            categories(ALBATROSS),
+            vec!["Seabirds", "Extant Eocene first appearances"]
+        );
+        assert!(!is_disambiguation(ALBATROSS));
+        assert!(is_disambiguation(
+            "'''Albatross''' may refer to:\n* [[Albatross (band)]]\n{{Disambiguation}}"
+        ));
+    }
+
+    /// Test that stray markup characters are left alone.
+    #[test]
+    fn keep_stray_markup_characters() {
+        assert_eq!(
+            prose_paragraphs("If a < b and b > c, then [a] & [b] are 5 &times 6 it's."),
+            vec!["If a < b and b > c, then [a] & [b] are 5 &times 6 it's."]
+        );
+    }
+}
@@ -8,6 +8,12 @@ extern crate juniper;
 #[macro_use]
 extern crate serde;
 
+/// Authentication of API users (password hashing and signed tokens).
+pub mod auth;
+
+/// Server configuration.
+pub mod config;
+
 /// A basic [data access layer] for WikiType, including [data access objects] for a handful of SQL
 /// and NoSQL databases.
 ///
@@ -15,8 +21,31 @@ extern crate serde;
 /// [data access objects]: https://en.wikipedia.org/wiki/Data_access_object
 pub mod database;
 
+/// Difficulty ratings of exercise texts.
+pub mod difficulty;
+
+/// Word-level differences between texts (e.g. between revisions of an exercise).
+pub mod diff;
+
 /// GraphQL types and resolvers.
 pub mod graphql;
 
+/// Import of exercises from [MediaWiki XML dumps] (e.g. of Wikipedia).
+///
+/// [MediaWiki XML dumps]: https://meta.wikimedia.org/wiki/Data_dumps
+pub mod import;
+
 /// Database-agnostic models for WikiType data.
 pub mod models;
+
+/// Authorization policies deciding what callers may do.
+pub mod policy;
+
+/// Scoring of typing attempts (typing speed, accuracy, errors).
+pub mod scoring;
+
+/// Normalization of exercise texts into text that can be typed on a regular keyboard.
+pub mod text;
+
+/// Validation of exercises before they are written.
+pub mod validation;
@@ -1,10 +1,16 @@
 extern crate log;
 
-use wikitype_api::graphql::{Context, Mutation, Query, Schema};
+use wikitype_api::config::Config;
+use wikitype_api::graphql::{self, Context, Mutation, Query, Schema};
 
 use dotenv::dotenv;
+use std::thread;
+use std::time::Duration;
 use warp::{http::Response, Filter};
 
+/// Interval at which exercises that have been in the trash for too long are purged.
+const TRASH_PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
 fn schema() -> Schema {
     Schema::new(Query, Mutation)
 }
@@ -32,10 +38,71 @@ fn main() {
             ))
     });
 
+    // `--migrate` runs pending migrations before serving; `--migrate-only` runs them and exits.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let migrate_only = args.iter().any(|arg| arg == "--migrate-only");
+    let migrate = migrate_only || args.iter().any(|arg| arg == "--migrate");
+
+    // Build the shared state (e.g. the connection pool) once, and hand out clones per request.
+    let config = Config::from_env().unwrap_or_else(|e| panic!("{}", e));
+    let run_migrations = migrate || config.run_migrations;
+    let context = Context::new(config)
+        .unwrap_or_else(|e| panic!("Error creating the connection pool: {}", e));
+
+    log::info!("Using a {} database", context.dialect());
+
+    if run_migrations {
+        let migrations = context
+            .pool()
+            .run_pending_migrations()
+            .unwrap_or_else(|e| panic!("Error running migrations: {}", e));
+        for migration in migrations {
+            log::info!("Ran migration {}", migration.name());
+        }
+    }
+    if migrate_only {
+        return;
+    }
+
+    // Refuse to serve against an out-of-date schema.
+    let pending = context
+        .pool()
+        .pending_migrations()
+        .unwrap_or_else(|e| panic!("Error checking migrations: {}", e));
+    if !pending.is_empty() {
+        let names: Vec<&str> = pending.iter().map(|migration| migration.name()).collect();
+        panic!(
+            "The database schema is out of date. Pending migrations: {}. Run them with \
+             DATABASE_RUN_MIGRATIONS=true or --migrate.",
+            names.join(", ")
+        );
+    }
+
+    // Rate the exercises created before difficulties were introduced.
+    let rated = context
+        .conn()
+        .and_then(|conn| conn.rate_unrated())
+        .unwrap_or_else(|e| panic!("Error rating exercises: {}", e));
+    if rated > 0 {
+        log::info!("Rated the difficulty of {} exercises", rated);
+    }
+
+    // Purge the exercises that have been in the trash for longer than `TRASH_RETENTION`.
+    if context.config().trash_retention.is_some() {
+        let context = context.clone();
+        thread::spawn(move || loop {
+            match context.purge_trash() {
+                Ok(0) => (),
+                Ok(purged) => log::info!("Purged {} trashed exercises", purged),
+                Err(e) => log::error!("Error purging trashed exercises: {}", e),
+            }
+            thread::sleep(TRASH_PURGE_INTERVAL);
+        });
+    }
     log::info!("Listening on 127.0.0.1:8080");
 
-    let state = warp::any().map(move || Context::new());
-    let graphql_filter = juniper_warp::make_graphql_filter(schema(), state.boxed());
+    let state = graphql::context_filter(context);
+    let graphql_filter = juniper_warp::make_graphql_filter(schema(), state);
 
     warp::serve(
         warp::get2()
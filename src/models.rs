@@ -1,10 +1,21 @@
-use crate::database::sql::schema::exercises;
+use crate::database::sql::schema::{
+    exercise_revisions, exercises, tags, topics, typing_attempts, users,
+};
+use crate::difficulty;
+use crate::scoring::Score;
 
 use chrono::NaiveDateTime;
+use diesel::backend::Backend;
+use diesel::query_builder::AsChangeset;
+use diesel::{ExpressionMethods, Queryable};
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::str::FromStr;
 
 /// A WikiType typing exercise.
-#[derive(juniper::GraphQLObject, Queryable, Debug, Eq, PartialEq, Clone)]
+///
+/// See `graphql` for the fields exposed via the API.
+#[derive(Queryable, Debug, Eq, PartialEq, Clone)]
 pub struct Exercise {
     /// UUID string.
     pub id: String,
@@ -15,9 +26,10 @@ pub struct Exercise {
     /// Content of the exercise.
     pub body: String,
 
-    /// Optional topic describing the general exercise category.
+    /// Optional free-text topic describing the general exercise category.
     ///
-    /// See <https://en.wikipedia.org/wiki/Portal:Contents/Portals> for an idea.
+    /// Superseded by the `Topic`s the exercise is linked to. Creating or updating an exercise
+    /// with a topic links it to the `Topic` with the same slug (creating it if needed).
     pub topic: Option<String>,
 
     /// Date and time of creation.
@@ -25,6 +37,25 @@ pub struct Exercise {
 
     /// Date and time of the last modification.
     pub modified_on: chrono::NaiveDateTime,
+
+    /// Id of the user who created the exercise (if created by a user that still exists).
+    pub author_id: Option<String>,
+
+    /// Version of the exercise, starting at 1 and incremented by every update.
+    pub version: i32,
+
+    /// Id of the series the exercise is a part of, if it was split from a longer text.
+    pub series_id: Option<String>,
+
+    /// Index of the exercise within its series, starting at 0.
+    pub part_index: Option<i32>,
+
+    /// Difficulty of typing the body, from 0 (easiest) to 100 (see `difficulty::difficulty`), or
+    /// `None` if the exercise has not been rated yet.
+    pub difficulty: Option<i32>,
+
+    /// Date and time the exercise was moved to the trash, or `None` if it is not trashed.
+    pub deleted_on: Option<chrono::NaiveDateTime>,
 }
 
 impl fmt::Display for Exercise {
@@ -33,6 +64,27 @@ impl fmt::Display for Exercise {
     }
 }
 
+/// Error type returned when a builder cannot build its resource.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BuilderError {
+    /// The given required field was not set.
+    MissingField(&'static str),
+
+    /// The update would not change any field.
+    EmptyUpdate,
+}
+
+impl fmt::Display for BuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuilderError::MissingField(field) => write!(f, "Missing field {}", field),
+            BuilderError::EmptyUpdate => write!(f, "The update does not change any field"),
+        }
+    }
+}
+
+impl std::error::Error for BuilderError {}
+
 /// Type for creating a new `Exercise`.
 #[derive(Insertable)]
 #[table_name = "exercises"]
@@ -43,6 +95,10 @@ pub struct NewExercise {
     pub topic: Option<String>,
     created_on: chrono::NaiveDateTime,
     modified_on: chrono::NaiveDateTime,
+    pub author_id: Option<String>,
+    pub series_id: Option<String>,
+    pub part_index: Option<i32>,
+    difficulty: i32,
 }
 
 impl NewExercise {
@@ -51,29 +107,55 @@ impl NewExercise {
     }
 }
 
+/// Returns the title of the part at `part_index` (starting at 0) of a series of `parts` exercises
+/// titled `title`.
+///
+/// # Examples
+///
+/// ```
+/// use wikitype_api::models::series_part_title;
+///
+/// assert_eq!(series_part_title("Albatross", 1, 3), "Albatross (2/3)");
+/// assert_eq!(series_part_title("Albatross", 0, 1), "Albatross");
+/// ```
+pub fn series_part_title(title: &str, part_index: usize, parts: usize) -> String {
+    if parts > 1 {
+        format!("{} ({}/{})", title, part_index + 1, parts)
+    } else {
+        String::from(title)
+    }
+}
+
 /// Type for creating a `NewExercise`.
 ///
 /// # Examples
 ///
 /// ```
-/// use wikitype_api::models::NewExerciseBuilder;
+/// use wikitype_api::models::{BuilderError, NewExerciseBuilder};
 ///
-/// // Create an updated exercise.
+/// // Create a new exercise.
 /// let new_exercise = NewExerciseBuilder::new()
 ///     .title("Albatross")
 ///     .body("Albatross body")
 ///     .topic(Some("It's a topic!"))
-///     .build();
+///     .build()
+///     .unwrap();
 ///
 /// assert_eq!(new_exercise.title, "Albatross");
 /// assert_eq!(new_exercise.body, "Albatross body");
 /// assert_eq!(new_exercise.topic, Some(String::from("It's a topic!")));
+///
+/// // Exercises need a title and a body.
+/// let missing_body = NewExerciseBuilder::new().title("Albatross").build();
+/// assert_eq!(missing_body.err(), Some(BuilderError::MissingField("body")));
 /// ```
 pub struct NewExerciseBuilder<'a> {
     id: String,
     title: Option<&'a str>,
     body: Option<&'a str>,
     topic: Option<&'a str>,
+    author_id: Option<&'a str>,
+    series: Option<(&'a str, i32)>,
 }
 
 impl<'a> NewExerciseBuilder<'a> {
@@ -83,6 +165,8 @@ impl<'a> NewExerciseBuilder<'a> {
             title: None,
             body: None,
             topic: None,
+            author_id: None,
+            series: None,
         }
     }
 
@@ -101,30 +185,60 @@ impl<'a> NewExerciseBuilder<'a> {
         self
     }
 
-    pub fn build(&mut self) -> NewExercise {
-        let title = self.title.expect("Missing exercise title.").to_string();
-        let body = self.body.expect("Missing exercise body.").to_string();
+    pub fn author_id(&mut self, author_id: Option<&'a str>) -> &mut NewExerciseBuilder<'a> {
+        self.author_id = author_id;
+        self
+    }
+
+    /// Makes the exercise the part at `part_index` (starting at 0) of the series `series_id`.
+    pub fn series(&mut self, series_id: &'a str, part_index: i32) -> &mut NewExerciseBuilder<'a> {
+        self.series = Some((series_id, part_index));
+        self
+    }
+
+    /// Builds the exercise, or fails with `BuilderError::MissingField` if the title or the body
+    /// was not set.
+    pub fn build(&mut self) -> Result<NewExercise, BuilderError> {
+        let title = self
+            .title
+            .ok_or(BuilderError::MissingField("title"))?
+            .to_string();
+        let body = self
+            .body
+            .ok_or(BuilderError::MissingField("body"))?
+            .to_string();
+        let difficulty = difficulty::difficulty(&body);
         let created_on = chrono::Utc::now().naive_utc();
         let modified_on = created_on;
-        NewExercise {
+        Ok(NewExercise {
             id: self.id.clone(),
             title,
             body,
             topic: self.topic.map(String::from),
             created_on,
             modified_on,
-        }
+            author_id: self.author_id.map(String::from),
+            series_id: self.series.map(|(series_id, _)| String::from(series_id)),
+            part_index: self.series.map(|(_, part_index)| part_index),
+            difficulty,
+        })
     }
 }
 
 /// Type for updating an `Exercise`.
-#[derive(AsChangeset, Identifiable, Clone)]
+///
+/// If `expected_version` is set, the update only applies to that version of the exercise (see
+/// `database::Error::Conflict`). `editor_id` is recorded as the author of the revision written by
+/// the update.
+#[derive(Identifiable, Clone)]
 #[table_name = "exercises"]
 pub struct UpdatedExercise<'a> {
     id: &'a str,
     pub title: Option<&'a str>,
     pub body: Option<&'a str>,
     pub topic: Option<Option<&'a str>>,
+    pub expected_version: Option<i32>,
+    pub editor_id: Option<&'a str>,
     modified_on: chrono::NaiveDateTime,
 }
 
@@ -134,12 +248,40 @@ impl<'a> UpdatedExercise<'a> {
     }
 }
 
+/// Columns set by an `UpdatedExercise`.
+type ExerciseChangeset<'a> = (
+    Option<diesel::dsl::Eq<exercises::title, &'a str>>,
+    Option<diesel::dsl::Eq<exercises::body, &'a str>>,
+    Option<diesel::dsl::Eq<exercises::difficulty, i32>>,
+    Option<diesel::dsl::Eq<exercises::topic, Option<&'a str>>>,
+    diesel::dsl::Eq<exercises::modified_on, NaiveDateTime>,
+);
+
+// NOTE: Implemented by hand (rather than derived) since `expected_version` and `editor_id` are not
+// columns and the difficulty is derived from the body.
+impl<'a> AsChangeset for &UpdatedExercise<'a> {
+    type Target = exercises::table;
+    type Changeset = <ExerciseChangeset<'a> as AsChangeset>::Changeset;
+
+    fn as_changeset(self) -> Self::Changeset {
+        (
+            self.title.map(|title| exercises::title.eq(title)),
+            self.body.map(|body| exercises::body.eq(body)),
+            self.body
+                .map(|body| exercises::difficulty.eq(difficulty::difficulty(body))),
+            self.topic.map(|topic| exercises::topic.eq(topic)),
+            exercises::modified_on.eq(self.modified_on),
+        )
+            .as_changeset()
+    }
+}
+
 /// Type for creating an `UpdatedExercise`.
 ///
 /// # Examples
 ///
 /// ```
-/// use wikitype_api::models::{Exercise, Uuid, UpdatedExerciseBuilder};
+/// use wikitype_api::models::{BuilderError, Exercise, Uuid, UpdatedExerciseBuilder};
 /// use chrono::NaiveDateTime;
 ///
 /// // Create an initial exercise.
@@ -150,16 +292,27 @@ impl<'a> UpdatedExercise<'a> {
 ///     topic: None,
 ///     created_on: NaiveDateTime::from_timestamp(0, 0),
 ///     modified_on: NaiveDateTime::from_timestamp(0, 0),
+///     author_id: None,
+///     version: 1,
+///     series_id: None,
+///     part_index: None,
+///     difficulty: None,
+///     deleted_on: None,
 /// };
 ///
 /// // Create an updated exercise.
 /// let updated_exercise = UpdatedExerciseBuilder::new(&exercise.id)
 ///     .title("Alabatross new")
 ///     .topic(Some("It's a topic!"))
-///     .build();
+///     .build()
+///     .unwrap();
 ///
 /// assert_eq!(exercise.id, updated_exercise.get_id());
 /// assert_eq!(None, updated_exercise.body);
+///
+/// // Updates need to change at least one field.
+/// let empty_update = UpdatedExerciseBuilder::new(&exercise.id).expected_version(1).build();
+/// assert_eq!(empty_update.err(), Some(BuilderError::EmptyUpdate));
 /// ```
 pub struct UpdatedExerciseBuilder<'a> {
     exercise: UpdatedExercise<'a>,
@@ -173,6 +326,8 @@ impl<'a> UpdatedExerciseBuilder<'a> {
                 title: None,
                 body: None,
                 topic: None,
+                expected_version: None,
+                editor_id: None,
                 modified_on: NaiveDateTime::from_timestamp(0, 0),
             },
         }
@@ -193,9 +348,887 @@ impl<'a> UpdatedExerciseBuilder<'a> {
         self
     }
 
-    pub fn build(&mut self) -> UpdatedExercise<'a> {
-        self.exercise.modified_on = chrono::Utc::now().naive_utc();
-        self.exercise.clone()
+    pub fn expected_version(&mut self, version: i32) -> &mut UpdatedExerciseBuilder<'a> {
+        self.exercise.expected_version = Some(version);
+        self
+    }
+
+    pub fn editor_id(&mut self, editor_id: Option<&'a str>) -> &mut UpdatedExerciseBuilder<'a> {
+        self.exercise.editor_id = editor_id;
+        self
+    }
+
+    /// Builds the update, or fails with `BuilderError::EmptyUpdate` if neither the title, the
+    /// body nor the topic was set.
+    pub fn build(&mut self) -> Result<UpdatedExercise<'a>, BuilderError> {
+        let exercise = &mut self.exercise;
+        if exercise.title.is_none() && exercise.body.is_none() && exercise.topic.is_none() {
+            return Err(BuilderError::EmptyUpdate);
+        }
+        exercise.modified_on = chrono::Utc::now().naive_utc();
+        Ok(exercise.clone())
+    }
+}
+
+/// A snapshot of the content of a version of an `Exercise`, written when the exercise is created
+/// or updated.
+#[derive(juniper::GraphQLObject, Queryable, Debug, Eq, PartialEq, Clone)]
+pub struct ExerciseRevision {
+    /// UUID string.
+    pub id: String,
+
+    /// Id of the exercise the revision is a version of.
+    pub exercise_id: String,
+
+    /// Version of the exercise the revision is a snapshot of.
+    pub version: i32,
+
+    /// Title of the exercise at this version.
+    pub title: String,
+
+    /// Content of the exercise at this version.
+    pub body: String,
+
+    /// Free-text topic of the exercise at this version.
+    pub topic: Option<String>,
+
+    /// Id of the user who made this version (if known and the user still exists).
+    pub author_id: Option<String>,
+
+    /// Date and time this version was made.
+    pub created_on: chrono::NaiveDateTime,
+}
+
+impl fmt::Display for ExerciseRevision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#?}", self)
+    }
+}
+
+/// Type for creating a new `ExerciseRevision`.
+#[derive(Insertable)]
+#[table_name = "exercise_revisions"]
+pub struct NewExerciseRevision {
+    id: String,
+    pub exercise_id: String,
+    pub version: i32,
+    pub title: String,
+    pub body: String,
+    pub topic: Option<String>,
+    pub author_id: Option<String>,
+    created_on: chrono::NaiveDateTime,
+}
+
+impl NewExerciseRevision {
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Type for creating a `NewExerciseRevision` from the current version of an `Exercise`.
+///
+/// # Examples
+///
+/// ```
+/// use wikitype_api::models::{Exercise, NewExerciseRevisionBuilder};
+/// use chrono::NaiveDateTime;
+///
+/// let exercise = Exercise {
+///     id: String::from("albatross"),
+///     title: String::from("Albatross"),
+///     body: String::from("Albatross body"),
+///     topic: None,
+///     created_on: NaiveDateTime::from_timestamp(0, 0),
+///     modified_on: NaiveDateTime::from_timestamp(60, 0),
+///     author_id: None,
+///     version: 2,
+///     series_id: None,
+///     part_index: None,
+///     difficulty: None,
+///     deleted_on: None,
+/// };
+///
+/// let new_revision = NewExerciseRevisionBuilder::new(&exercise)
+///     .author_id(Some("editor"))
+///     .build();
+///
+/// assert_eq!(new_revision.exercise_id, "albatross");
+/// assert_eq!(new_revision.version, 2);
+/// assert_eq!(new_revision.body, "Albatross body");
+/// assert_eq!(new_revision.author_id, Some(String::from("editor")));
+/// ```
+pub struct NewExerciseRevisionBuilder<'a> {
+    id: String,
+    exercise: &'a Exercise,
+    author_id: Option<&'a str>,
+}
+
+impl<'a> NewExerciseRevisionBuilder<'a> {
+    pub fn new(exercise: &'a Exercise) -> NewExerciseRevisionBuilder<'a> {
+        NewExerciseRevisionBuilder {
+            id: Uuid::new().to_string(),
+            exercise,
+            author_id: None,
+        }
+    }
+
+    pub fn author_id(&mut self, author_id: Option<&'a str>) -> &mut NewExerciseRevisionBuilder<'a> {
+        self.author_id = author_id;
+        self
+    }
+
+    pub fn build(&mut self) -> NewExerciseRevision {
+        NewExerciseRevision {
+            id: self.id.clone(),
+            exercise_id: self.exercise.id.clone(),
+            version: self.exercise.version,
+            title: self.exercise.title.clone(),
+            body: self.exercise.body.clone(),
+            topic: self.exercise.topic.clone(),
+            author_id: self.author_id.map(String::from),
+            created_on: self.exercise.modified_on,
+        }
+    }
+}
+
+/// An attempt at typing an `Exercise`.
+#[derive(juniper::GraphQLObject, Debug, PartialEq, Clone)]
+pub struct TypingAttempt {
+    /// UUID string.
+    pub id: String,
+
+    /// Id of the exercise that was typed.
+    pub exercise_id: String,
+
+    /// Date and time the typist started typing.
+    pub started_on: chrono::NaiveDateTime,
+
+    /// Date and time of the typist's last keystroke.
+    pub finished_on: chrono::NaiveDateTime,
+
+    /// Text as typed by the typist.
+    pub typed_text: String,
+
+    /// Number of keys pressed, including backspaces.
+    pub keystrokes: i32,
+
+    /// Number of characters typed which did not match the exercise at their position.
+    pub errors: i32,
+
+    /// Number of errors which the typist deleted again.
+    pub corrected_errors: i32,
+
+    /// Positions (in characters) of the exercise body at which the typist made errors.
+    pub error_positions: Vec<i32>,
+
+    /// Typed characters (including errors) in words (of five characters) per minute.
+    pub gross_wpm: f64,
+
+    /// Gross words per minute, less the uncorrected errors per minute.
+    pub net_wpm: f64,
+
+    /// Fraction of typed characters that were not errors, between 0 and 1.
+    pub accuracy: f64,
+}
+
+/// Columns of a `typing_attempts` row, in the order of the schema.
+type TypingAttemptRow = (
+    String,
+    String,
+    NaiveDateTime,
+    NaiveDateTime,
+    String,
+    i32,
+    i32,
+    f64,
+    f64,
+    f64,
+    i32,
+    String,
+);
+
+impl<DB> Queryable<typing_attempts::SqlType, DB> for TypingAttempt
+where
+    DB: Backend,
+    TypingAttemptRow: Queryable<typing_attempts::SqlType, DB>,
+{
+    type Row = <TypingAttemptRow as Queryable<typing_attempts::SqlType, DB>>::Row;
+
+    fn build(row: Self::Row) -> Self {
+        let (
+            id,
+            exercise_id,
+            started_on,
+            finished_on,
+            typed_text,
+            keystrokes,
+            errors,
+            gross_wpm,
+            accuracy,
+            net_wpm,
+            corrected_errors,
+            error_positions,
+        ) = TypingAttemptRow::build(row);
+        TypingAttempt {
+            id,
+            exercise_id,
+            started_on,
+            finished_on,
+            typed_text,
+            keystrokes,
+            errors,
+            corrected_errors,
+            error_positions: error_positions
+                .split(',')
+                .filter_map(|position| position.parse().ok())
+                .collect(),
+            gross_wpm,
+            net_wpm,
+            accuracy,
+        }
+    }
+}
+
+impl fmt::Display for TypingAttempt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#?}", self)
+    }
+}
+
+/// Type for recording a new `TypingAttempt`.
+#[derive(Insertable)]
+#[table_name = "typing_attempts"]
+pub struct NewTypingAttempt {
+    id: String,
+    pub exercise_id: String,
+    pub started_on: chrono::NaiveDateTime,
+    pub finished_on: chrono::NaiveDateTime,
+    pub typed_text: String,
+    pub keystrokes: i32,
+    pub errors: i32,
+    pub corrected_errors: i32,
+    error_positions: String,
+    pub gross_wpm: f64,
+    pub net_wpm: f64,
+    pub accuracy: f64,
+}
+
+impl NewTypingAttempt {
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Type for creating a `NewTypingAttempt` from the `Score` of its keystroke log.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use wikitype_api::models::NewTypingAttemptBuilder;
+/// use wikitype_api::scoring::{self, Key, Keystroke};
+///
+/// // Type "Hi" in 3 seconds.
+/// let log = vec![
+///     Keystroke { key: Key::Char('H'), elapsed_ms: 1_000 },
+///     Keystroke { key: Key::Char('i'), elapsed_ms: 3_000 },
+/// ];
+/// let score = scoring::score("Hi", &log).unwrap();
+///
+/// let started_on = NaiveDateTime::from_timestamp(0, 0);
+/// let new_attempt = NewTypingAttemptBuilder::new()
+///     .exercise_id("3f1c5b0e-6f1d-4f55-9a4c-0d2b9f1f6a11")
+///     .started_on(started_on)
+///     .score(&score)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(new_attempt.finished_on, NaiveDateTime::from_timestamp(3, 0));
+/// assert_eq!(new_attempt.typed_text, "Hi");
+/// assert_eq!(new_attempt.gross_wpm, 8.0);
+/// assert_eq!(new_attempt.accuracy, 1.0);
+/// ```
+pub struct NewTypingAttemptBuilder<'a> {
+    id: String,
+    exercise_id: Option<&'a str>,
+    started_on: Option<NaiveDateTime>,
+    score: Option<&'a Score>,
+}
+
+impl<'a> Default for NewTypingAttemptBuilder<'a> {
+    fn default() -> NewTypingAttemptBuilder<'a> {
+        NewTypingAttemptBuilder::new()
+    }
+}
+
+impl<'a> NewTypingAttemptBuilder<'a> {
+    pub fn new() -> NewTypingAttemptBuilder<'a> {
+        NewTypingAttemptBuilder {
+            id: Uuid::new().to_string(),
+            exercise_id: None,
+            started_on: None,
+            score: None,
+        }
+    }
+
+    pub fn exercise_id(&mut self, exercise_id: &'a str) -> &mut NewTypingAttemptBuilder<'a> {
+        self.exercise_id = Some(exercise_id);
+        self
+    }
+
+    pub fn started_on(&mut self, started_on: NaiveDateTime) -> &mut NewTypingAttemptBuilder<'a> {
+        self.started_on = Some(started_on);
+        self
+    }
+
+    pub fn score(&mut self, score: &'a Score) -> &mut NewTypingAttemptBuilder<'a> {
+        self.score = Some(score);
+        self
+    }
+
+    /// Builds the attempt, or fails with `BuilderError::MissingField` if the exercise id, the
+    /// start time or the score was not set.
+    pub fn build(&mut self) -> Result<NewTypingAttempt, BuilderError> {
+        let exercise_id = self
+            .exercise_id
+            .ok_or(BuilderError::MissingField("exercise_id"))?;
+        let started_on = self
+            .started_on
+            .ok_or(BuilderError::MissingField("started_on"))?;
+        let score = self.score.ok_or(BuilderError::MissingField("score"))?;
+        let error_positions: Vec<String> = score
+            .error_positions
+            .iter()
+            .map(|position| position.to_string())
+            .collect();
+        Ok(NewTypingAttempt {
+            id: self.id.clone(),
+            exercise_id: exercise_id.to_string(),
+            started_on,
+            finished_on: started_on + chrono::Duration::milliseconds(score.duration_ms),
+            typed_text: score.typed_text.clone(),
+            keystrokes: score.keystrokes as i32,
+            errors: score.errors as i32,
+            corrected_errors: score.corrected_errors as i32,
+            error_positions: error_positions.join(","),
+            gross_wpm: score.gross_wpm,
+            net_wpm: score.net_wpm,
+            accuracy: score.accuracy,
+        })
+    }
+}
+
+/// Role of a `User`, determining what the user may do.
+#[derive(juniper::GraphQLEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Role {
+    /// A regular user, who may edit their own exercises.
+    User,
+
+    /// A moderator, who may edit any exercise.
+    Moderator,
+
+    /// An administrator, who may do anything.
+    Admin,
+}
+
+impl Role {
+    /// Returns the name of the role as stored in the database.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Role, String> {
+        match s {
+            "user" => Ok(Role::User),
+            "moderator" => Ok(Role::Moderator),
+            "admin" => Ok(Role::Admin),
+            s => Err(format!("Unknown role {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A registered WikiType user.
+#[derive(juniper::GraphQLObject, Debug, Eq, PartialEq, Clone)]
+pub struct User {
+    /// UUID string.
+    pub id: String,
+
+    /// Unique name the user logs in with.
+    pub username: String,
+
+    /// Encoded hash of the user's password (see `auth::hash_password`).
+    #[graphql(skip)]
+    pub password_hash: String,
+
+    /// Role of the user.
+    pub role: Role,
+
+    /// Date and time of registration.
+    pub created_on: chrono::NaiveDateTime,
+}
+
+/// Columns of a `users` row, in the order of the schema.
+type UserRow = (String, String, String, String, NaiveDateTime);
+
+impl<DB> Queryable<users::SqlType, DB> for User
+where
+    DB: Backend,
+    UserRow: Queryable<users::SqlType, DB>,
+{
+    type Row = <UserRow as Queryable<users::SqlType, DB>>::Row;
+
+    fn build(row: Self::Row) -> Self {
+        let (id, username, password_hash, role, created_on) = UserRow::build(row);
+        User {
+            id,
+            username,
+            password_hash,
+            // NOTE: Unknown roles (e.g. from a newer schema) get the least privileges.
+            role: role.parse().unwrap_or(Role::User),
+            created_on,
+        }
+    }
+}
+
+impl fmt::Display for User {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#?}", self)
+    }
+}
+
+/// Type for registering a new `User`.
+#[derive(Insertable)]
+#[table_name = "users"]
+pub struct NewUser {
+    id: String,
+    pub username: String,
+    pub password_hash: String,
+    role: String,
+    created_on: chrono::NaiveDateTime,
+}
+
+impl NewUser {
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_role(&self) -> Role {
+        self.role.parse().unwrap_or(Role::User)
+    }
+}
+
+/// Type for creating a `NewUser`.
+///
+/// Users are regular users unless given another role.
+///
+/// # Examples
+///
+/// ```
+/// use wikitype_api::auth;
+/// use wikitype_api::models::{NewUserBuilder, Role};
+///
+/// let password_hash = auth::hash_password("correct horse battery staple").unwrap();
+/// let new_user = NewUserBuilder::new()
+///     .username("albatross")
+///     .password_hash(&password_hash)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(new_user.username, "albatross");
+/// assert_eq!(new_user.get_role(), Role::User);
+/// assert!(auth::verify_password(&new_user.password_hash, "correct horse battery staple"));
+/// ```
+pub struct NewUserBuilder<'a> {
+    id: String,
+    username: Option<&'a str>,
+    password_hash: Option<&'a str>,
+    role: Role,
+}
+
+impl<'a> Default for NewUserBuilder<'a> {
+    fn default() -> NewUserBuilder<'a> {
+        NewUserBuilder::new()
+    }
+}
+
+impl<'a> NewUserBuilder<'a> {
+    pub fn new() -> NewUserBuilder<'a> {
+        NewUserBuilder {
+            id: Uuid::new().to_string(),
+            username: None,
+            password_hash: None,
+            role: Role::User,
+        }
+    }
+
+    pub fn username(&mut self, username: &'a str) -> &mut NewUserBuilder<'a> {
+        self.username = Some(username);
+        self
+    }
+
+    pub fn password_hash(&mut self, password_hash: &'a str) -> &mut NewUserBuilder<'a> {
+        self.password_hash = Some(password_hash);
+        self
+    }
+
+    pub fn role(&mut self, role: Role) -> &mut NewUserBuilder<'a> {
+        self.role = role;
+        self
+    }
+
+    /// Builds the user, or fails with `BuilderError::MissingField` if the username or the
+    /// password hash was not set.
+    pub fn build(&mut self) -> Result<NewUser, BuilderError> {
+        let username = self
+            .username
+            .ok_or(BuilderError::MissingField("username"))?
+            .to_string();
+        let password_hash = self
+            .password_hash
+            .ok_or(BuilderError::MissingField("password_hash"))?
+            .to_string();
+        Ok(NewUser {
+            id: self.id.clone(),
+            username,
+            password_hash,
+            role: self.role.as_str().to_string(),
+            created_on: chrono::Utc::now().naive_utc(),
+        })
+    }
+}
+
+/// A topic in the tree of exercise categories (e.g. "Science" > "Biology" > "Birds").
+///
+/// See <https://en.wikipedia.org/wiki/Portal:Contents/Portals> for an idea.
+#[derive(juniper::GraphQLObject, Queryable, Debug, Eq, PartialEq, Clone)]
+pub struct Topic {
+    /// UUID string.
+    pub id: String,
+
+    /// Id of the topic this topic is a subtopic of, or `None` for top-level topics.
+    pub parent_id: Option<String>,
+
+    /// Unique, URL-friendly name of the topic (e.g. "natural-sciences").
+    pub slug: String,
+
+    /// Display name of the topic (e.g. "Natural sciences").
+    pub name: String,
+
+    /// Optional description of the exercises in the topic.
+    pub description: Option<String>,
+
+    /// Date and time of creation.
+    pub created_on: chrono::NaiveDateTime,
+}
+
+impl fmt::Display for Topic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#?}", self)
+    }
+}
+
+/// Type for creating a new `Topic`.
+#[derive(Insertable)]
+#[table_name = "topics"]
+pub struct NewTopic {
+    id: String,
+    pub parent_id: Option<String>,
+    pub slug: String,
+    pub name: String,
+    pub description: Option<String>,
+    created_on: chrono::NaiveDateTime,
+}
+
+impl NewTopic {
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Returns the slug of a topic named `name`: its lowercase words, joined by hyphens.
+///
+/// # Examples
+///
+/// ```
+/// use wikitype_api::models::slugify;
+///
+/// assert_eq!(slugify("Natural sciences"), "natural-sciences");
+/// assert_eq!(slugify(" Arts & Crafts! "), "arts-crafts");
+/// assert_eq!(slugify("Ørsted's law"), "ørsted-s-law");
+/// ```
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for word in name.split(|c: char| !c.is_alphanumeric()) {
+        if word.is_empty() {
+            continue;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.extend(word.chars().flat_map(char::to_lowercase));
+    }
+    slug
+}
+
+/// Type for creating a `NewTopic`.
+///
+/// Topics are top-level topics unless given a parent, and their slug is derived from their name
+/// (see `slugify`) unless given explicitly.
+///
+/// # Examples
+///
+/// ```
+/// use wikitype_api::models::NewTopicBuilder;
+///
+/// let new_topic = NewTopicBuilder::new()
+///     .name("Sea birds")
+///     .description(Some("Birds that live on the open sea."))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(new_topic.name, "Sea birds");
+/// assert_eq!(new_topic.slug, "sea-birds");
+/// assert_eq!(new_topic.parent_id, None);
+/// ```
+pub struct NewTopicBuilder<'a> {
+    id: String,
+    parent_id: Option<&'a str>,
+    slug: Option<&'a str>,
+    name: Option<&'a str>,
+    description: Option<&'a str>,
+}
+
+impl<'a> Default for NewTopicBuilder<'a> {
+    fn default() -> NewTopicBuilder<'a> {
+        NewTopicBuilder::new()
+    }
+}
+
+impl<'a> NewTopicBuilder<'a> {
+    pub fn new() -> NewTopicBuilder<'a> {
+        NewTopicBuilder {
+            id: Uuid::new().to_string(),
+            parent_id: None,
+            slug: None,
+            name: None,
+            description: None,
+        }
+    }
+
+    pub fn parent_id(&mut self, parent_id: Option<&'a str>) -> &mut NewTopicBuilder<'a> {
+        self.parent_id = parent_id;
+        self
+    }
+
+    pub fn slug(&mut self, slug: &'a str) -> &mut NewTopicBuilder<'a> {
+        self.slug = Some(slug);
+        self
+    }
+
+    pub fn name(&mut self, name: &'a str) -> &mut NewTopicBuilder<'a> {
+        self.name = Some(name);
+        self
+    }
+
+    pub fn description(&mut self, description: Option<&'a str>) -> &mut NewTopicBuilder<'a> {
+        self.description = description;
+        self
+    }
+
+    /// Builds the topic, or fails with `BuilderError::MissingField` if the name was not set.
+    pub fn build(&mut self) -> Result<NewTopic, BuilderError> {
+        let name = self
+            .name
+            .ok_or(BuilderError::MissingField("name"))?
+            .to_string();
+        let slug = self
+            .slug
+            .map(String::from)
+            .unwrap_or_else(|| slugify(&name));
+        Ok(NewTopic {
+            id: self.id.clone(),
+            parent_id: self.parent_id.map(String::from),
+            slug,
+            name,
+            description: self.description.map(String::from),
+            created_on: chrono::Utc::now().naive_utc(),
+        })
+    }
+}
+
+/// A topic within the tree of topics, along with its subtopics.
+#[derive(juniper::GraphQLObject, Debug, PartialEq, Clone)]
+pub struct TopicNode {
+    pub topic: Topic,
+
+    /// Number of exercises linked to the topic itself (not counting its subtopics).
+    pub exercise_count: i32,
+
+    /// Subtopics of the topic, sorted by name.
+    pub children: Vec<TopicNode>,
+}
+
+/// Arranges `topics`, along with their number of exercises, into a tree.
+///
+/// Returns the top-level topics, sorted by name. Topics whose parent is not among `topics` are
+/// treated as top-level topics.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use wikitype_api::models::{topic_tree, Topic};
+///
+/// let topic = |id: &str, parent_id: Option<&str>| Topic {
+///     id: String::from(id),
+///     parent_id: parent_id.map(String::from),
+///     slug: id.to_lowercase(),
+///     name: String::from(id),
+///     description: None,
+///     created_on: NaiveDateTime::from_timestamp(0, 0),
+/// };
+///
+/// let tree = topic_tree(vec![
+///     (topic("Birds", Some("Biology")), 2),
+///     (topic("Biology", None), 1),
+///     (topic("Arts", None), 0),
+/// ]);
+///
+/// assert_eq!(tree.len(), 2);
+/// assert_eq!(tree[0].topic.name, "Arts");
+/// assert_eq!(tree[1].topic.name, "Biology");
+/// assert_eq!(tree[1].exercise_count, 1);
+/// assert_eq!(tree[1].children[0].topic.name, "Birds");
+/// assert_eq!(tree[1].children[0].exercise_count, 2);
+/// ```
+pub fn topic_tree(topics: Vec<(Topic, i64)>) -> Vec<TopicNode> {
+    let ids: HashSet<String> = topics.iter().map(|(topic, _)| topic.id.clone()).collect();
+    let mut children: HashMap<Option<String>, Vec<(Topic, i64)>> = HashMap::new();
+    for (topic, exercise_count) in topics {
+        let parent_id = topic.parent_id.clone().filter(|id| ids.contains(id));
+        children
+            .entry(parent_id)
+            .or_default()
+            .push((topic, exercise_count));
+    }
+    topic_nodes(&mut children, None)
+}
+
+/// Removes the topics with the given parent from `children` and returns them as nodes, sorted by
+/// name.
+fn topic_nodes(
+    children: &mut HashMap<Option<String>, Vec<(Topic, i64)>>,
+    parent_id: Option<String>,
+) -> Vec<TopicNode> {
+    let mut nodes = Vec::new();
+    for (topic, exercise_count) in children.remove(&parent_id).unwrap_or_default() {
+        let subtopics = topic_nodes(children, Some(topic.id.clone()));
+        nodes.push(TopicNode {
+            topic,
+            exercise_count: exercise_count as i32,
+            children: subtopics,
+        });
+    }
+    nodes.sort_by(|a, b| a.topic.name.cmp(&b.topic.name));
+    nodes
+}
+
+/// A free-form tag of exercises (e.g. "numbers-heavy" or "quotes").
+#[derive(juniper::GraphQLObject, Queryable, Debug, Eq, PartialEq, Clone)]
+pub struct Tag {
+    /// UUID string.
+    pub id: String,
+
+    /// Unique name of the tag.
+    pub name: String,
+
+    /// Date and time the tag was first used.
+    pub created_on: chrono::NaiveDateTime,
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#?}", self)
+    }
+}
+
+/// Type for creating a new `Tag`.
+#[derive(Insertable)]
+#[table_name = "tags"]
+pub struct NewTag {
+    id: String,
+    pub name: String,
+    created_on: chrono::NaiveDateTime,
+}
+
+impl NewTag {
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Type for creating a `NewTag`.
+///
+/// # Examples
+///
+/// ```
+/// use wikitype_api::models::{BuilderError, NewTagBuilder};
+///
+/// let new_tag = NewTagBuilder::new().name("numbers-heavy").build().unwrap();
+/// let unnamed = NewTagBuilder::new().build();
+///
+/// assert_eq!(new_tag.name, "numbers-heavy");
+/// assert_eq!(unnamed.err(), Some(BuilderError::MissingField("name")));
+/// ```
+pub struct NewTagBuilder<'a> {
+    id: String,
+    name: Option<&'a str>,
+}
+
+impl<'a> Default for NewTagBuilder<'a> {
+    fn default() -> NewTagBuilder<'a> {
+        NewTagBuilder::new()
+    }
+}
+
+impl<'a> NewTagBuilder<'a> {
+    pub fn new() -> NewTagBuilder<'a> {
+        NewTagBuilder {
+            id: Uuid::new().to_string(),
+            name: None,
+        }
+    }
+
+    pub fn name(&mut self, name: &'a str) -> &mut NewTagBuilder<'a> {
+        self.name = Some(name);
+        self
+    }
+
+    /// Builds the tag, or fails with `BuilderError::MissingField` if the name was not set.
+    pub fn build(&mut self) -> Result<NewTag, BuilderError> {
+        let name = self
+            .name
+            .ok_or(BuilderError::MissingField("name"))?
+            .to_string();
+        Ok(NewTag {
+            id: self.id.clone(),
+            name,
+            created_on: chrono::Utc::now().naive_utc(),
+        })
     }
 }
 
@@ -0,0 +1,244 @@
+use crate::auth::Caller;
+use crate::database;
+use crate::models::{Exercise, Role};
+
+/// Actions on exercises which require authorization.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExerciseAction<'a> {
+    /// Creating a new exercise.
+    Create,
+
+    /// Updating the given exercise.
+    Update(&'a Exercise),
+
+    /// Deleting (i.e. moving to the trash) or restoring the given exercise.
+    Delete(&'a Exercise),
+
+    /// Permanently deleting the given exercise.
+    Purge(&'a Exercise),
+}
+
+/// Decides whether `caller` may perform `action`.
+///
+/// - Anonymous callers may do nothing, and get `Error::Unauthorized`.
+/// - Users may create exercises, and update or delete the exercises they authored.
+/// - Moderators and administrators may do anything, including purging exercises.
+///
+/// Callers who may not perform `action` get `Error::Forbidden`.
+///
+/// NOTE: Roles are taken from the caller's access token, so role changes only take effect once
+/// the caller's access token is refreshed.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDateTime;
+/// use wikitype_api::auth::Caller;
+/// use wikitype_api::database;
+/// use wikitype_api::models::{Exercise, Role};
+/// use wikitype_api::policy::{self, ExerciseAction};
+///
+/// let author = Caller { user_id: String::from("author"), role: Role::User };
+/// let other = Caller { user_id: String::from("other"), role: Role::User };
+/// let exercise = Exercise {
+///     id: String::from("3f1c5b0e-6f1d-4f55-9a4c-0d2b9f1f6a11"),
+///     title: String::from("Albatross"),
+///     body: String::from("Albatross body"),
+///     topic: None,
+///     created_on: NaiveDateTime::from_timestamp(0, 0),
+///     modified_on: NaiveDateTime::from_timestamp(0, 0),
+///     author_id: Some(author.user_id.clone()),
+///     version: 1,
+///     series_id: None,
+///     part_index: None,
+///     difficulty: None,
+///     deleted_on: None,
+/// };
+///
+/// let update = ExerciseAction::Update(&exercise);
+/// assert_eq!(policy::authorize_exercise(Some(&author), update), Ok(()));
+/// assert!(matches!(
+///     policy::authorize_exercise(Some(&other), update),
+///     Err(database::Error::Forbidden(_))
+/// ));
+/// assert!(matches!(
+///     policy::authorize_exercise(None, ExerciseAction::Create),
+///     Err(database::Error::Unauthorized(_))
+/// ));
+/// ```
+pub fn authorize_exercise(caller: Option<&Caller>, action: ExerciseAction) -> database::Result<()> {
+    let caller = caller
+        .ok_or_else(|| database::Error::Unauthorized(String::from("Authentication required")))?;
+    let permitted = match action {
+        ExerciseAction::Create => true,
+        ExerciseAction::Update(exercise) | ExerciseAction::Delete(exercise) => {
+            is_moderator(caller) || is_author(caller, exercise)
+        }
+        ExerciseAction::Purge(_) => is_moderator(caller),
+    };
+    if permitted {
+        Ok(())
+    } else {
+        Err(database::Error::Forbidden(String::from(match action {
+            ExerciseAction::Create => "You may not create exercises",
+            ExerciseAction::Update(_) => "You may only update your own exercises",
+            ExerciseAction::Delete(_) => "You may only delete your own exercises",
+            ExerciseAction::Purge(_) => "Only moderators may purge exercises",
+        })))
+    }
+}
+
+/// Decides whether `caller` may create topics.
+///
+/// The topic tree is curated by moderators and administrators. Anonymous callers get
+/// `Error::Unauthorized`, and users get `Error::Forbidden`. Linking topics to an exercise is an
+/// update of the exercise (see `authorize_exercise`).
+pub fn authorize_topic(caller: Option<&Caller>) -> database::Result<()> {
+    let caller = caller
+        .ok_or_else(|| database::Error::Unauthorized(String::from("Authentication required")))?;
+    if is_moderator(caller) {
+        Ok(())
+    } else {
+        Err(database::Error::Forbidden(String::from(
+            "Only moderators may create topics",
+        )))
+    }
+}
+
+/// Decides whether `caller` may list trashed exercises.
+///
+/// Trashed exercises are only visible to moderators and administrators. Anonymous callers get
+/// `Error::Unauthorized`, and users get `Error::Forbidden`.
+pub fn authorize_trash(caller: Option<&Caller>) -> database::Result<()> {
+    let caller = caller
+        .ok_or_else(|| database::Error::Unauthorized(String::from("Authentication required")))?;
+    if is_moderator(caller) {
+        Ok(())
+    } else {
+        Err(database::Error::Forbidden(String::from(
+            "Only moderators may list trashed exercises",
+        )))
+    }
+}
+
+/// Returns whether `caller` may moderate every resource.
+fn is_moderator(caller: &Caller) -> bool {
+    match caller.role {
+        Role::Moderator | Role::Admin => true,
+        Role::User => false,
+    }
+}
+
+/// Returns whether `caller` is the author of `exercise`.
+fn is_author(caller: &Caller, exercise: &Exercise) -> bool {
+    exercise.author_id.as_ref() == Some(&caller.user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn caller(user_id: &str, role: Role) -> Caller {
+        Caller {
+            user_id: String::from(user_id),
+            role,
+        }
+    }
+
+    fn exercise(author_id: Option<&str>) -> Exercise {
+        Exercise {
+            id: String::from("1"),
+            title: String::from("Albatross"),
+            body: String::from("Albatross body"),
+            topic: None,
+            created_on: NaiveDateTime::from_timestamp(0, 0),
+            modified_on: NaiveDateTime::from_timestamp(0, 0),
+            author_id: author_id.map(String::from),
+            version: 1,
+            series_id: None,
+            part_index: None,
+            difficulty: None,
+            deleted_on: None,
+        }
+    }
+
+    /// Test which roles may update, delete and purge whose exercises.
+    #[test]
+    fn authorize_exercise_actions() {
+        let authored = exercise(Some("author"));
+        let orphaned = exercise(None);
+        let author = caller("author", Role::User);
+        let user = caller("user", Role::User);
+        let moderator = caller("moderator", Role::Moderator);
+        let admin = caller("admin", Role::Admin);
+
+        for exercise in &[&authored, &orphaned] {
+            for action in &[
+                ExerciseAction::Update(exercise),
+                ExerciseAction::Delete(exercise),
+                ExerciseAction::Purge(exercise),
+            ] {
+                assert_eq!(authorize_exercise(Some(&moderator), *action), Ok(()));
+                assert_eq!(authorize_exercise(Some(&admin), *action), Ok(()));
+                assert!(matches!(
+                    authorize_exercise(Some(&user), *action),
+                    Err(database::Error::Forbidden(_))
+                ));
+                assert!(matches!(
+                    authorize_exercise(None, *action),
+                    Err(database::Error::Unauthorized(_))
+                ));
+            }
+        }
+        assert_eq!(
+            authorize_exercise(Some(&author), ExerciseAction::Delete(&authored)),
+            Ok(())
+        );
+        assert!(authorize_exercise(Some(&author), ExerciseAction::Delete(&orphaned)).is_err());
+        assert!(matches!(
+            authorize_exercise(Some(&author), ExerciseAction::Purge(&authored)),
+            Err(database::Error::Forbidden(_))
+        ));
+        assert_eq!(
+            authorize_exercise(Some(&user), ExerciseAction::Create),
+            Ok(())
+        );
+    }
+
+    /// Test that only moderators and administrators may create topics.
+    #[test]
+    fn authorize_topic_creation() {
+        assert_eq!(
+            authorize_topic(Some(&caller("moderator", Role::Moderator))),
+            Ok(())
+        );
+        assert_eq!(authorize_topic(Some(&caller("admin", Role::Admin))), Ok(()));
+        assert!(matches!(
+            authorize_topic(Some(&caller("user", Role::User))),
+            Err(database::Error::Forbidden(_))
+        ));
+        assert!(matches!(
+            authorize_topic(None),
+            Err(database::Error::Unauthorized(_))
+        ));
+    }
+
+    /// Test that only moderators and administrators may list trashed exercises.
+    #[test]
+    fn authorize_trash_listing() {
+        assert_eq!(
+            authorize_trash(Some(&caller("moderator", Role::Moderator))),
+            Ok(())
+        );
+        assert_eq!(authorize_trash(Some(&caller("admin", Role::Admin))), Ok(()));
+        assert!(matches!(
+            authorize_trash(Some(&caller("user", Role::User))),
+            Err(database::Error::Forbidden(_))
+        ));
+        assert!(matches!(
+            authorize_trash(None),
+            Err(database::Error::Unauthorized(_))
+        ));
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Number of characters per "word" when measuring typing speed.
+///
+/// See <https://en.wikipedia.org/wiki/Words_per_minute>.
+pub const CHARACTERS_PER_WORD: f64 = 5.0;
+
+/// A key pressed by the typist.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Key {
+    /// A key typing a character.
+    Char(char),
+
+    /// The backspace key, deleting the last typed character.
+    Backspace,
+}
+
+/// A key press, along with the time it occurred.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Keystroke {
+    /// The pressed key.
+    pub key: Key,
+
+    /// Number of milliseconds between the start of the attempt and the key press.
+    pub elapsed_ms: i64,
+}
+
+/// Error type returned when a keystroke log cannot be scored.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The keystroke at the given index occurred before the start of the attempt.
+    NegativeTime(usize),
+
+    /// The keystroke at the given index occurred before the previous keystroke.
+    Unordered(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NegativeTime(i) => write!(f, "Keystroke {} occurred before the start", i),
+            Error::Unordered(i) => write!(f, "Keystroke {} occurred before keystroke {}", i, i - 1),
+        }
+    }
+}
+
+/// The score of an attempt at typing a text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Score {
+    /// Text as typed by the typist, i.e. after applying every keystroke.
+    pub typed_text: String,
+
+    /// Number of keys pressed, including backspaces.
+    pub keystrokes: usize,
+
+    /// Number of characters typed which did not match the text at their position.
+    pub errors: usize,
+
+    /// Number of errors which were deleted again.
+    pub corrected_errors: usize,
+
+    /// Number of characters in `typed_text` which do not match the text at their position.
+    pub uncorrected_errors: usize,
+
+    /// Positions (in characters) of the text at which the typist made at least one error,
+    /// in ascending order.
+    pub error_positions: Vec<usize>,
+
+    /// Number of milliseconds between the start of the attempt and the last keystroke.
+    pub duration_ms: i64,
+
+    /// Typed characters (including errors) in words per minute.
+    pub gross_wpm: f64,
+
+    /// Gross words per minute, less the uncorrected errors per minute (but at least 0).
+    pub net_wpm: f64,
+
+    /// Fraction of typed characters that were not errors, between 0 and 1 (or 0 if no characters
+    /// were typed).
+    pub accuracy: f64,
+}
+
+/// Scores an attempt at typing `text` by replaying its keystroke log.
+///
+/// The score depends on nothing but `text` and `log`, so it can be recalculated at any time.
+///
+/// # Examples
+///
+/// ```
+/// use wikitype_api::scoring::{self, Key, Keystroke};
+///
+/// // Type "cat" as "cst", correct the error and finish within 12 seconds.
+/// let keys = vec![
+///     Key::Char('c'),
+///     Key::Char('s'),
+///     Key::Backspace,
+///     Key::Char('a'),
+///     Key::Char('t'),
+/// ];
+/// let log: Vec<Keystroke> = keys
+///     .into_iter()
+///     .zip(1..)
+///     .map(|(key, i)| Keystroke { key, elapsed_ms: i * 2_400 })
+///     .collect();
+///
+/// let score = scoring::score("cat", &log).unwrap();
+/// assert_eq!(score.typed_text, "cat");
+/// assert_eq!(score.keystrokes, 5);
+/// assert_eq!((score.errors, score.corrected_errors, score.uncorrected_errors), (1, 1, 0));
+/// assert_eq!(score.error_positions, vec![1]);
+/// assert_eq!(score.duration_ms, 12_000);
+/// assert_eq!(score.gross_wpm, 4.0);
+/// assert_eq!(score.net_wpm, 4.0);
+/// assert_eq!(score.accuracy, 0.75);
+/// ```
+pub fn score(text: &str, log: &[Keystroke]) -> Result<Score, Error> {
+    let expected: Vec<char> = text.chars().collect();
+
+    // Typed characters, along with whether each one was an error.
+    let mut typed: Vec<(char, bool)> = Vec::new();
+    let mut characters = 0;
+    let mut errors = 0;
+    let mut corrected_errors = 0;
+    let mut error_positions = BTreeSet::new();
+    let mut duration_ms = 0;
+
+    for (i, keystroke) in log.iter().enumerate() {
+        if keystroke.elapsed_ms < 0 {
+            return Err(Error::NegativeTime(i));
+        }
+        if keystroke.elapsed_ms < duration_ms {
+            return Err(Error::Unordered(i));
+        }
+        duration_ms = keystroke.elapsed_ms;
+
+        match keystroke.key {
+            Key::Char(c) => {
+                let position = typed.len();
+                let error = expected.get(position) != Some(&c);
+                if error {
+                    errors += 1;
+                    error_positions.insert(position);
+                }
+                characters += 1;
+                typed.push((c, error));
+            }
+            Key::Backspace => {
+                if let Some((_, true)) = typed.pop() {
+                    corrected_errors += 1;
+                }
+            }
+        }
+    }
+
+    let uncorrected_errors = typed.iter().filter(|(_, error)| *error).count();
+    let minutes = duration_ms as f64 / 60_000.0;
+    let (gross_wpm, net_wpm) = if minutes > 0.0 {
+        let gross_wpm = characters as f64 / CHARACTERS_PER_WORD / minutes;
+        let net_wpm = (gross_wpm - uncorrected_errors as f64 / minutes).max(0.0);
+        (gross_wpm, net_wpm)
+    } else {
+        (0.0, 0.0)
+    };
+    let accuracy = if characters > 0 {
+        (characters - errors) as f64 / characters as f64
+    } else {
+        0.0
+    };
+
+    Ok(Score {
+        typed_text: typed.iter().map(|(c, _)| c).collect(),
+        keystrokes: log.len(),
+        errors,
+        corrected_errors,
+        uncorrected_errors,
+        error_positions: error_positions.into_iter().collect(),
+        duration_ms,
+        gross_wpm,
+        net_wpm,
+        accuracy,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a keystroke log typing `keys` (where `<` is a backspace) one key per second.
+    fn log(keys: &str) -> Vec<Keystroke> {
+        keys.chars()
+            .zip(1..)
+            .map(|(c, i)| Keystroke {
+                key: if c == '<' {
+                    Key::Backspace
+                } else {
+                    Key::Char(c)
+                },
+                elapsed_ms: i * 1_000,
+            })
+            .collect()
+    }
+
+    /// Test scoring uncorrected errors, extra characters and unfinished texts.
+    #[test]
+    fn score_uncorrected_errors() {
+        // Two uncorrected errors, typed in 60 seconds.
+        let mut keystrokes = log("Tha quick brovn");
+        keystrokes.last_mut().unwrap().elapsed_ms = 60_000;
+        let score = super::score("The quick brown fox", &keystrokes).unwrap();
+        assert_eq!(score.typed_text, "Tha quick brovn");
+        assert_eq!(
+            (
+                score.errors,
+                score.corrected_errors,
+                score.uncorrected_errors
+            ),
+            (2, 0, 2)
+        );
+        assert_eq!(score.error_positions, vec![2, 13]);
+        assert_eq!(score.gross_wpm, 3.0);
+        assert_eq!(score.net_wpm, 1.0);
+        assert!((score.accuracy - 13.0 / 15.0).abs() < 1e-12);
+
+        // Characters typed past the end of the text are errors.
+        let score = super::score("cat", &log("cats")).unwrap();
+        assert_eq!(score.error_positions, vec![3]);
+        assert_eq!(score.uncorrected_errors, 1);
+
+        // Net WPM is never negative.
+        let score = super::score("cat", &log("xyz")).unwrap();
+        assert_eq!(score.net_wpm, 0.0);
+        assert_eq!(score.accuracy, 0.0);
+    }
+
+    /// Test that errors stay errors after being corrected, and that deleting correct characters
+    /// is not an error.
+    #[test]
+    fn score_corrected_errors() {
+        let score = super::score("cat", &log("<xx<<ca<at")).unwrap();
+        assert_eq!(score.typed_text, "cat");
+        assert_eq!(score.keystrokes, 10);
+        assert_eq!(
+            (
+                score.errors,
+                score.corrected_errors,
+                score.uncorrected_errors
+            ),
+            (2, 2, 0)
+        );
+        assert_eq!(score.error_positions, vec![0, 1]);
+        assert_eq!(score.accuracy, 4.0 / 6.0);
+    }
+
+    /// Test that empty logs score zero and that unordered logs are rejected.
+    #[test]
+    fn score_keystroke_logs() {
+        let score = super::score("cat", &[]).unwrap();
+        assert_eq!(score.typed_text, "");
+        assert_eq!(
+            (score.gross_wpm, score.net_wpm, score.accuracy),
+            (0.0, 0.0, 0.0)
+        );
+
+        let mut keystrokes = log("cat");
+        keystrokes[2].elapsed_ms = 1_500;
+        assert_eq!(super::score("cat", &keystrokes), Err(Error::Unordered(2)));
+        keystrokes[0].elapsed_ms = -1;
+        assert_eq!(
+            super::score("cat", &keystrokes),
+            Err(Error::NegativeTime(0))
+        );
+    }
+}
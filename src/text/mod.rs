@@ -0,0 +1,498 @@
+use std::fmt;
+use std::str::FromStr;
+use unicode_normalization::UnicodeNormalization;
+
+/// Splitting of long texts into exercise-sized passages.
+pub mod passages;
+
+/// A step of `normalize`.
+///
+/// Steps are applied in the order in which they are declared here.
+#[derive(juniper::GraphQLEnum, Clone, Copy, Debug, Eq, PartialEq)]
+#[graphql(name = "TextNormalizationStep")]
+pub enum Step {
+    /// Removes bracketed citations and maintenance notes, e.g. `[1]`, `[note 2]` or
+    /// `[citation needed]`.
+    Citations,
+
+    /// Composes characters according to [Unicode Normalization Form C], so that e.g. an `e`
+    /// followed by a combining acute accent becomes a single `é`.
+    ///
+    /// [Unicode Normalization Form C]: https://unicode.org/reports/tr15/
+    Nfc,
+
+    /// Folds typographic quotes (e.g. `‘’` and `“”`) into `'` and `"`.
+    Quotes,
+
+    /// Replaces hyphens, en dashes and minus signs by `-`, and em dashes by ` - `.
+    Dashes,
+
+    /// Replaces the ellipsis character `…` by `...`.
+    Ellipses,
+
+    /// Collapses runs of whitespace into a single space, removes invisible characters (e.g. soft
+    /// hyphens) and trims the text.
+    Whitespace,
+}
+
+impl Step {
+    /// All steps, in the order in which they are applied.
+    pub const ALL: [Step; 6] = [
+        Step::Citations,
+        Step::Nfc,
+        Step::Quotes,
+        Step::Dashes,
+        Step::Ellipses,
+        Step::Whitespace,
+    ];
+
+    /// Returns the name of the step as used in `NormalizeOptions` strings.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Step::Citations => "citations",
+            Step::Nfc => "nfc",
+            Step::Quotes => "quotes",
+            Step::Dashes => "dashes",
+            Step::Ellipses => "ellipses",
+            Step::Whitespace => "whitespace",
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Step {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Step, String> {
+        Step::ALL
+            .iter()
+            .cloned()
+            .find(|step| step.as_str() == s)
+            .ok_or_else(|| format!("Unknown text normalization step {:?}", s))
+    }
+}
+
+/// Steps applied by `normalize`.
+///
+/// Options are written as a comma-separated list of steps (e.g. `"quotes,dashes"`), or as `"all"`
+/// or `"none"`. By default, all steps are applied.
+///
+/// # Examples
+///
+/// ```
+/// use wikitype_api::text::{NormalizeOptions, Step};
+///
+/// let options: NormalizeOptions = "quotes, dashes".parse().unwrap();
+///
+/// assert!(options.contains(Step::Quotes));
+/// assert!(!options.contains(Step::Citations));
+/// assert_eq!(options.to_string(), "quotes,dashes");
+/// assert_eq!("all".parse(), Ok(NormalizeOptions::default()));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NormalizeOptions {
+    steps: Vec<Step>,
+}
+
+impl NormalizeOptions {
+    /// Options applying every step.
+    pub fn all() -> NormalizeOptions {
+        NormalizeOptions {
+            steps: Step::ALL.to_vec(),
+        }
+    }
+
+    /// Options applying no step (i.e. leaving texts unchanged).
+    pub fn none() -> NormalizeOptions {
+        NormalizeOptions { steps: Vec::new() }
+    }
+
+    /// Returns whether `step` is applied.
+    pub fn contains(&self, step: Step) -> bool {
+        self.steps.contains(&step)
+    }
+
+    /// Applies `step` as well.
+    pub fn with(mut self, step: Step) -> NormalizeOptions {
+        if !self.contains(step) {
+            self.steps.push(step);
+            self.steps
+                .sort_by_key(|s| Step::ALL.iter().position(|a| a == s));
+        }
+        self
+    }
+
+    /// Does not apply `step`.
+    pub fn without(mut self, step: Step) -> NormalizeOptions {
+        self.steps.retain(|&s| s != step);
+        self
+    }
+}
+
+impl Default for NormalizeOptions {
+    fn default() -> NormalizeOptions {
+        NormalizeOptions::all()
+    }
+}
+
+impl fmt::Display for NormalizeOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.steps.is_empty() {
+            return f.write_str("none");
+        }
+        let steps: Vec<&str> = self.steps.iter().map(|step| step.as_str()).collect();
+        f.write_str(&steps.join(","))
+    }
+}
+
+impl FromStr for NormalizeOptions {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<NormalizeOptions, String> {
+        match s.trim() {
+            "all" => Ok(NormalizeOptions::all()),
+            "none" | "" => Ok(NormalizeOptions::none()),
+            steps => steps
+                .split(',')
+                .map(|step| step.trim().parse())
+                .try_fold(NormalizeOptions::none(), |options, step| {
+                    step.map(|step| options.with(step))
+                }),
+        }
+    }
+}
+
+/// A change made by a step of `normalize`.
+#[derive(juniper::GraphQLObject, Clone, Debug, Eq, PartialEq)]
+#[graphql(name = "TextChange")]
+pub struct Change {
+    /// Step which changed the text.
+    pub step: Step,
+
+    /// Number of places in which the step changed the text.
+    pub count: i32,
+}
+
+/// A text normalized by `normalize`, along with what was changed.
+#[derive(juniper::GraphQLObject, Clone, Debug, Eq, PartialEq)]
+#[graphql(name = "NormalizedText")]
+pub struct Normalized {
+    /// The normalized text.
+    pub text: String,
+
+    /// Changes made to the text, one per step which changed it (in the order of the steps).
+    pub changes: Vec<Change>,
+}
+
+impl Normalized {
+    /// Returns whether the text was changed.
+    pub fn is_changed(&self) -> bool {
+        !self.changes.is_empty()
+    }
+}
+
+/// Normalizes `text` into text that can be typed on a regular keyboard, applying the steps of
+/// `options`.
+///
+/// # Examples
+///
+/// ```
+/// use wikitype_api::text::{normalize, Change, NormalizeOptions, Step};
+///
+/// let normalized = normalize(
+///     "“Albatrosses” are large seabirds[1] \u{2014} really…",
+///     &NormalizeOptions::default(),
+/// );
+///
+/// assert_eq!(normalized.text, "\"Albatrosses\" are large seabirds - really...");
+/// assert_eq!(
+///     normalized.changes,
+///     vec![
+///         Change { step: Step::Citations, count: 1 },
+///         Change { step: Step::Quotes, count: 2 },
+///         Change { step: Step::Dashes, count: 1 },
+///         Change { step: Step::Ellipses, count: 1 },
+///     ]
+/// );
+/// ```
+pub fn normalize(text: &str, options: &NormalizeOptions) -> Normalized {
+    let mut text = String::from(text);
+    let mut changes = Vec::new();
+    for &step in &options.steps {
+        let (normalized, count) = match step {
+            Step::Citations => remove_citations(&text),
+            Step::Nfc => compose(&text),
+            Step::Quotes => fold_quotes(&text),
+            Step::Dashes => replace_dashes(&text),
+            Step::Ellipses => replace_ellipses(&text),
+            Step::Whitespace => collapse_whitespace(&text),
+        };
+        if count > 0 {
+            changes.push(Change {
+                step,
+                count: count as i32,
+            });
+            text = normalized;
+        }
+    }
+    Normalized { text, changes }
+}
+
+/// Maximum number of characters between the brackets of a citation.
+const MAX_CITATION_LENGTH: usize = 40;
+
+/// Removes bracketed citations (along with the spaces before them).
+fn remove_citations(text: &str) -> (String, usize) {
+    let mut result = String::with_capacity(text.len());
+    let mut count = 0;
+    let mut rest = text;
+    while let Some(start) = rest.find('[') {
+        let citation = rest[start + 1..]
+            .find([']', '[', '\n'])
+            .filter(|&end| rest[start + 1 + end..].starts_with(']'))
+            .map(|end| &rest[start + 1..start + 1 + end])
+            .filter(|content| is_citation(content));
+        match citation {
+            Some(content) => {
+                result.push_str(rest[..start].trim_end_matches([' ', '\u{a0}']));
+                rest = &rest[start + content.len() + 2..];
+                count += 1;
+            }
+            None => {
+                result.push_str(&rest[..=start]);
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    (result, count)
+}
+
+/// Returns whether `content` (between brackets) is a citation or maintenance note.
+fn is_citation(content: &str) -> bool {
+    let content = content.trim().to_lowercase();
+    if content.is_empty() || content.chars().count() > MAX_CITATION_LENGTH {
+        return false;
+    }
+    // Reference numbers, e.g. "1", "2, 3" or "4–6".
+    let is_reference = content.chars().any(|c| c.is_ascii_digit())
+        && content
+            .chars()
+            .all(|c| c.is_ascii_digit() || ", -–".contains(c));
+    // Footnote markers, e.g. "a" or "note 2".
+    let is_footnote = (content.len() <= 2 && content.chars().all(|c| c.is_ascii_lowercase()))
+        || ["note ", "nb ", "n "]
+            .iter()
+            .any(|prefix| content.starts_with(prefix) && !content[prefix.len()..].contains(' '));
+    // Maintenance notes, e.g. "citation needed", "dubious – discuss" or "who?".
+    let is_note = ["needed", "citation", "dubious", "clarif", "verif"]
+        .iter()
+        .any(|word| content.contains(word))
+        || (content.ends_with('?') && content.split_whitespace().count() <= 3);
+    is_reference || is_footnote || is_note
+}
+
+/// Composes characters according to Unicode Normalization Form C.
+fn compose(text: &str) -> (String, usize) {
+    let composed: String = text.nfc().collect();
+    let count = if composed == text {
+        0
+    } else {
+        // Each composition merges at least two characters into one.
+        (text.chars().count() - composed.chars().count()).max(1)
+    };
+    (composed, count)
+}
+
+/// Replaces each character of `text` for which `replacement` returns a replacement.
+fn replace_chars(
+    text: &str,
+    replacement: impl Fn(char) -> Option<&'static str>,
+) -> (String, usize) {
+    let mut result = String::with_capacity(text.len());
+    let mut count = 0;
+    for c in text.chars() {
+        match replacement(c) {
+            Some(replacement) => {
+                result.push_str(replacement);
+                count += 1;
+            }
+            None => result.push(c),
+        }
+    }
+    (result, count)
+}
+
+/// Folds typographic quotes into `'` and `"`.
+fn fold_quotes(text: &str) -> (String, usize) {
+    replace_chars(text, |c| match c {
+        '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}' | '\u{2032}' => Some("'"),
+        '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{201f}' | '\u{2033}' => Some("\""),
+        _ => None,
+    })
+}
+
+/// Replaces hyphens, en dashes and minus signs by `-`, and em dashes by ` - ` (without doubling
+/// surrounding spaces).
+fn replace_dashes(text: &str) -> (String, usize) {
+    let mut result = String::with_capacity(text.len());
+    let mut count = 0;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2212}' => {
+                result.push('-');
+                count += 1;
+            }
+            '\u{2014}' | '\u{2015}' => {
+                if !result.is_empty() && !result.ends_with(char::is_whitespace) {
+                    result.push(' ');
+                }
+                result.push('-');
+                if chars.peek().is_some_and(|next| !next.is_whitespace()) {
+                    result.push(' ');
+                }
+                count += 1;
+            }
+            c => result.push(c),
+        }
+    }
+    (result, count)
+}
+
+/// Replaces the ellipsis character by `...`.
+fn replace_ellipses(text: &str) -> (String, usize) {
+    replace_chars(text, |c| if c == '\u{2026}' { Some("...") } else { None })
+}
+
+/// Returns whether `c` is invisible and should be removed (soft hyphens, zero-width spaces and
+/// byte order marks).
+fn is_invisible(c: char) -> bool {
+    matches!(c, '\u{ad}' | '\u{200b}' | '\u{2060}' | '\u{feff}')
+}
+
+/// Collapses runs of whitespace into a single space, removes invisible characters and trims the
+/// text.
+fn collapse_whitespace(text: &str) -> (String, usize) {
+    let mut result = String::with_capacity(text.len());
+    let mut count = 0;
+    // The run of whitespace (and invisible characters) not yet written.
+    let mut run = String::new();
+    for c in text.chars() {
+        if c.is_whitespace() || is_invisible(c) {
+            run.push(c);
+            continue;
+        }
+        if !run.is_empty() {
+            let has_space = run.chars().any(char::is_whitespace);
+            if has_space && !result.is_empty() {
+                result.push(' ');
+            }
+            if run != " " || result.is_empty() {
+                count += 1;
+            }
+            run.clear();
+        }
+        result.push(c);
+    }
+    if !run.is_empty() {
+        count += 1;
+    }
+    (result, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test removing citations while keeping other bracketed text.
+    #[test]
+    fn remove_bracketed_citations() {
+        assert_eq!(
+            remove_citations(
+                "Albatrosses [1] are large[2, 3] seabirds.[note 4][a][citation needed]"
+            ),
+            (String::from("Albatrosses are large seabirds."), 5)
+        );
+        assert_eq!(
+            remove_citations("They live long [who?] [Dubious – discuss] [4–6]."),
+            (String::from("They live long."), 3)
+        );
+        let text = "Arrays [sic] are indexed [from zero] [, ] [[links]] [unclosed";
+        assert_eq!(remove_citations(text), (String::from(text), 0));
+    }
+
+    /// Test composing, folding and replacing characters.
+    #[test]
+    fn replace_typographic_characters() {
+        assert_eq!(compose("Cafe\u{301}"), (String::from("Café"), 1));
+        assert_eq!(compose("Café"), (String::from("Café"), 0));
+        assert_eq!(
+            fold_quotes("‘It’s’ „so“ “good”"),
+            (String::from("'It's' \"so\" \"good\""), 7)
+        );
+        assert_eq!(
+            replace_dashes("1914–1918, \u{2212}5 and\u{2014}sadly \u{2014} war\u{2014}"),
+            (String::from("1914-1918, -5 and - sadly - war -"), 5)
+        );
+        assert_eq!(
+            replace_ellipses("Well… no."),
+            (String::from("Well... no."), 1)
+        );
+    }
+
+    /// Test collapsing whitespace and removing invisible characters.
+    #[test]
+    fn collapse_whitespace_runs() {
+        assert_eq!(
+            collapse_whitespace("  Alba\u{ad}tross\u{a0}is \t\n large. "),
+            (String::from("Albatross is large."), 5)
+        );
+        assert_eq!(
+            collapse_whitespace("Albatross is large."),
+            (String::from("Albatross is large."), 0)
+        );
+    }
+
+    /// Test applying only the configured steps.
+    #[test]
+    fn normalize_configured_steps() {
+        let text = "“Albatross”  [1]";
+        let options: NormalizeOptions = "whitespace,quotes".parse().unwrap();
+        assert_eq!(
+            options,
+            NormalizeOptions::none()
+                .with(Step::Quotes)
+                .with(Step::Whitespace)
+        );
+        let normalized = normalize(text, &options);
+        assert_eq!(normalized.text, "\"Albatross\" [1]");
+        assert_eq!(
+            normalized.changes,
+            vec![
+                Change {
+                    step: Step::Quotes,
+                    count: 2
+                },
+                Change {
+                    step: Step::Whitespace,
+                    count: 1
+                },
+            ]
+        );
+
+        let normalized = normalize(text, &NormalizeOptions::none());
+        assert_eq!(normalized.text, text);
+        assert!(!normalized.is_changed());
+        assert!("quotes,smileys".parse::<NormalizeOptions>().is_err());
+        assert_eq!(
+            NormalizeOptions::all().without(Step::Nfc).to_string(),
+            "citations,quotes,dashes,ellipses,whitespace"
+        );
+    }
+}
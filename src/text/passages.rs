@@ -0,0 +1,185 @@
+/// Splits the paragraphs of an article into passages of between `min_length` and `max_length`
+/// characters (as far as possible without splitting sentences).
+///
+/// Consecutive paragraphs are joined into one passage while they fit; paragraphs that are too
+/// long are split between sentences. Leftover text that is too short to make a passage on its own
+/// is dropped.
+pub fn split_passages(paragraphs: &[String], min_length: usize, max_length: usize) -> Vec<String> {
+    pack(paragraphs, max_length)
+        .into_iter()
+        .filter(|passage| char_count(passage) >= min_length)
+        .collect()
+}
+
+/// Splits `text` into passages of about `target_length` characters, between paragraphs (separated
+/// by blank lines) or sentences where possible.
+///
+/// Unlike `split_passages`, no text is dropped: a last passage shorter than half of
+/// `target_length` is appended to the passage before it. Whitespace within paragraphs is
+/// collapsed.
+///
+/// # Examples
+///
+/// ```
+/// use wikitype_api::text::passages::segment;
+///
+/// let text = "Albatrosses are large seabirds.\n\nThey range widely. They glide.";
+///
+/// assert_eq!(
+///     segment(text, 35),
+///     vec!["Albatrosses are large seabirds.", "They range widely. They glide."]
+/// );
+/// assert_eq!(segment(text, 1000), vec![text.replace("\n\n", " ")]);
+/// ```
+pub fn segment(text: &str, target_length: usize) -> Vec<String> {
+    let mut paragraphs = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in text.lines().chain(std::iter::once("")) {
+        if line.trim().is_empty() {
+            if !paragraph.is_empty() {
+                paragraphs.push(paragraph.join(" "));
+                paragraph.clear();
+            }
+        } else {
+            paragraph.extend(line.split_whitespace());
+        }
+    }
+    let mut passages = pack(&paragraphs, target_length);
+    if passages.len() > 1 && char_count(&passages[passages.len() - 1]) < target_length / 2 {
+        let last = passages.pop().unwrap_or_default();
+        if let Some(passage) = passages.last_mut() {
+            passage.push(' ');
+            passage.push_str(&last);
+        }
+    }
+    passages
+}
+
+/// Joins and splits paragraphs into passages of at most `max_length` characters (unless a single
+/// word is longer), without dropping any text.
+fn pack(paragraphs: &[String], max_length: usize) -> Vec<String> {
+    let mut passages = Vec::new();
+    let mut passage = String::new();
+    let mut passage_length = 0;
+    for paragraph in paragraphs {
+        let units = if char_count(paragraph) <= max_length {
+            vec![paragraph.as_str()]
+        } else {
+            sentences(paragraph)
+                .into_iter()
+                .flat_map(|sentence| split_words(sentence, max_length))
+                .collect()
+        };
+        for unit in units {
+            let unit_length = char_count(unit);
+            if passage_length > 0 && passage_length + 1 + unit_length > max_length {
+                passages.push(passage.clone());
+                passage.clear();
+                passage_length = 0;
+            }
+            if passage_length > 0 {
+                passage.push(' ');
+                passage_length += 1;
+            }
+            passage.push_str(unit);
+            passage_length += unit_length;
+        }
+    }
+    if passage_length > 0 {
+        passages.push(passage);
+    }
+    passages
+}
+
+/// Returns the number of characters in `s`.
+fn char_count(s: &str) -> usize {
+    s.chars().count()
+}
+
+/// Splits `text` into sentences (after a `.`, `!` or `?` followed by a space and an uppercase
+/// letter, digit or quote).
+fn sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    for window in chars.windows(3) {
+        if let [(_, end), (space, ' '), (_, next)] = window {
+            let ends_sentence = ".!?".contains(*end)
+                && (next.is_uppercase() || next.is_ascii_digit() || "\"“'‘(".contains(*next));
+            if ends_sentence {
+                sentences.push(&text[start..*space]);
+                start = space + 1;
+            }
+        }
+    }
+    sentences.push(&text[start..]);
+    sentences
+}
+
+/// Splits `sentence` between words into pieces of at most `max_length` characters (unless a
+/// single word is longer).
+fn split_words(sentence: &str, max_length: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut last_space = None;
+    let mut length = 0;
+    for (i, c) in sentence.char_indices() {
+        if length >= max_length {
+            if let Some(space) = last_space {
+                pieces.push(&sentence[start..space]);
+                start = space + 1;
+                length = char_count(&sentence[start..i]);
+                last_space = None;
+            }
+        }
+        if c == ' ' {
+            last_space = Some(i);
+        }
+        length += 1;
+    }
+    pieces.push(&sentence[start..]);
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test joining short paragraphs and splitting long paragraphs between sentences.
+    #[test]
+    fn split_paragraphs_into_passages() {
+        let paragraphs: Vec<String> = vec![
+            "Albatrosses are large seabirds.",
+            "They range widely.",
+            "Albatrosses are efficient in the air. They cover great distances. Dr. Smith agrees.",
+            "Short.",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        assert_eq!(
+            split_passages(&paragraphs, 20, 50),
+            vec![
+                "Albatrosses are large seabirds. They range widely.",
+                "Albatrosses are efficient in the air.",
+                "They cover great distances. Dr. Smith agrees.",
+            ]
+        );
+        assert_eq!(
+            segment(
+                "Albatrosses are large seabirds.\n  \nThey range\nwidely. Albatrosses are \
+                 efficient in the air. They cover great distances.\n\nShort.",
+                50
+            ),
+            vec![
+                "Albatrosses are large seabirds. They range widely.",
+                "Albatrosses are efficient in the air.",
+                "They cover great distances. Short.",
+            ]
+        );
+        assert_eq!(
+            split_words("one two three four", 9),
+            vec!["one two", "three", "four"]
+        );
+    }
+}
@@ -0,0 +1,329 @@
+use crate::database;
+
+use std::fmt;
+
+/// Default maximum number of characters in the title of an exercise.
+const DEFAULT_MAX_TITLE_LENGTH: usize = 255;
+
+/// Default maximum number of characters in the body of an exercise.
+const DEFAULT_MAX_BODY_LENGTH: usize = 20_000;
+
+/// Default maximum number of characters in the topic of an exercise.
+const DEFAULT_MAX_TOPIC_LENGTH: usize = 255;
+
+/// Maximum number of characters in titles and topics that the database can store.
+pub const MAX_COLUMN_LENGTH: usize = 255;
+
+/// Limits on the fields of exercises, as checked by an `ExerciseValidator`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ExerciseLimits {
+    /// Maximum number of characters in a title (at most `MAX_COLUMN_LENGTH`).
+    pub max_title_length: usize,
+
+    /// Maximum number of characters in a body.
+    pub max_body_length: usize,
+
+    /// Maximum number of characters in a topic (at most `MAX_COLUMN_LENGTH`).
+    pub max_topic_length: usize,
+}
+
+impl Default for ExerciseLimits {
+    fn default() -> ExerciseLimits {
+        ExerciseLimits {
+            max_title_length: DEFAULT_MAX_TITLE_LENGTH,
+            max_body_length: DEFAULT_MAX_BODY_LENGTH,
+            max_topic_length: DEFAULT_MAX_TOPIC_LENGTH,
+        }
+    }
+}
+
+/// Reason a field failed validation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Violation {
+    /// The field is empty or consists of whitespace only.
+    Empty,
+
+    /// The field has more characters than allowed.
+    TooLong,
+
+    /// The field contains a control character (other than line breaks and tabs in bodies).
+    ControlCharacter,
+
+    /// The body contains a character that cannot be typed on a regular keyboard.
+    UntypeableCharacter,
+}
+
+impl Violation {
+    /// Returns the code of the violation, as reported in the `validation_error` extension of
+    /// GraphQL errors.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Violation::Empty => "empty",
+            Violation::TooLong => "too_long",
+            Violation::ControlCharacter => "control_character",
+            Violation::UntypeableCharacter => "untypeable_character",
+        }
+    }
+}
+
+/// A field of an input which failed validation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FieldError {
+    /// Name of the field, as in the GraphQL input (e.g. `title`).
+    pub field: &'static str,
+
+    /// Reason the field failed validation.
+    pub violation: Violation,
+
+    /// Description of the failure.
+    pub message: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Validator of the fields of an exercise to be created or updated.
+///
+/// Every checked field is validated, so that all failing fields are reported at once by `finish`
+/// as an `Error::ValidationError`. Bodies should be checked once normalized (see
+/// `text::normalize`), since normalization folds many untypeable characters into typeable ones.
+///
+/// # Examples
+///
+/// ```
+/// use wikitype_api::database;
+/// use wikitype_api::validation::{ExerciseLimits, ExerciseValidator, Violation};
+///
+/// let limits = ExerciseLimits::default();
+///
+/// let valid = ExerciseValidator::new(&limits)
+///     .title("Albatross")
+///     .body("Albatrosses are large seabirds.")
+///     .topic(Some("Birds"))
+///     .finish();
+/// assert_eq!(valid, Ok(()));
+///
+/// let invalid = ExerciseValidator::new(&limits)
+///     .title(" ")
+///     .body("Albatrosses \u{1f426} are large seabirds.")
+///     .finish();
+/// let errors = match invalid {
+///     Err(database::Error::ValidationError(errors)) => errors,
+///     _ => panic!("Expected a validation error"),
+/// };
+/// assert_eq!(errors.len(), 2);
+/// assert_eq!((errors[0].field, errors[0].violation), ("title", Violation::Empty));
+/// assert_eq!(
+///     (errors[1].field, errors[1].violation),
+///     ("body", Violation::UntypeableCharacter)
+/// );
+/// ```
+pub struct ExerciseValidator<'a> {
+    limits: &'a ExerciseLimits,
+    errors: Vec<FieldError>,
+}
+
+impl<'a> ExerciseValidator<'a> {
+    pub fn new(limits: &'a ExerciseLimits) -> ExerciseValidator<'a> {
+        ExerciseValidator {
+            limits,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Checks that the title is not blank, not too long and free of control characters.
+    pub fn title(&mut self, title: &str) -> &mut ExerciseValidator<'a> {
+        self.check("title", title, self.limits.max_title_length, false)
+    }
+
+    /// Checks that the body is not blank, not too long, free of control characters (except line
+    /// breaks and tabs) and typeable.
+    pub fn body(&mut self, body: &str) -> &mut ExerciseValidator<'a> {
+        self.check("body", body, self.limits.max_body_length, true)
+    }
+
+    /// Checks that the topic, if any, is not blank, not too long and free of control characters.
+    pub fn topic(&mut self, topic: Option<&str>) -> &mut ExerciseValidator<'a> {
+        match topic {
+            Some(topic) => self.check("topic", topic, self.limits.max_topic_length, false),
+            None => self,
+        }
+    }
+
+    /// Returns every failing field as an `Error::ValidationError`, if any.
+    pub fn finish(&mut self) -> database::Result<()> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(database::Error::ValidationError(self.errors.clone()))
+        }
+    }
+
+    /// Checks a text field, reporting at most one failure per field.
+    fn check(
+        &mut self,
+        field: &'static str,
+        text: &str,
+        max_length: usize,
+        is_body: bool,
+    ) -> &mut ExerciseValidator<'a> {
+        let error = check_text(field, text, max_length, is_body);
+        // NOTE: Fields may be checked repeatedly (e.g. the parts of a series), so the same failure
+        // is only reported once.
+        if let Some(error) = error {
+            let is_reported = self
+                .errors
+                .iter()
+                .any(|e| e.field == error.field && e.violation == error.violation);
+            if !is_reported {
+                self.errors.push(error);
+            }
+        }
+        self
+    }
+}
+
+/// Returns the first failure of a text field, if any.
+fn check_text(
+    field: &'static str,
+    text: &str,
+    max_length: usize,
+    is_body: bool,
+) -> Option<FieldError> {
+    let error = |violation, message| {
+        Some(FieldError {
+            field,
+            violation,
+            message,
+        })
+    };
+    if text.trim().is_empty() {
+        return error(Violation::Empty, format!("{} must not be empty", field));
+    }
+    let length = text.chars().count();
+    if length > max_length {
+        return error(
+            Violation::TooLong,
+            format!(
+                "{} must have at most {} characters, but has {}",
+                field, max_length, length
+            ),
+        );
+    }
+    for (position, c) in text.chars().enumerate() {
+        if c.is_control() && !(is_body && (c == '\n' || c == '\t')) {
+            return error(
+                Violation::ControlCharacter,
+                format!(
+                    "{} contains the control character U+{:04X} at position {}",
+                    field, c as u32, position
+                ),
+            );
+        }
+        if is_body && !c.is_control() && !is_typeable(c) {
+            return error(
+                Violation::UntypeableCharacter,
+                format!(
+                    "{} contains the untypeable character {:?} (U+{:04X}) at position {}",
+                    field, c, c as u32, position
+                ),
+            );
+        }
+    }
+    None
+}
+
+/// Returns whether `c` can be typed on a regular (e.g. US, UK, German, French or Spanish)
+/// keyboard: printable ASCII, the printable characters of Latin-1 and Latin Extended-A (e.g. `é`,
+/// `ß` or `ł`) and the euro sign.
+fn is_typeable(c: char) -> bool {
+    c.is_ascii_graphic()
+        || c == ' '
+        || c == '€'
+        || (('\u{a1}'..='\u{17f}').contains(&c) && c != '\u{ad}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the failures of an exercise with the given fields.
+    fn validate(title: &str, body: &str, topic: Option<&str>) -> Vec<(&'static str, Violation)> {
+        let limits = ExerciseLimits {
+            max_title_length: 10,
+            max_body_length: 20,
+            max_topic_length: 5,
+        };
+        match ExerciseValidator::new(&limits)
+            .title(title)
+            .body(body)
+            .topic(topic)
+            .finish()
+        {
+            Ok(()) => Vec::new(),
+            Err(database::Error::ValidationError(errors)) => errors
+                .into_iter()
+                .map(|error| (error.field, error.violation))
+                .collect(),
+            Err(e) => panic!("Unexpected error {:?}", e),
+        }
+    }
+
+    /// Test that valid fields pass, and that every failing field is reported.
+    #[test]
+    fn validate_exercise_fields() {
+        assert_eq!(validate("Albatross", "Große Vögel.\nÉté.", None), vec![]);
+        assert_eq!(
+            validate("Pi", "3.14 € ¡Sí!\t(π)", Some("Maths")),
+            vec![("body", Violation::UntypeableCharacter)]
+        );
+        assert_eq!(
+            validate("", "\n\t ", Some(" ")),
+            vec![
+                ("title", Violation::Empty),
+                ("body", Violation::Empty),
+                ("topic", Violation::Empty),
+            ]
+        );
+        assert_eq!(
+            validate("Albatrosses", "Albatrosses are large.", Some("Birds!")),
+            vec![
+                ("title", Violation::TooLong),
+                ("body", Violation::TooLong),
+                ("topic", Violation::TooLong),
+            ]
+        );
+        assert_eq!(
+            validate("Alba\ntross", "Large\u{7} birds.", Some("B\tird")),
+            vec![
+                ("title", Violation::ControlCharacter),
+                ("body", Violation::ControlCharacter),
+                ("topic", Violation::ControlCharacter),
+            ]
+        );
+        assert_eq!(
+            validate("Albatross", "Large\u{a0}seabirds.", None),
+            vec![("body", Violation::UntypeableCharacter)]
+        );
+    }
+
+    /// Test that a failure is only reported once when a field is checked repeatedly.
+    #[test]
+    fn report_repeated_failures_once() {
+        let limits = ExerciseLimits::default();
+        let result = ExerciseValidator::new(&limits)
+            .body("")
+            .body(" ")
+            .body("Birds.")
+            .finish();
+        let errors = match result {
+            Err(database::Error::ValidationError(errors)) => errors,
+            _ => panic!("Expected a validation error"),
+        };
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "body must not be empty");
+    }
+}